@@ -0,0 +1,202 @@
+//! Per-capture level diagnostics
+//!
+//! Bad transcripts are often caused by input levels rather than the model: a clipping
+//! preamp, a mic that is muted or barely audible, or a DC offset from cheap hardware.
+//! `analyze` summarizes a recording so these show up in logs, history and `doctor`.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Samples at or above this magnitude count as clipped. Slightly below full scale
+/// because resampling smooths flat-topped peaks.
+const CLIP_LEVEL: f32 = 0.99;
+
+/// Below -60 dBFS counts as silence
+const SILENCE_LEVEL: f32 = 0.001;
+
+/// Window for silence detection
+const SILENCE_FRAME_MS: usize = 10;
+
+/// Warn when more than this share of samples is clipped
+const CLIPPED_WARN_PCT: f32 = 0.1;
+
+/// Warn when the loudest sample stays below -30 dBFS
+const LOW_PEAK_WARN: f32 = 0.03;
+
+/// Warn above this absolute mean
+const DC_OFFSET_WARN: f32 = 0.05;
+
+/// Level summary of one recording
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CaptureStats {
+    /// Highest absolute sample value (0.0..=1.0)
+    pub peak: f32,
+    /// RMS over the whole recording
+    pub rms: f32,
+    /// Percentage of samples at full scale
+    pub clipped_pct: f32,
+    /// Mean sample value (should be ~0)
+    pub dc_offset: f32,
+    /// Longest continuous run below -60 dBFS
+    pub longest_silence_ms: u64,
+    /// Recording length
+    pub duration_ms: u64,
+}
+
+impl CaptureStats {
+    /// Human-readable warnings for levels likely to hurt transcription
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.duration_ms == 0 {
+            return warnings;
+        }
+
+        if self.peak < SILENCE_LEVEL {
+            warnings.push(
+                "No signal from the microphone, check that it is unmuted and selected".to_string(),
+            );
+            return warnings;
+        }
+        if self.clipped_pct > CLIPPED_WARN_PCT {
+            warnings.push(format!(
+                "Input is clipping ({:.1}% of samples), lower the mic gain",
+                self.clipped_pct
+            ));
+        }
+        if self.peak < LOW_PEAK_WARN {
+            warnings.push(format!(
+                "Input level is very low (peak {:.1} dBFS), raise the mic gain",
+                20.0 * self.peak.log10()
+            ));
+        }
+        if self.dc_offset.abs() > DC_OFFSET_WARN {
+            warnings.push(format!(
+                "Large DC offset ({:+.3}), the input device may be faulty",
+                self.dc_offset
+            ));
+        }
+
+        warnings
+    }
+}
+
+impl fmt::Display for CaptureStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "peak {:.3}, rms {:.3}, clipped {:.2}%, dc {:+.4}, longest silence {}ms",
+            self.peak, self.rms, self.clipped_pct, self.dc_offset, self.longest_silence_ms
+        )
+    }
+}
+
+/// Analyze mono samples at `sample_rate`
+pub fn analyze(samples: &[f32], sample_rate: u32) -> CaptureStats {
+    if samples.is_empty() || sample_rate == 0 {
+        return CaptureStats::default();
+    }
+
+    let n = samples.len() as f64;
+    let mut peak = 0.0f32;
+    let mut clipped = 0usize;
+    let mut sum = 0.0f64;
+    let mut sum_sq = 0.0f64;
+    for &s in samples {
+        peak = peak.max(s.abs());
+        if s.abs() >= CLIP_LEVEL {
+            clipped += 1;
+        }
+        sum += s as f64;
+        sum_sq += (s as f64) * (s as f64);
+    }
+
+    let frame_len = (sample_rate as usize * SILENCE_FRAME_MS / 1000).max(1);
+    let mut longest = 0usize;
+    let mut run = 0usize;
+    for frame in samples.chunks(frame_len) {
+        if frame.iter().all(|s| s.abs() < SILENCE_LEVEL) {
+            run += frame.len();
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+
+    let to_ms = |count: usize| (count as u64 * 1000) / sample_rate as u64;
+    CaptureStats {
+        peak,
+        rms: (sum_sq / n).sqrt() as f32,
+        clipped_pct: (clipped as f64 * 100.0 / n) as f32,
+        dc_offset: (sum / n) as f32,
+        longest_silence_ms: to_ms(longest),
+        duration_ms: to_ms(samples.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (i as f32 * 0.05).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_speech_level_has_no_warnings() {
+        let stats = analyze(&tone(0.3, 16000), 16000);
+        assert!((stats.peak - 0.3).abs() < 0.01);
+        assert!((stats.rms - 0.3 / 2f32.sqrt()).abs() < 0.01);
+        assert_eq!(stats.clipped_pct, 0.0);
+        assert!(stats.dc_offset.abs() < 0.01);
+        assert_eq!(stats.duration_ms, 1000);
+        assert!(stats.warnings().is_empty(), "{:?}", stats.warnings());
+    }
+
+    #[test]
+    fn test_clipping_detected() {
+        let samples: Vec<f32> = tone(3.0, 16000).iter().map(|s| s.clamp(-1.0, 1.0)).collect();
+        let stats = analyze(&samples, 16000);
+        assert!(stats.clipped_pct > 10.0);
+        assert!(stats.warnings()[0].contains("clipping"));
+    }
+
+    #[test]
+    fn test_longest_silence() {
+        let mut samples = tone(0.3, 1600);
+        samples.extend(vec![0.0; 8000]);
+        samples.extend(tone(0.3, 1600));
+        samples.extend(vec![0.0; 3200]);
+
+        let stats = analyze(&samples, 16000);
+        assert_eq!(stats.longest_silence_ms, 500);
+    }
+
+    #[test]
+    fn test_silent_and_quiet_input() {
+        let muted = analyze(&vec![0.0; 16000], 16000);
+        assert_eq!(muted.longest_silence_ms, 1000);
+        let warnings = muted.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("No signal"));
+
+        let quiet = analyze(&tone(0.01, 16000), 16000);
+        assert!(quiet.warnings()[0].contains("very low"));
+    }
+
+    #[test]
+    fn test_dc_offset() {
+        let samples: Vec<f32> = tone(0.3, 16000).iter().map(|s| s + 0.1).collect();
+        let stats = analyze(&samples, 16000);
+        assert!((stats.dc_offset - 0.1).abs() < 0.01);
+        assert!(stats.warnings().iter().any(|w| w.contains("DC offset")));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let stats = analyze(&[], 16000);
+        assert_eq!(stats, CaptureStats::default());
+        assert!(stats.warnings().is_empty());
+    }
+}
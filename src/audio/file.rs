@@ -0,0 +1,442 @@
+//! Audio file loading shared by `transcribe-file`, the benchmark runner and the daemon
+//!
+//! Decoding uses symphonia (pure Rust). The container is detected from the file contents,
+//! not the extension, so mislabelled files still load. Supported: WAV, MP3, FLAC,
+//! OGG/Vorbis, OGG/Opus and M4A/MP4 (AAC). Symphonia has no Opus codec, so Opus packets
+//! from its Ogg demuxer go through libopus (audiopus).
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CODEC_TYPE_NULL, CODEC_TYPE_OPUS, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader, Track};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use tracing::{info, warn};
+
+use super::TARGET_SAMPLE_RATE;
+
+/// Load an audio file and convert it to 16kHz mono f32 samples (Whisper input format).
+pub fn load_audio(path: &Path) -> Result<Vec<f32>> {
+    if !path.exists() {
+        anyhow::bail!("File not found: {}", path.display());
+    }
+
+    info!("Loading audio file: {}", path.display());
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let (samples, sample_rate, channels) =
+        decode(stream).with_context(|| format!("Failed to decode {}", path.display()))?;
+
+    to_whisper_input(samples, sample_rate, channels)
+}
+
+/// Decode an in-memory audio file (any supported container) to 16kHz mono f32 samples.
+pub fn load_audio_bytes(data: Vec<u8>) -> Result<Vec<f32>> {
+    let stream = MediaSourceStream::new(Box::new(std::io::Cursor::new(data)), Default::default());
+    let (samples, sample_rate, channels) = decode(stream)?;
+
+    to_whisper_input(samples, sample_rate, channels)
+}
+
+/// Sample encoding of headerless PCM input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PcmFormat {
+    U8,
+    S16le,
+    S32le,
+    F32le,
+}
+
+impl PcmFormat {
+    fn bytes_per_sample(self) -> usize {
+        match self {
+            PcmFormat::U8 => 1,
+            PcmFormat::S16le => 2,
+            PcmFormat::S32le | PcmFormat::F32le => 4,
+        }
+    }
+}
+
+impl std::str::FromStr for PcmFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "u8" => Ok(PcmFormat::U8),
+            "s16le" => Ok(PcmFormat::S16le),
+            "s32le" => Ok(PcmFormat::S32le),
+            "f32le" => Ok(PcmFormat::F32le),
+            _ => anyhow::bail!("Unknown PCM format '{}'. Use: u8, s16le, s32le, f32le", s),
+        }
+    }
+}
+
+/// Convert headerless interleaved PCM bytes to 16kHz mono f32 samples.
+pub fn load_raw_pcm(
+    data: &[u8],
+    format: PcmFormat,
+    sample_rate: u32,
+    channels: u16,
+) -> Result<Vec<f32>> {
+    if sample_rate == 0 || channels == 0 {
+        anyhow::bail!("Raw PCM input needs a non-zero sample rate and channel count");
+    }
+
+    let width = format.bytes_per_sample();
+    let frame_bytes = width * channels as usize;
+    let usable = data.len() - data.len() % frame_bytes;
+    if usable < data.len() {
+        warn!(
+            "Ignoring {} trailing byte(s) that do not form a full frame",
+            data.len() - usable
+        );
+    }
+
+    let samples: Vec<f32> = data[..usable]
+        .chunks_exact(width)
+        .map(|b| match format {
+            PcmFormat::U8 => (b[0] as f32 - 128.0) / 128.0,
+            PcmFormat::S16le => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            PcmFormat::S32le => {
+                i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0
+            },
+            PcmFormat::F32le => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        })
+        .collect();
+
+    to_whisper_input(samples, sample_rate, channels)
+}
+
+/// Downmix and resample decoded audio to Whisper's 16kHz mono input
+fn to_whisper_input(samples: Vec<f32>, sample_rate: u32, channels: u16) -> Result<Vec<f32>> {
+    info!(
+        "Decoded {} samples ({} ch, {}Hz)",
+        samples.len(),
+        channels,
+        sample_rate
+    );
+
+    let mono_samples = downmix(samples, channels);
+    let audio_16k = resample_to_target(mono_samples, sample_rate)?;
+
+    info!(
+        "Final audio: {} samples ({:.2}s at {}Hz)",
+        audio_16k.len(),
+        audio_16k.len() as f32 / TARGET_SAMPLE_RATE as f32,
+        TARGET_SAMPLE_RATE
+    );
+
+    Ok(audio_16k)
+}
+
+/// Decode the first audio track of a media stream into interleaved f32 samples.
+/// Returns (samples, sample_rate, channels).
+fn decode(stream: MediaSourceStream) -> Result<(Vec<f32>, u32, u16)> {
+    // No extension hint: probe the container from its magic bytes
+    let probed = symphonia::default::get_probe()
+        .format(
+            &Hint::new(),
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .context("Unrecognized or unsupported audio format")?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .context("No audio track found")?;
+
+    if track.codec_params.codec == CODEC_TYPE_OPUS {
+        let track = track.clone();
+        return decode_opus(format.as_mut(), &track);
+    }
+
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+    let mut channels = track
+        .codec_params
+        .channels
+        .map(|c| c.count() as u16)
+        .unwrap_or(1);
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .context("Unsupported audio codec")?;
+
+    let mut samples = Vec::new();
+    let mut sample_buf: Option<SampleBuffer<f32>> = None;
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // Symphonia signals end of stream with an EOF I/O error
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break;
+            },
+            Err(e) => return Err(e.into()),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(e)) => {
+                // Corrupt frames are skipped, matching common player behavior
+                warn!("Skipping undecodable packet: {}", e);
+                continue;
+            },
+            Err(e) => return Err(e.into()),
+        };
+
+        let spec = *decoded.spec();
+        sample_rate = spec.rate;
+        channels = spec.channels.count() as u16;
+
+        let buf = match sample_buf.as_mut() {
+            Some(buf) if buf.capacity() >= decoded.capacity() * spec.channels.count() => buf,
+            _ => sample_buf.insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+        };
+        buf.copy_interleaved_ref(decoded);
+        samples.extend_from_slice(buf.samples());
+    }
+
+    if sample_rate == 0 {
+        anyhow::bail!("Audio stream has no sample rate");
+    }
+
+    Ok((samples, sample_rate, channels))
+}
+
+/// Decode an Opus track with libopus. Output is always 48kHz; the encoder delay
+/// (pre-skip) is dropped from the start.
+fn decode_opus(format: &mut dyn FormatReader, track: &Track) -> Result<(Vec<f32>, u32, u16)> {
+    use audiopus::coder::Decoder;
+    use audiopus::packet::Packet;
+    use audiopus::{Channels, SampleRate};
+
+    /// Longest Opus frame: 120ms at 48kHz
+    const MAX_FRAME: usize = 5760;
+
+    let channels = match track.codec_params.channels.map(|c| c.count()) {
+        Some(1) | None => Channels::Mono,
+        Some(2) => Channels::Stereo,
+        Some(n) => anyhow::bail!("Opus audio with {} channels is not supported", n),
+    };
+    let count = channels as usize;
+    let mut decoder =
+        Decoder::new(SampleRate::Hz48000, channels).context("Failed to create Opus decoder")?;
+
+    let mut samples = Vec::new();
+    let mut frame = vec![0.0f32; MAX_FRAME * count];
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break;
+            },
+            Err(e) => return Err(e.into()),
+        };
+        if packet.track_id() != track.id {
+            continue;
+        }
+
+        let decoded = Packet::try_from(packet.buf())
+            .and_then(|data| decoder.decode_float(Some(data), (&mut frame).try_into()?, false));
+        match decoded {
+            Ok(len) => samples.extend_from_slice(&frame[..len * count]),
+            Err(e) => warn!("Skipping undecodable packet: {}", e),
+        }
+    }
+
+    let pre_skip = track.codec_params.delay.unwrap_or(0) as usize * count;
+    samples.drain(..pre_skip.min(samples.len()));
+
+    Ok((samples, 48000, count as u16))
+}
+
+/// Average interleaved channels down to mono (a trailing partial frame is averaged as-is)
+fn downmix(samples: Vec<f32>, channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return samples;
+    }
+
+    samples
+        .chunks(channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// Resample mono audio to 16kHz using sinc interpolation (no-op if already 16kHz)
+fn resample_to_target(samples: Vec<f32>, source_rate: u32) -> Result<Vec<f32>> {
+    use rubato::{
+        Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
+    };
+
+    if source_rate == TARGET_SAMPLE_RATE || samples.is_empty() {
+        return Ok(samples);
+    }
+
+    info!("Resampling {}Hz -> {}Hz", source_rate, TARGET_SAMPLE_RATE);
+
+    let params = SincInterpolationParameters {
+        sinc_len: 256,
+        f_cutoff: 0.95,
+        interpolation: SincInterpolationType::Linear,
+        oversampling_factor: 256,
+        window: WindowFunction::BlackmanHarris2,
+    };
+
+    let mut resampler = SincFixedIn::<f32>::new(
+        TARGET_SAMPLE_RATE as f64 / source_rate as f64,
+        2.0,
+        params,
+        samples.len(),
+        1,
+    )?;
+
+    let waves_in = vec![samples];
+    let mut waves_out = resampler.process(&waves_in, None)?;
+    Ok(waves_out.remove(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_test_wav(path: &Path, sample_rate: u32, channels: u16, frames: usize) {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for i in 0..frames {
+            let value = ((i as f32 * 0.05).sin() * 8000.0) as i16;
+            for _ in 0..channels {
+                writer.write_sample(value).unwrap();
+            }
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_load_audio_16k_mono_passthrough() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mono.wav");
+        write_test_wav(&path, 16000, 1, 16000);
+
+        let samples = load_audio(&path).unwrap();
+        assert_eq!(samples.len(), 16000);
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_load_audio_resamples_and_downmixes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stereo.wav");
+        write_test_wav(&path, 48000, 2, 48000);
+
+        let samples = load_audio(&path).unwrap();
+        // One second of audio should come out as ~16000 samples
+        assert!(
+            (15500..=16500).contains(&samples.len()),
+            "expected ~16000 samples, got {}",
+            samples.len()
+        );
+    }
+
+    #[test]
+    fn test_load_audio_detects_format_by_content() {
+        // A WAV file with a misleading extension is still decoded as WAV
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memo.mp3");
+        write_test_wav(&path, 16000, 1, 8000);
+
+        let samples = load_audio(&path).unwrap();
+        assert_eq!(samples.len(), 8000);
+    }
+
+    #[test]
+    fn test_load_audio_decodes_ogg_opus() {
+        // One second of a 440Hz tone, 48kHz mono Ogg/Opus
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tone-440hz.opus");
+
+        let samples = load_audio(&path).unwrap();
+        assert!(
+            (15500..=16500).contains(&samples.len()),
+            "expected ~16000 samples, got {}",
+            samples.len()
+        );
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        assert!(rms > 0.1, "decoded tone is silent (rms {})", rms);
+    }
+
+    #[test]
+    fn test_load_audio_rejects_non_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.wav");
+        std::fs::write(&path, "definitely not audio").unwrap();
+
+        let err = load_audio(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains("Unrecognized or unsupported audio format"));
+    }
+
+    #[test]
+    fn test_load_audio_missing_file() {
+        let result = load_audio(Path::new("/nonexistent/audio.wav"));
+        assert!(result.unwrap_err().to_string().contains("File not found"));
+    }
+
+    #[test]
+    fn test_load_audio_bytes_decodes_wav() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("piped.wav");
+        write_test_wav(&path, 16000, 1, 4000);
+
+        let samples = load_audio_bytes(std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(samples.len(), 4000);
+    }
+
+    #[test]
+    fn test_load_raw_pcm_s16le_stereo() {
+        // Two stereo frames: (max, 0) and (min, min), plus one stray byte
+        let mut data = Vec::new();
+        for s in [i16::MAX, 0, i16::MIN, i16::MIN] {
+            data.extend_from_slice(&s.to_le_bytes());
+        }
+        data.push(0);
+
+        let samples = load_raw_pcm(&data, PcmFormat::S16le, 16000, 2).unwrap();
+        assert_eq!(samples.len(), 2);
+        assert!((samples[0] - 0.5).abs() < 1e-3);
+        assert!((samples[1] + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_load_raw_pcm_rejects_zero_rate() {
+        assert!(load_raw_pcm(&[0, 0], PcmFormat::S16le, 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_pcm_format_from_str() {
+        assert_eq!("s16le".parse::<PcmFormat>().unwrap(), PcmFormat::S16le);
+        assert_eq!("F32LE".parse::<PcmFormat>().unwrap(), PcmFormat::F32le);
+        assert!("s24be".parse::<PcmFormat>().is_err());
+    }
+
+    #[test]
+    fn test_downmix_averages_channels() {
+        let mono = downmix(vec![1.0, 0.0, 0.5, 0.5, -1.0, 1.0], 2);
+        assert_eq!(mono, vec![0.5, 0.5, 0.0]);
+    }
+}
@@ -0,0 +1,347 @@
+//! Input device health and stream recovery
+//!
+//! cpal reports device loss (a USB headset unplugged mid-recording) through the
+//! stream's error callback, and some backends just stop delivering data. Capture
+//! loops watch each stream with a [`StreamWatch`]; when it fails they reopen the
+//! configured device, fall back to the system default if it is gone, and switch
+//! back once the configured device reappears. The outcome is tracked process-wide
+//! so the daemon can report it in `status`.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// A stream that delivered data before but has been quiet this long is considered dead
+const STALL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Delay between reconnect attempts while no device can be opened
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often to check whether the configured device is back while on the fallback
+const PROBE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Current state of the input device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceState {
+    /// No input stream open
+    #[default]
+    Idle,
+    /// Capturing from the configured device
+    Ok,
+    /// Configured device unavailable, capturing from the system default
+    Fallback,
+    /// Stream failed, reopening
+    Reconnecting,
+    /// No device could be opened
+    Lost,
+}
+
+/// Device health snapshot for status reporting
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceHealth {
+    pub state: DeviceState,
+    /// Device currently capturing ("default" for the system default)
+    pub device: Option<String>,
+    /// Most recent stream or open error
+    pub last_error: Option<String>,
+    /// Successful reconnects since startup
+    pub reconnects: u32,
+}
+
+static HEALTH: LazyLock<Mutex<DeviceHealth>> = LazyLock::new(Mutex::default);
+
+fn update(f: impl FnOnce(&mut DeviceHealth)) {
+    if let Ok(mut health) = HEALTH.lock() {
+        f(&mut health);
+    }
+}
+
+/// Current device health of this process
+pub fn current() -> DeviceHealth {
+    HEALTH.lock().map(|h| h.clone()).unwrap_or_default()
+}
+
+/// Record a newly opened stream
+pub(super) fn opened(device: Option<&str>, fallback: bool) {
+    update(|h| {
+        h.state = if fallback {
+            DeviceState::Fallback
+        } else {
+            DeviceState::Ok
+        };
+        h.device = Some(device.unwrap_or("default").to_string());
+    });
+}
+
+/// Record that the input stream was closed normally
+pub(super) fn closed() {
+    update(|h| {
+        h.state = DeviceState::Idle;
+        h.device = None;
+    });
+}
+
+/// Record a failed stream that is about to be reopened
+pub(super) fn failed(reason: &str) {
+    record_error(DeviceState::Reconnecting, reason.to_string());
+}
+
+fn record_error(state: DeviceState, error: String) {
+    update(|h| {
+        h.state = state;
+        h.last_error = Some(error);
+    });
+}
+
+/// Liveness of one cpal stream: error callbacks and data arrival
+#[derive(Clone)]
+pub(super) struct StreamWatch {
+    /// Error reported by cpal, if any
+    error: Arc<Mutex<Option<String>>>,
+    /// Milliseconds since `epoch()` of the last data callback (0 = none yet)
+    last_data_ms: Arc<AtomicU64>,
+}
+
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+fn now_ms() -> u64 {
+    // +1 so a callback in the very first millisecond still counts as data
+    epoch().elapsed().as_millis() as u64 + 1
+}
+
+impl StreamWatch {
+    pub(super) fn new() -> Self {
+        epoch();
+        Self {
+            error: Arc::default(),
+            last_data_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Call from the data callback
+    pub(super) fn touch(&self) {
+        self.last_data_ms.store(now_ms(), Ordering::Relaxed);
+    }
+
+    /// Error callback for the stream backend
+    pub(super) fn error_callback<E: std::fmt::Display>(&self) -> impl FnMut(E) + Send + 'static {
+        let error = self.error.clone();
+        move |err| {
+            warn!("Audio stream error: {}", err);
+            if let Ok(mut error) = error.lock() {
+                error.get_or_insert_with(|| err.to_string());
+            }
+        }
+    }
+
+    /// Why the stream should be replaced, if it should
+    pub(super) fn failure(&self) -> Option<String> {
+        if let Some(error) = self.error.lock().ok().and_then(|e| e.clone()) {
+            return Some(error);
+        }
+        let last = self.last_data_ms.load(Ordering::Relaxed);
+        let quiet = Duration::from_millis(now_ms().saturating_sub(last));
+        (last > 0 && quiet >= STALL_TIMEOUT)
+            .then(|| format!("no audio data for {}ms", quiet.as_millis()))
+    }
+}
+
+/// What a capture loop should do on this tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Action {
+    Continue,
+    /// The stream is dead: reopen configured device, else the default
+    Reconnect,
+    /// On the fallback and the configured device may be back
+    ProbeConfigured,
+}
+
+/// Reconnect/probe pacing for one capture
+pub(super) struct Supervisor {
+    configured: Option<String>,
+    fallback: bool,
+    next_attempt: Instant,
+    next_probe: Instant,
+}
+
+impl Supervisor {
+    pub(super) fn new(configured: Option<&str>, fallback: bool, now: Instant) -> Self {
+        Self {
+            configured: configured.map(str::to_string),
+            fallback,
+            next_attempt: now,
+            next_probe: now + PROBE_INTERVAL,
+        }
+    }
+
+    pub(super) fn check(&mut self, now: Instant, stream_failed: bool) -> Action {
+        if stream_failed {
+            if now < self.next_attempt {
+                return Action::Continue;
+            }
+            self.next_attempt = now + RETRY_INTERVAL;
+            return Action::Reconnect;
+        }
+        if self.fallback && self.configured.is_some() && now >= self.next_probe {
+            self.next_probe = now + PROBE_INTERVAL;
+            return Action::ProbeConfigured;
+        }
+        Action::Continue
+    }
+
+    /// Reopen after a failure: the configured device, then the system default.
+    /// Returns None (and marks the device lost) when nothing could be opened.
+    pub(super) fn reconnect<T>(&mut self, open: impl Fn(Option<&str>) -> Result<T>) -> Option<T> {
+        let mut candidates = vec![(self.configured.clone(), false)];
+        if self.configured.is_some() {
+            candidates.push((None, true));
+        }
+
+        let mut last_error = None;
+        for (name, fallback) in candidates {
+            match open(name.as_deref()) {
+                Ok(opened) => {
+                    info!(
+                        "Reconnected to {}{}",
+                        name.as_deref().unwrap_or("default input"),
+                        if fallback { " (fallback)" } else { "" }
+                    );
+                    self.fallback = fallback;
+                    self.next_probe = Instant::now() + PROBE_INTERVAL;
+                    self::opened(name.as_deref(), fallback);
+                    update(|h| h.reconnects += 1);
+                    return Some(opened);
+                },
+                Err(e) => {
+                    warn!(
+                        "Failed to open {}: {:#}",
+                        name.as_deref().unwrap_or("default input"),
+                        e
+                    );
+                    last_error = Some(format!("{:#}", e));
+                },
+            }
+        }
+
+        record_error(
+            DeviceState::Lost,
+            last_error.unwrap_or_else(|| "no input device".to_string()),
+        );
+        None
+    }
+
+    /// Switch back to the configured device if it is available again
+    pub(super) fn probe_configured<T>(
+        &mut self,
+        open: impl Fn(Option<&str>) -> Result<T>,
+    ) -> Option<T> {
+        let name = self.configured.clone()?;
+        if !device_available(&name) {
+            return None;
+        }
+        match open(Some(&name)) {
+            Ok(opened) => {
+                info!("Configured device '{}' is back, switching to it", name);
+                self.fallback = false;
+                self::opened(Some(&name), false);
+                update(|h| h.reconnects += 1);
+                Some(opened)
+            },
+            Err(e) => {
+                warn!(
+                    "Configured device '{}' listed but failed to open: {:#}",
+                    name, e
+                );
+                None
+            },
+        }
+    }
+}
+
+/// Whether an input device matching `name` (display or internal name) is present
+fn device_available(name: &str) -> bool {
+    super::list_input_devices()
+        .map(|devices| {
+            devices
+                .iter()
+                .any(|d| d.name == name || d.internal_name.as_deref() == Some(name))
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_is_paced() {
+        let start = Instant::now();
+        let mut supervisor = Supervisor::new(Some("USB Headset"), false, start);
+
+        assert_eq!(supervisor.check(start, false), Action::Continue);
+        assert_eq!(supervisor.check(start, true), Action::Reconnect);
+        // Another failure right away waits for the retry interval
+        assert_eq!(supervisor.check(start, true), Action::Continue);
+        assert_eq!(
+            supervisor.check(start + RETRY_INTERVAL, true),
+            Action::Reconnect
+        );
+    }
+
+    #[test]
+    fn test_probe_only_on_fallback() {
+        let start = Instant::now();
+        let later = start + PROBE_INTERVAL;
+
+        let mut on_configured = Supervisor::new(Some("USB Headset"), false, start);
+        assert_eq!(on_configured.check(later, false), Action::Continue);
+
+        let mut on_fallback = Supervisor::new(Some("USB Headset"), true, start);
+        assert_eq!(on_fallback.check(start, false), Action::Continue);
+        assert_eq!(on_fallback.check(later, false), Action::ProbeConfigured);
+        assert_eq!(on_fallback.check(later, false), Action::Continue);
+
+        // Nothing to go back to when the default is the configured device
+        let mut default = Supervisor::new(None, true, start);
+        assert_eq!(default.check(later, false), Action::Continue);
+    }
+
+    #[test]
+    fn test_reconnect_falls_back_to_default() {
+        let mut supervisor = Supervisor::new(Some("USB Headset"), false, Instant::now());
+        let opened = supervisor.reconnect(|name| match name {
+            Some(_) => anyhow::bail!("device unplugged"),
+            None => Ok("default"),
+        });
+        assert_eq!(opened, Some("default"));
+        assert!(supervisor.fallback);
+
+        let opened = supervisor.reconnect(|name| Ok(name.unwrap_or("default").to_string()));
+        assert_eq!(opened.as_deref(), Some("USB Headset"));
+        assert!(!supervisor.fallback);
+
+        let nothing: Option<()> = supervisor.reconnect(|_| anyhow::bail!("no devices"));
+        assert!(nothing.is_none());
+        assert_eq!(current().state, DeviceState::Lost);
+    }
+
+    #[test]
+    fn test_stream_watch() {
+        let watch = StreamWatch::new();
+        // No data yet is not a stall (the stream may still be starting)
+        assert_eq!(watch.failure(), None);
+        watch.touch();
+        assert_eq!(watch.failure(), None);
+
+        let mut on_error = watch.error_callback::<cpal::StreamError>();
+        on_error(cpal::StreamError::DeviceNotAvailable);
+        assert!(watch.failure().unwrap().contains("no longer available"));
+    }
+}
@@ -8,7 +8,52 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tracing::{info, warn};
 
-const TARGET_SAMPLE_RATE: u32 = 16000;
+pub mod denoise;
+pub mod diagnostics;
+mod file;
+pub mod health;
+pub mod levels;
+pub mod preprocess;
+mod preroll;
+#[cfg(target_os = "linux")]
+pub mod pulse;
+pub mod vad;
+
+pub use file::{PcmFormat, load_audio, load_audio_bytes, load_raw_pcm};
+pub use preroll::PreRoll;
+
+/// Rate of all captured audio handed out of this module (Whisper's input rate)
+pub const TARGET_SAMPLE_RATE: u32 = 16000;
+
+/// Trailing audio that must be silent for smart stop to end the buffer early
+const SMART_STOP_SILENCE_MS: usize = 300;
+
+/// Recent audio used to estimate the speech threshold for smart stop
+const SMART_STOP_HISTORY_SECS: usize = 10;
+
+/// How often continuous capture hands audio to its consumer
+const CONTINUOUS_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Input frames per resampler call
+const RESAMPLE_CHUNK: usize = 1024;
+
+/// How long to keep capturing after the stop signal
+#[derive(Debug, Clone, Copy)]
+pub struct TrailingBuffer {
+    /// Maximum trailing capture in milliseconds
+    pub max_ms: u32,
+    /// End early once the most recent audio is silent
+    pub smart: bool,
+}
+
+impl Default for TrailingBuffer {
+    fn default() -> Self {
+        Self {
+            max_ms: 1000,
+            smart: false,
+        }
+    }
+}
 
 /// Audio device info for UI display
 #[allow(dead_code)] // Public API - called from Tauri UI
@@ -18,25 +63,26 @@ pub struct AudioDeviceInfo {
     pub name: String,
     /// Whether this is the system default device
     pub is_default: bool,
-    /// Internal device identifier for CPAL (ALSA device name on Linux)
-    /// When None, uses the name field directly
+    /// Stable device identifier: the PipeWire/PulseAudio source name, or the CPAL
+    /// device name. When None, uses the name field directly
     pub internal_name: Option<String>,
 }
 
 /// List available audio input devices
-/// On Linux with PipeWire, this queries both CPAL/ALSA and PipeWire sources
+/// On Linux with PipeWire/PulseAudio, this lists the sound server's sources
 #[allow(dead_code)] // Public API - called from Tauri UI
 pub fn list_input_devices() -> Result<Vec<AudioDeviceInfo>> {
     #[cfg(target_os = "linux")]
     {
-        // Try to get devices from PipeWire first (more comprehensive on modern Linux)
-        if let Ok(pipewire_devices) = list_pipewire_devices() {
-            if !pipewire_devices.is_empty() {
-                info!("Found {} PipeWire audio source(s)", pipewire_devices.len());
-                return Ok(pipewire_devices);
-            }
+        // Sound server sources first (more comprehensive on modern Linux)
+        match list_pulse_devices() {
+            Ok(devices) if !devices.is_empty() => return Ok(devices),
+            Ok(_) => warn!("PipeWire/PulseAudio reported no sources, falling back to CPAL/ALSA"),
+            Err(e) => warn!(
+                "PipeWire/PulseAudio enumeration failed ({:#}), falling back to CPAL/ALSA",
+                e
+            ),
         }
-        warn!("PipeWire enumeration failed or empty, falling back to CPAL/ALSA");
     }
 
     // Fallback: Use CPAL's ALSA backend (works on all platforms)
@@ -83,264 +129,191 @@ fn list_cpal_devices() -> Result<Vec<AudioDeviceInfo>> {
     Ok(devices)
 }
 
-/// Query PipeWire/PulseAudio for available audio sources (Linux only)
-#[cfg(target_os = "linux")]
-fn list_pipewire_devices() -> Result<Vec<AudioDeviceInfo>> {
-    use std::process::Command;
-
-    // Try pactl first (works with both PulseAudio and PipeWire)
-    let output = Command::new("pactl")
-        .args(["list", "sources", "short"])
-        .output()
-        .context("Failed to run pactl - PulseAudio/PipeWire not available")?;
-
-    if !output.status.success() {
-        anyhow::bail!("pactl command failed");
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut devices = Vec::new();
-
-    // Get default source
-    let default_source = Command::new("pactl")
-        .args(["get-default-source"])
-        .output()
-        .ok()
-        .and_then(|o| {
-            if o.status.success() {
-                Some(String::from_utf8_lossy(&o.stdout).trim().to_string())
-            } else {
-                None
-            }
-        });
-
-    // Parse pactl output: ID  NAME  MODULE  SAMPLE_SPEC  STATE
-    for line in stdout.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 2 {
-            let source_name = parts[1].to_string();
-
-            // Get human-readable description
-            let description = get_source_description(&source_name).unwrap_or_else(|| source_name.clone());
-
-            // Prefix monitor sources (application audio loopbacks) so they're visually distinct
-            let display_name = if source_name.ends_with(".monitor") {
-                format!("[Monitor] {}", description)
-            } else {
-                description
-            };
-
-            devices.push(AudioDeviceInfo {
-                name: display_name,
-                is_default: default_source.as_ref() == Some(&source_name),
-                internal_name: Some(source_name), // Store PipeWire source name
-            });
-        }
-    }
-
-    Ok(devices)
-}
-
-/// Get human-readable description for a PipeWire source
+/// List PipeWire/PulseAudio sources over the native protocol (Linux only)
 #[cfg(target_os = "linux")]
-fn get_source_description(source_name: &str) -> Option<String> {
-    use std::process::Command;
-
-    let output = Command::new("pactl")
-        .args(["list", "sources"])
-        .output()
-        .ok()?;
-
-    if !output.status.success() {
-        return None;
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut in_target_source = false;
-
-    for line in stdout.lines() {
-        let trimmed = line.trim();
-
-        // Check if we're entering the target source section
-        if trimmed.starts_with("Name: ") && trimmed.contains(source_name) {
-            in_target_source = true;
-            continue;
-        }
-
-        // If we're in the target source, look for Description
-        if in_target_source {
-            if trimmed.starts_with("Description: ") {
-                return Some(trimmed.strip_prefix("Description: ")?.trim().to_string());
-            }
-
-            // Stop if we hit another source
-            if trimmed.starts_with("Name: ") {
-                break;
-            }
-        }
-    }
-
-    None
-}
-
-/// Temporarily set a PipeWire source as the default input
-/// This allows CPAL to use it via the "default" ALSA device
-#[cfg(target_os = "linux")]
-fn set_pipewire_source_temporarily(source_name: &str) -> Result<()> {
-    use std::process::Command;
-
-    info!("Setting PipeWire default source to: {}", source_name);
-
-    let output = Command::new("pactl")
-        .args(["set-default-source", source_name])
-        .output()
-        .context("Failed to run pactl")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Failed to set default source: {}", stderr);
-    }
-
-    Ok(())
-}
-
-#[cfg(not(target_os = "linux"))]
-fn set_pipewire_source_temporarily(_source_name: &str) -> Result<()> {
-    // No-op on non-Linux platforms
-    Ok(())
+fn list_pulse_devices() -> Result<Vec<AudioDeviceInfo>> {
+    let mut client = pulse::Client::connect()?;
+    let default_source = client.server_info()?.default_source;
+    let sources = client.sources()?;
+    info!("Found {} PipeWire/PulseAudio source(s)", sources.len());
+
+    Ok(sources
+        .iter()
+        .map(|source| AudioDeviceInfo {
+            name: source_display_name(source),
+            is_default: default_source.as_deref() == Some(source.name.as_str()),
+            internal_name: Some(source.name.clone()),
+        })
+        .collect())
 }
 
-/// Get the current PipeWire/PulseAudio default source name
+/// Human-readable source name. Monitor sources (application audio loopbacks) are
+/// prefixed so they're visually distinct.
 #[cfg(target_os = "linux")]
-fn get_pipewire_default_source() -> Result<String> {
-    use std::process::Command;
-
-    let output = Command::new("pactl")
-        .args(["info"])
-        .output()
-        .context("Failed to run pactl")?;
-
-    if !output.status.success() {
-        anyhow::bail!("pactl info failed");
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    for line in stdout.lines() {
-        if line.starts_with("Default Source:") {
-            if let Some(source) = line.strip_prefix("Default Source:") {
-                return Ok(source.trim().to_string());
-            }
-        }
+fn source_display_name(source: &pulse::SourceInfo) -> String {
+    if source.monitor_of_sink.is_some() || source.name.ends_with(".monitor") {
+        format!("[Monitor] {}", source.description)
+    } else {
+        source.description.clone()
     }
-
-    anyhow::bail!("Could not find default source in pactl info")
 }
 
 /// Get the monitor source for the current default PipeWire/PulseAudio sink.
 /// Returns e.g. "bluez_output.XX_XX_XX.1.monitor" for active headphones.
-/// Returns None if pactl is unavailable or no default sink is set.
+/// Returns None if the sound server is unreachable or no default sink is set.
 #[cfg(target_os = "linux")]
 pub fn get_default_sink_monitor() -> Option<String> {
-    use std::process::Command;
-
-    let output = Command::new("pactl")
-        .args(["get-default-sink"])
-        .output()
-        .ok()?;
-
-    if !output.status.success() {
-        return None;
-    }
-
-    let sink = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if sink.is_empty() {
-        return None;
-    }
+    let mut client = pulse::Client::connect().ok()?;
+    let sink = client.server_info().ok()?.default_sink?;
+    let monitor = client.sources().ok().and_then(|sources| {
+        sources
+            .into_iter()
+            .find(|s| s.monitor_of_sink.as_deref() == Some(sink.as_str()))
+            .map(|s| s.name)
+    });
+
+    Some(monitor.unwrap_or_else(|| format!("{}.monitor", sink)))
+}
 
-    Some(format!("{}.monitor", sink))
+/// Where an input stream is opened
+enum InputBackend {
+    Cpal {
+        device: Device,
+        config: StreamConfig,
+    },
+    /// A PipeWire/PulseAudio source opened by name over the native protocol
+    /// (None = the server's default source)
+    #[cfg(target_os = "linux")]
+    Pulse { source: Option<String> },
 }
 
 /// Audio device configuration
 struct AudioSetup {
-    device: Device,
-    config: StreamConfig,
+    backend: InputBackend,
     sample_rate: u32,
     channels: u16,
 }
 
-/// Set up an audio input device by name, or the default if None
-fn setup_audio_device(device_name: Option<&str>) -> Result<AudioSetup> {
-    // On Linux with PipeWire, handle device selection specially
+/// A running input stream; capture stops when it is dropped
+enum InputStream {
+    Cpal(#[allow(dead_code)] Stream),
     #[cfg(target_os = "linux")]
-    {
-        if let Some(name) = device_name {
-            // Try to resolve device name (handles both display names and internal names)
-            if let Ok(internal_name) = resolve_device_name(name) {
-                info!("Resolved device '{}' to PipeWire source '{}'", name, internal_name);
-                // Set as default PipeWire source, then use ALSA "default" device
-                if let Err(e) = set_pipewire_source_temporarily(&internal_name) {
-                    warn!("Failed to set PipeWire source: {}. Falling back to default device.", e);
-                } else {
-                    return setup_cpal_device(Some("default"));
-                }
-            }
+    Pulse(#[allow(dead_code)] pulse::RecordStream),
+}
 
-            // Fallback: try direct CPAL device lookup
-            info!("Using device name '{}' directly with CPAL", name);
-        } else {
-            // No device specified - use PipeWire's actual default source
-            // CPAL's default_input_device() doesn't respect PipeWire's default on Linux
-            match get_pipewire_default_source() {
-                Ok(source) => {
-                    info!("Using PipeWire default source: {}", source);
-                    // The source is already the default in PipeWire, just use CPAL's "default"
-                    return setup_cpal_device(Some("default"));
-                }
-                Err(e) => {
-                    warn!("Failed to get PipeWire default source: {}. Using CPAL default.", e);
-                }
-            }
+impl AudioSetup {
+    /// Start delivering interleaved samples to `on_data`. The stream reports data
+    /// arrival and errors to `watch` and feeds the level meter.
+    fn start_stream(
+        &self,
+        mut on_data: impl FnMut(&[f32]) + Send + 'static,
+        watch: &health::StreamWatch,
+    ) -> Result<InputStream> {
+        let data_watch = watch.clone();
+        let mut on_data = move |data: &[f32]| {
+            data_watch.touch();
+            levels::update(data);
+            on_data(data);
+        };
+
+        match &self.backend {
+            InputBackend::Cpal { device, config } => {
+                let stream = device.build_input_stream(
+                    config,
+                    move |data: &[f32], _: &cpal::InputCallbackInfo| on_data(data),
+                    watch.error_callback(),
+                    None,
+                )?;
+                stream.play()?;
+                Ok(InputStream::Cpal(stream))
+            },
+            #[cfg(target_os = "linux")]
+            InputBackend::Pulse { source } => {
+                let stream = pulse::Client::connect()?.record(
+                    source.as_deref(),
+                    self.sample_rate,
+                    self.channels as u8,
+                    on_data,
+                    watch.error_callback(),
+                )?;
+                Ok(InputStream::Pulse(stream))
+            },
         }
     }
+}
+
+/// Set up an audio input device by name, or the default if None.
+///
+/// On Linux the PipeWire/PulseAudio source is opened directly by name, which routes
+/// only our stream and leaves the user's default source alone. Names that aren't
+/// sound server sources (ALSA `hw:` names), and systems without a sound server, go
+/// through CPAL.
+fn setup_audio_device(device_name: Option<&str>) -> Result<AudioSetup> {
+    #[cfg(target_os = "linux")]
+    match pulse::Client::connect().and_then(|mut client| client.sources()) {
+        Ok(sources) => {
+            if let Some(setup) = setup_pulse_source(&sources, device_name) {
+                return Ok(setup);
+            }
+        },
+        Err(e) => warn!("PipeWire/PulseAudio unavailable ({:#}), using CPAL", e),
+    }
 
-    // Standard CPAL device selection for ALSA device names or other platforms
     setup_cpal_device(device_name)
 }
 
-/// Resolve a device name (display name or internal name) to a PipeWire source name
-/// Returns the internal PipeWire source name if found
+/// Capture from a sound server source. The server converts to 16kHz mono for us.
 #[cfg(target_os = "linux")]
-fn resolve_device_name(name: &str) -> Result<String> {
-    // If it already looks like a PipeWire source name, use it directly
-    if name.contains('.') || name.starts_with("alsa_") || name.starts_with("bluez_") {
-        return Ok(name.to_string());
-    }
+fn setup_pulse_source(
+    sources: &[pulse::SourceInfo],
+    device_name: Option<&str>,
+) -> Option<AudioSetup> {
+    let source = match device_name {
+        Some(name) => {
+            let Some(source) = resolve_source(sources, name) else {
+                info!("'{}' is not a PipeWire/PulseAudio source, trying CPAL", name);
+                return None;
+            };
+            info!("Resolved device '{}' to source '{}'", name, source.name);
+            Some(source)
+        },
+        None => None,
+    };
 
-    // Query PipeWire for all sources and match by display name
-    let devices = list_pipewire_devices()
-        .context("Failed to query PipeWire devices")?;
+    info!(
+        "Audio device: {} (PipeWire/PulseAudio, {}Hz, 1 ch)",
+        source.map_or("default source", |s| s.description.as_str()),
+        TARGET_SAMPLE_RATE
+    );
+    Some(AudioSetup {
+        backend: InputBackend::Pulse {
+            source: source.map(|s| s.name.clone()),
+        },
+        sample_rate: TARGET_SAMPLE_RATE,
+        channels: 1,
+    })
+}
 
-    // Try exact match on display name
-    for device in &devices {
-        if device.name == name {
-            if let Some(ref internal) = device.internal_name {
-                return Ok(internal.clone());
-            }
-        }
+/// Find a source by its stable name, description or display name, then by
+/// case-insensitive partial description match
+#[cfg(target_os = "linux")]
+fn resolve_source<'a>(
+    sources: &'a [pulse::SourceInfo],
+    name: &str,
+) -> Option<&'a pulse::SourceInfo> {
+    let exact = sources.iter().find(|s| {
+        s.name == name || s.description == name || source_display_name(s) == name
+    });
+    if exact.is_some() {
+        return exact;
     }
 
-    // Try partial match (case-insensitive)
     let name_lower = name.to_lowercase();
-    for device in &devices {
-        if device.name.to_lowercase().contains(&name_lower) {
-            if let Some(ref internal) = device.internal_name {
-                warn!("Using partial match: '{}' -> '{}'", name, device.name);
-                return Ok(internal.clone());
-            }
-        }
-    }
-
-    anyhow::bail!("Device '{}' not found in PipeWire sources", name)
+    let partial = sources
+        .iter()
+        .find(|s| s.description.to_lowercase().contains(&name_lower))?;
+    warn!("Using partial match: '{}' -> '{}'", name, partial.description);
+    Some(partial)
 }
 
 /// Set up CPAL device using standard ALSA/CoreAudio/WASAPI device names
@@ -390,10 +363,12 @@ fn setup_cpal_device(device_name: Option<&str>) -> Result<AudioSetup> {
     );
 
     Ok(AudioSetup {
-        config: default_config.config(),
         sample_rate: default_config.sample_rate().0,
         channels: default_config.channels(),
-        device,
+        backend: InputBackend::Cpal {
+            config: default_config.config(),
+            device,
+        },
     })
 }
 
@@ -402,20 +377,195 @@ fn build_capture_stream(
     setup: &AudioSetup,
     buffer: Arc<Mutex<Vec<f32>>>,
     started: Arc<AtomicBool>,
-) -> Result<Stream> {
-    let stream = setup.device.build_input_stream(
-        &setup.config,
-        move |data: &[f32], _: &cpal::InputCallbackInfo| {
+    watch: &health::StreamWatch,
+) -> Result<InputStream> {
+    setup.start_stream(
+        move |data| {
             if !started.swap(true, Ordering::Relaxed) {
                 info!("Recording started - speak now!");
             }
             buffer.lock().unwrap().extend_from_slice(data);
         },
-        |err| eprintln!("Stream error: {}", err),
-        None,
-    )?;
-    stream.play()?;
-    Ok(stream)
+        watch,
+    )
+}
+
+/// One input stream of a capture and the samples it delivered
+struct CaptureSegment {
+    setup: AudioSetup,
+    buffer: Arc<Mutex<Vec<f32>>>,
+    watch: health::StreamWatch,
+    stream: InputStream,
+    /// Resampler state for `drain`, created on first use
+    resampler: Option<rubato::FftFixedIn<f64>>,
+}
+
+impl CaptureSegment {
+    fn start(device_name: Option<&str>, started: &Arc<AtomicBool>) -> Result<Self> {
+        let setup = setup_audio_device(device_name)?;
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let watch = health::StreamWatch::new();
+        let stream = build_capture_stream(&setup, buffer.clone(), started.clone(), &watch)?;
+        Ok(Self {
+            setup,
+            buffer,
+            watch,
+            stream,
+            resampler: None,
+        })
+    }
+
+    /// Take the samples delivered so far as 16kHz mono, leaving the stream running.
+    ///
+    /// When resampling, only whole resampler chunks are taken and the resampler is
+    /// kept between calls, so the output is seamless across drains.
+    fn drain(&mut self) -> Vec<f32> {
+        use rubato::{FftFixedIn, Resampler};
+
+        let rate = self.setup.sample_rate;
+        let frame = self.setup.channels as usize;
+        let needs_resample = needs_resample(rate, TARGET_SAMPLE_RATE);
+        let samples: Vec<f32> = {
+            let mut buffer = self.buffer.lock().unwrap();
+            let unit = if needs_resample { frame * RESAMPLE_CHUNK } else { frame };
+            let n = buffer.len() - buffer.len() % unit;
+            buffer.drain(..n).collect()
+        };
+
+        let mono = to_mono(samples, self.setup.channels);
+        if !needs_resample || mono.is_empty() {
+            return mono;
+        }
+
+        if self.resampler.is_none() {
+            let target = TARGET_SAMPLE_RATE as usize;
+            match FftFixedIn::<f64>::new(rate as usize, target, RESAMPLE_CHUNK, 2, 1) {
+                Ok(r) => self.resampler = Some(r),
+                Err(e) => {
+                    warn!("Resampler init failed: {}, using linear fallback", e);
+                    return resample_linear(&mono, rate as f32 / TARGET_SAMPLE_RATE as f32);
+                },
+            }
+        }
+        let resampler = self.resampler.as_mut().expect("resampler initialized above");
+
+        let mut output = Vec::new();
+        for chunk in mono.chunks_exact(RESAMPLE_CHUNK) {
+            let input = vec![chunk.iter().map(|&s| s as f64).collect::<Vec<f64>>()];
+            match resampler.process(&input, None) {
+                Ok(out) => output.extend(out[0].iter().map(|&s| s as f32)),
+                Err(e) => warn!("Resample error: {}, dropping {} samples", e, chunk.len()),
+            }
+        }
+        output
+    }
+
+    /// Close the stream and convert its samples to 16kHz mono
+    fn finish(self) -> Result<Vec<f32>> {
+        drop(self.stream);
+        let samples = extract_samples(self.buffer);
+        log_capture_stats(&samples, &self.setup);
+
+        let mono_samples = to_mono(samples, self.setup.channels);
+        finalize_audio_samples(mono_samples, self.setup.sample_rate, TARGET_SAMPLE_RATE)
+    }
+}
+
+/// A capture that survives device loss.
+///
+/// When the stream fails it is reopened on the configured device or, if that is gone,
+/// the system default; on the fallback the configured device is probed and switched
+/// back to when it reappears. Each stream's audio is converted to 16kHz mono as it is
+/// replaced, so devices with different formats can be stitched together.
+struct LiveCapture {
+    current: Option<CaptureSegment>,
+    /// Audio from streams that were replaced, already at 16kHz mono
+    earlier: Vec<f32>,
+    started: Arc<AtomicBool>,
+    supervisor: health::Supervisor,
+}
+
+impl LiveCapture {
+    fn start(device_name: Option<&str>) -> Result<Self> {
+        let started = Arc::new(AtomicBool::new(false));
+        let current = CaptureSegment::start(device_name, &started)?;
+        health::opened(device_name, false);
+        levels::start();
+
+        Ok(Self {
+            current: Some(current),
+            earlier: Vec::new(),
+            started,
+            supervisor: health::Supervisor::new(device_name, false, Instant::now()),
+        })
+    }
+
+    /// Replace a failed stream, or go back to the configured device. Called on every
+    /// poll tick of the capture loop.
+    fn supervise(&mut self) {
+        let failure = self.current.as_ref().and_then(|c| c.watch.failure());
+        if let Some(reason) = failure {
+            warn!("Input stream failed ({}), reconnecting", reason);
+            health::failed(&reason);
+            self.retire_current();
+        }
+
+        let started = self.started.clone();
+        let open = |name: Option<&str>| CaptureSegment::start(name, &started);
+        match self.supervisor.check(Instant::now(), self.current.is_none()) {
+            health::Action::Continue => {},
+            health::Action::Reconnect => {
+                self.current = self.supervisor.reconnect(open);
+            },
+            health::Action::ProbeConfigured => {
+                if let Some(segment) = self.supervisor.probe_configured(open) {
+                    self.retire_current();
+                    self.current = Some(segment);
+                }
+            },
+        }
+    }
+
+    fn retire_current(&mut self) {
+        let Some(segment) = self.current.take() else {
+            return;
+        };
+        match segment.finish() {
+            Ok(samples) => self.earlier.extend(samples),
+            Err(e) => warn!("Dropping audio from the failed stream: {:#}", e),
+        }
+    }
+
+    /// The last `ms` of the current stream at 16kHz mono (empty while reconnecting)
+    fn tail(&self, ms: usize) -> Vec<f32> {
+        self.current
+            .as_ref()
+            .map(|c| {
+                let buffer = c.buffer.lock().unwrap();
+                recent_audio(&buffer, ms, c.setup.sample_rate, c.setup.channels)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Take the audio captured so far at 16kHz mono, leaving the capture running
+    fn drain(&mut self) -> Vec<f32> {
+        let mut samples = std::mem::take(&mut self.earlier);
+        if let Some(current) = self.current.as_mut() {
+            samples.extend(current.drain());
+        }
+        samples
+    }
+
+    /// Stop capturing and return all audio at 16kHz mono
+    fn finish(mut self) -> Result<Vec<f32>> {
+        let current = self.current.take().map(CaptureSegment::finish).transpose()?;
+        levels::stop();
+        health::closed();
+
+        let mut samples = std::mem::take(&mut self.earlier);
+        samples.extend(current.unwrap_or_default());
+        Ok(samples)
+    }
 }
 
 /// Extract samples from the shared buffer after recording
@@ -425,18 +575,33 @@ fn extract_samples(buffer: Arc<Mutex<Vec<f32>>>) -> Vec<f32> {
         .unwrap_or_else(|arc| arc.lock().unwrap().clone())
 }
 
-/// Convert stereo to mono by averaging channels
+/// Convert interleaved multi-channel audio to mono by averaging channels
 fn to_mono(samples: Vec<f32>, channels: u16) -> Vec<f32> {
-    if channels == 2 {
+    if channels > 1 {
         samples
-            .chunks_exact(2)
-            .map(|chunk| (chunk[0] + chunk[1]) / 2.0)
+            .chunks_exact(channels as usize)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
             .collect()
     } else {
         samples
     }
 }
 
+/// The last `ms` of an interleaved device-rate buffer as 16kHz mono, for level checks
+/// that assume Whisper's input format
+fn recent_audio(buffer: &[f32], ms: usize, sample_rate: u32, channels: u16) -> Vec<f32> {
+    let frame = channels.max(1) as usize;
+    let end = buffer.len() - buffer.len() % frame;
+    let len = (sample_rate as usize * ms / 1000 * frame).min(end);
+    let mono = to_mono(buffer[end - len..end].to_vec(), channels);
+    if needs_resample(sample_rate, TARGET_SAMPLE_RATE) {
+        // Linear is plenty for RMS and keeps repeated polls cheap
+        resample_linear(&mono, sample_rate as f32 / TARGET_SAMPLE_RATE as f32)
+    } else {
+        mono
+    }
+}
+
 /// Capture audio from microphone for fixed duration.
 /// Returns f32 PCM samples at 16kHz mono (Whisper requirement).
 pub fn capture(
@@ -446,20 +611,13 @@ pub fn capture(
 ) -> Result<Vec<f32>> {
     info!("Starting audio capture: {}s", duration_secs);
 
-    let setup = setup_audio_device(device_name)?;
-    let expected_samples = (setup.sample_rate * duration_secs) as usize * setup.channels as usize;
-    let buffer = Arc::new(Mutex::new(Vec::with_capacity(expected_samples)));
-    let started = Arc::new(AtomicBool::new(false));
-
-    let stream = build_capture_stream(&setup, buffer.clone(), started)?;
-    std::thread::sleep(Duration::from_secs(duration_secs as u64));
-    drop(stream);
-
-    let samples = extract_samples(buffer);
-    log_capture_stats(&samples, &setup);
-
-    let mono_samples = to_mono(samples, setup.channels);
-    finalize_audio_samples(mono_samples, setup.sample_rate, TARGET_SAMPLE_RATE)
+    let mut live = LiveCapture::start(device_name)?;
+    let deadline = Instant::now() + Duration::from_secs(duration_secs as u64);
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        std::thread::sleep(remaining.min(Duration::from_millis(100)));
+        live.supervise();
+    }
+    live.finish()
 }
 
 /// Capture in toggle mode - stops when signal received or max duration reached
@@ -467,18 +625,48 @@ pub fn capture_toggle(
     max_duration_secs: u32,
     _sample_rate: u32,
     device_name: Option<&str>,
+    trailing: TrailingBuffer,
 ) -> Result<Vec<f32>> {
+    info!("Starting toggle mode capture (max {}s)", max_duration_secs);
+
+    let mut live = LiveCapture::start(device_name)?;
+    wait_for_stop(max_duration_secs, || live.supervise());
+    buffer_trailing_audio(trailing, |ms| live.tail(ms));
+    live.finish()
+}
+
+/// Capture until the stop signal, handing 16kHz mono audio to `on_audio` as it arrives.
+///
+/// Only audio from the last poll is held here, so this can run indefinitely; what
+/// `on_audio` keeps is up to the caller.
+pub fn capture_continuous(
+    device_name: Option<&str>,
+    mut on_audio: impl FnMut(Vec<f32>) -> Result<()>,
+) -> Result<()> {
     use crate::state::toggle::should_stop;
 
-    info!("Starting toggle mode capture (max {}s)", max_duration_secs);
+    info!("Starting continuous capture");
 
-    let setup = setup_audio_device(device_name)?;
-    let expected_samples =
-        (setup.sample_rate * max_duration_secs) as usize * setup.channels as usize;
-    let buffer = Arc::new(Mutex::new(Vec::with_capacity(expected_samples)));
-    let started = Arc::new(AtomicBool::new(false));
+    let mut live = LiveCapture::start(device_name)?;
+    let mut run = || -> Result<()> {
+        while !should_stop() {
+            std::thread::sleep(CONTINUOUS_POLL_INTERVAL);
+            live.supervise();
+            on_audio(live.drain())?;
+        }
+        info!("Stop signal received");
+        Ok(())
+    };
+    let result = run();
+    let rest = live.finish();
+    result?;
+    on_audio(rest?)
+}
 
-    let stream = build_capture_stream(&setup, buffer.clone(), started)?;
+/// Block until the stop signal arrives or the max duration is reached, calling
+/// `on_tick` after every poll
+fn wait_for_stop(max_duration_secs: u32, mut on_tick: impl FnMut()) {
+    use crate::state::toggle::should_stop;
 
     let poll_interval = Duration::from_millis(100);
     let max_duration = Duration::from_secs(max_duration_secs as u64);
@@ -496,19 +684,42 @@ pub fn capture_toggle(
             info!("Max duration reached ({}s)", max_duration_secs);
             break;
         }
-    }
 
-    // Buffer trailing words for 1 second after stop
-    info!("Buffering trailing audio (1s)...");
-    std::thread::sleep(Duration::from_secs(1));
+        on_tick();
+    }
+}
 
-    drop(stream);
+/// Keep capturing after stop so trailing words aren't cut off.
+///
+/// In smart mode the wait ends as soon as the last `SMART_STOP_SILENCE_MS` of audio is
+/// below the recording's speech threshold, so a quick command returns immediately while
+/// a sentence still being finished gets the full buffer. `tail(ms)` returns the last
+/// `ms` captured so far at 16kHz mono, the format the detector's frames assume.
+fn buffer_trailing_audio(trailing: TrailingBuffer, tail: impl Fn(usize) -> Vec<f32>) {
+    let max_wait = Duration::from_millis(trailing.max_ms as u64);
+
+    if !trailing.smart {
+        info!("Buffering trailing audio ({}ms)...", trailing.max_ms);
+        std::thread::sleep(max_wait);
+        return;
+    }
 
-    let samples = extract_samples(buffer);
-    log_capture_stats(&samples, &setup);
+    info!("Buffering trailing audio (up to {}ms, until silence)...", trailing.max_ms);
+    let history = tail(SMART_STOP_HISTORY_SECS * 1000);
+    let threshold = vad::speech_threshold(&vad::frame_rms(&history));
 
-    let mono_samples = to_mono(samples, setup.channels);
-    finalize_audio_samples(mono_samples, setup.sample_rate, TARGET_SAMPLE_RATE)
+    let poll_interval = Duration::from_millis(50);
+    let start = Instant::now();
+    while start.elapsed() < max_wait {
+        if vad::is_silent(&tail(SMART_STOP_SILENCE_MS), threshold) {
+            info!(
+                "Trailing silence detected, stopping after {}ms",
+                start.elapsed().as_millis()
+            );
+            return;
+        }
+        std::thread::sleep(poll_interval.min(max_wait.saturating_sub(start.elapsed())));
+    }
 }
 
 fn log_capture_stats(samples: &[f32], setup: &AudioSetup) {
@@ -519,6 +730,36 @@ fn log_capture_stats(samples: &[f32], setup: &AudioSetup) {
         actual_duration,
         setup.sample_rate
     );
+
+    let stats = channel_stats(samples, setup.sample_rate, setup.channels);
+    if let [stats] = stats.as_slice() {
+        info!("Capture levels: {}", stats);
+        return;
+    }
+    for (channel, stats) in stats.iter().enumerate() {
+        info!("Capture levels (channel {}): {}", channel + 1, stats);
+    }
+}
+
+/// Level stats of each channel of interleaved audio at the device rate. Channels are
+/// kept apart because a downmix would hide one of them clipping or being dead.
+fn channel_stats(
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+) -> Vec<diagnostics::CaptureStats> {
+    let channels = channels.max(1) as usize;
+    (0..channels)
+        .map(|channel| {
+            let samples: Vec<f32> = samples
+                .iter()
+                .skip(channel)
+                .step_by(channels)
+                .copied()
+                .collect();
+            diagnostics::analyze(&samples, sample_rate)
+        })
+        .collect()
 }
 
 /// Resample to target rate if needed, with 1kHz tolerance
@@ -532,8 +773,7 @@ fn finalize_audio_samples(
         return Ok(Vec::new());
     }
 
-    let needs_resample = (source_rate as i32 - target_rate as i32).abs() > 1000;
-    let samples = if needs_resample {
+    let samples = if needs_resample(source_rate, target_rate) {
         info!("Resampling {}Hz -> {}Hz", source_rate, target_rate);
         resample(&raw_samples, source_rate, target_rate)
     } else {
@@ -549,12 +789,17 @@ fn finalize_audio_samples(
     Ok(samples)
 }
 
+/// Rates within 1kHz of each other are treated as equal
+fn needs_resample(source_rate: u32, target_rate: u32) -> bool {
+    (source_rate as i32 - target_rate as i32).abs() > 1000
+}
+
 /// High-quality resampling using rubato (sinc interpolation)
 fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
     use rubato::{FftFixedIn, Resampler};
 
     let samples_f64: Vec<f64> = samples.iter().map(|&s| s as f64).collect();
-    let chunk_size = 1024;
+    let chunk_size = RESAMPLE_CHUNK;
 
     let mut resampler =
         match FftFixedIn::<f64>::new(from_rate as usize, to_rate as usize, chunk_size, 2, 1) {
@@ -616,34 +861,105 @@ fn resample_linear(samples: &[f32], ratio: f32) -> Vec<f32> {
 mod tests {
     use super::*;
 
+    #[cfg(target_os = "linux")]
+    fn source(
+        name: &str,
+        description: &str,
+        monitor_of_sink: Option<&str>,
+    ) -> pulse::SourceInfo {
+        pulse::SourceInfo {
+            index: 0,
+            name: name.to_string(),
+            description: description.to_string(),
+            monitor_of_sink: monitor_of_sink.map(str::to_string),
+            sample_rate: 48000,
+            channels: 2,
+        }
+    }
+
     #[test]
+    #[cfg(target_os = "linux")]
     fn test_monitor_source_display_name_gets_prefix() {
-        let source_name = "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor";
-        let description = "Built-in Audio Analog Stereo";
-
-        let display_name = if source_name.ends_with(".monitor") {
-            format!("[Monitor] {}", description)
-        } else {
-            description.to_string()
-        };
-
+        let monitor = source(
+            "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor",
+            "Monitor of Built-in Audio Analog Stereo",
+            Some("alsa_output.pci-0000_00_1f.3.analog-stereo"),
+        );
+        let display_name = source_display_name(&monitor);
         assert!(display_name.starts_with("[Monitor]"));
         assert!(display_name.contains("Built-in Audio"));
     }
 
     #[test]
+    #[cfg(target_os = "linux")]
     fn test_non_monitor_source_has_no_prefix() {
-        let source_name = "alsa_input.pci-0000_00_1f.3.analog-stereo";
-        let description = "Built-in Audio Analog Stereo";
+        let mic = source(
+            "alsa_input.pci-0000_00_1f.3.analog-stereo",
+            "Built-in Audio Analog Stereo",
+            None,
+        );
+        assert_eq!(source_display_name(&mic), "Built-in Audio Analog Stereo");
+    }
 
-        let display_name = if source_name.ends_with(".monitor") {
-            format!("[Monitor] {}", description)
-        } else {
-            description.to_string()
-        };
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_resolve_source() {
+        let sources = [
+            source(
+                "alsa_input.usb-Yeti-00.analog-stereo",
+                "Yeti Stereo Microphone",
+                None,
+            ),
+            source(
+                "alsa_output.hdmi.monitor",
+                "Monitor of HDMI Audio",
+                Some("alsa_output.hdmi"),
+            ),
+        ];
+        let resolve = |name| resolve_source(&sources, name).map(|s| s.name.as_str());
+
+        assert_eq!(
+            resolve("alsa_input.usb-Yeti-00.analog-stereo"),
+            Some("alsa_input.usb-Yeti-00.analog-stereo")
+        );
+        assert_eq!(
+            resolve("Yeti Stereo Microphone"),
+            Some("alsa_input.usb-Yeti-00.analog-stereo")
+        );
+        assert_eq!(
+            resolve("[Monitor] Monitor of HDMI Audio"),
+            Some("alsa_output.hdmi.monitor")
+        );
+        assert_eq!(resolve("yeti"), Some("alsa_input.usb-Yeti-00.analog-stereo"));
+        assert_eq!(resolve("hw:1,0"), None);
+    }
 
-        assert!(!display_name.starts_with("[Monitor]"));
-        assert_eq!(display_name, description);
+    #[test]
+    fn test_recent_audio_downmixes_and_resamples() {
+        // 1s of 48kHz stereo: left channel at 0.5, right at -0.1, then a partial frame
+        let mut buffer: Vec<f32> = (0..48000).flat_map(|_| [0.5, -0.1]).collect();
+        buffer.push(1.0);
+
+        let tail = recent_audio(&buffer, 300, 48000, 2);
+        assert_eq!(tail.len(), 4800);
+        assert!(tail.iter().all(|s| (s - 0.2).abs() < 1e-6));
+
+        // Asking for more than was captured returns everything
+        assert_eq!(recent_audio(&buffer, 5000, 48000, 2).len(), 16000);
+        assert_eq!(recent_audio(&[0.1; 1600], 50, 16000, 1).len(), 800);
+    }
+
+    #[test]
+    fn test_channel_stats_splits_interleaved_audio() {
+        // 1s of 48kHz stereo with a clipping left channel and a dead right one
+        let samples: Vec<f32> = (0..48000).flat_map(|_| [1.0, 0.0]).collect();
+        let stats = channel_stats(&samples, 48000, 2);
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].duration_ms, 1000);
+        assert_eq!(stats[0].clipped_pct, 100.0);
+        assert_eq!(stats[1].peak, 0.0);
+        assert_eq!(stats[1].longest_silence_ms, 1000);
     }
 
     #[test]
@@ -669,7 +985,7 @@ mod tests {
                 "expected .monitor suffix, got: {}",
                 source
             ),
-            None => println!("pactl unavailable or no default sink — skipping assertion"),
+            None => println!("sound server unavailable or no default sink — skipping assertion"),
         }
     }
 }
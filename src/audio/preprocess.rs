@@ -0,0 +1,286 @@
+//! Signal conditioning before inference
+//!
+//! Quiet laptop mics and cheap USB interfaces hand Whisper audio at -40 dBFS with
+//! rumble and a DC offset, which costs accuracy. The chain here runs on 16kHz mono
+//! samples in this order:
+//!
+//! 1. High-pass filter (removes DC offset and low-frequency rumble)
+//! 2. Noise gate (attenuates frames below a threshold between words)
+//! 3. Peak or loudness normalization (with a gain cap so noise isn't blown up)
+
+use serde::{Deserialize, Serialize};
+
+/// Frame size for gating and loudness measurement
+const FRAME_MS: usize = 10;
+
+/// Gate stays open this long after the level drops, so word tails survive
+const GATE_HOLD_MS: usize = 150;
+
+/// Gain applied to gated frames (-30 dB rather than hard silence)
+const GATE_FLOOR: f32 = 0.03;
+
+/// Frames below this level don't count towards loudness
+const LOUDNESS_FLOOR_DBFS: f32 = -50.0;
+
+/// Normalization never pushes peaks above this (-1 dBFS)
+const PEAK_CEILING: f32 = 0.891;
+
+/// Normalization strategy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalize {
+    /// Leave the gain alone
+    Off,
+    /// Scale so the loudest sample hits the target
+    #[default]
+    Peak,
+    /// Scale so the RMS of non-silent frames hits the target
+    Loudness,
+}
+
+impl Normalize {
+    /// Default target level for this mode in dBFS
+    pub fn default_target_dbfs(self) -> f32 {
+        match self {
+            Normalize::Loudness => -20.0,
+            Normalize::Off | Normalize::Peak => -3.0,
+        }
+    }
+}
+
+/// Preprocessing chain settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Preprocess {
+    /// High-pass cutoff in Hz (None = off)
+    pub high_pass_hz: Option<f32>,
+    /// Frames below this level are attenuated (None = off)
+    pub noise_gate_dbfs: Option<f32>,
+    pub normalize: Normalize,
+    /// Peak target (peak mode) or RMS target (loudness mode)
+    pub target_dbfs: f32,
+    /// Upper bound on normalization gain
+    pub max_gain_db: f32,
+}
+
+impl Default for Preprocess {
+    fn default() -> Self {
+        Self {
+            high_pass_hz: Some(80.0),
+            noise_gate_dbfs: None,
+            normalize: Normalize::Peak,
+            target_dbfs: Normalize::Peak.default_target_dbfs(),
+            max_gain_db: 20.0,
+        }
+    }
+}
+
+impl Preprocess {
+    /// Run the chain in place
+    pub fn apply(&self, samples: &mut [f32], sample_rate: u32) {
+        if samples.is_empty() || sample_rate == 0 {
+            return;
+        }
+        if let Some(cutoff) = self.high_pass_hz {
+            high_pass(samples, sample_rate, cutoff);
+        }
+        if let Some(threshold) = self.noise_gate_dbfs {
+            noise_gate(samples, sample_rate, threshold);
+        }
+        let gain = normalization_gain(samples, sample_rate, self);
+        if gain != 1.0 {
+            samples.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+}
+
+fn db_to_amplitude(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn frame_len(sample_rate: u32) -> usize {
+    (sample_rate as usize * FRAME_MS / 1000).max(1)
+}
+
+fn rms(frame: &[f32]) -> f32 {
+    (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt()
+}
+
+/// First-order high-pass filter. Starting from the first sample avoids a step
+/// transient when the input carries a DC offset.
+fn high_pass(samples: &mut [f32], sample_rate: u32, cutoff_hz: f32) {
+    if cutoff_hz <= 0.0 {
+        return;
+    }
+    let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff_hz);
+    let dt = 1.0 / sample_rate as f32;
+    let alpha = rc / (rc + dt);
+
+    let mut prev_in = samples[0];
+    let mut prev_out = 0.0;
+    for s in samples.iter_mut() {
+        let out = alpha * (prev_out + *s - prev_in);
+        prev_in = *s;
+        prev_out = out;
+        *s = out;
+    }
+}
+
+/// Attenuate frames below `threshold_dbfs`, holding the gate open briefly around
+/// speech and ramping gain across each frame to avoid clicks
+fn noise_gate(samples: &mut [f32], sample_rate: u32, threshold_dbfs: f32) {
+    let len = frame_len(sample_rate);
+    let threshold = db_to_amplitude(threshold_dbfs);
+    let hold = GATE_HOLD_MS / FRAME_MS;
+
+    let loud: Vec<bool> = samples.chunks(len).map(|f| rms(f) >= threshold).collect();
+    let open: Vec<bool> = (0..loud.len())
+        .map(|i| {
+            let from = i.saturating_sub(hold);
+            let to = (i + hold + 1).min(loud.len());
+            loud[from..to].iter().any(|&l| l)
+        })
+        .collect();
+
+    let mut prev_gain = if open[0] { 1.0 } else { GATE_FLOOR };
+    for (frame, &is_open) in samples.chunks_mut(len).zip(&open) {
+        let gain = if is_open { 1.0 } else { GATE_FLOOR };
+        let n = frame.len() as f32;
+        for (i, s) in frame.iter_mut().enumerate() {
+            *s *= prev_gain + (gain - prev_gain) * (i + 1) as f32 / n;
+        }
+        prev_gain = gain;
+    }
+}
+
+/// Gain that brings the signal to the target, capped by `max_gain_db` and the peak ceiling
+fn normalization_gain(samples: &[f32], sample_rate: u32, settings: &Preprocess) -> f32 {
+    let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+    if peak == 0.0 {
+        return 1.0;
+    }
+
+    let target = db_to_amplitude(settings.target_dbfs);
+    let gain = match settings.normalize {
+        Normalize::Off => return 1.0,
+        Normalize::Peak => target / peak,
+        Normalize::Loudness => {
+            let floor = db_to_amplitude(LOUDNESS_FLOOR_DBFS);
+            let active: Vec<f32> = samples
+                .chunks(frame_len(sample_rate))
+                .map(rms)
+                .filter(|&r| r >= floor)
+                .collect();
+            if active.is_empty() {
+                return 1.0;
+            }
+            let level = (active.iter().map(|r| r * r).sum::<f32>() / active.len() as f32).sqrt();
+            target / level
+        },
+    };
+
+    gain.min(db_to_amplitude(settings.max_gain_db))
+        .min(PEAK_CEILING / peak)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(amplitude: f32, freq: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0f32, |max, s| max.max(s.abs()))
+    }
+
+    fn mean(samples: &[f32]) -> f32 {
+        samples.iter().sum::<f32>() / samples.len() as f32
+    }
+
+    #[test]
+    fn test_high_pass_removes_dc_and_keeps_speech_band() {
+        let mut samples: Vec<f32> = tone(0.2, 1000.0, 16000).iter().map(|s| s + 0.3).collect();
+        high_pass(&mut samples, 16000, 80.0);
+
+        // Once settled, the offset is gone and the 1kHz tone is intact
+        let settled = &samples[4000..];
+        assert!(mean(settled).abs() < 0.005, "dc {}", mean(settled));
+        assert!((peak(settled) - 0.2).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_peak_normalization_with_gain_cap() {
+        let settings = Preprocess {
+            high_pass_hz: None,
+            ..Preprocess::default()
+        };
+
+        let mut quiet = tone(0.1, 440.0, 16000);
+        settings.apply(&mut quiet, 16000);
+        assert!((peak(&quiet) - db_to_amplitude(-3.0)).abs() < 0.01);
+
+        // -60 dBFS would need +57 dB; the cap holds it to +20 dB
+        let mut faint = tone(0.001, 440.0, 16000);
+        settings.apply(&mut faint, 16000);
+        assert!((peak(&faint) - 0.01).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_loudness_normalization_ignores_silence() {
+        let settings = Preprocess {
+            high_pass_hz: None,
+            normalize: Normalize::Loudness,
+            target_dbfs: -20.0,
+            ..Preprocess::default()
+        };
+        let mut samples = tone(0.05, 440.0, 8000);
+        samples.extend(vec![0.0; 24000]);
+        settings.apply(&mut samples, 16000);
+
+        let speech_rms = rms(&samples[..8000]);
+        assert!((speech_rms - db_to_amplitude(-20.0)).abs() < 0.005);
+    }
+
+    #[test]
+    fn test_normalization_respects_peak_ceiling() {
+        let settings = Preprocess {
+            high_pass_hz: None,
+            normalize: Normalize::Loudness,
+            target_dbfs: -3.0,
+            ..Preprocess::default()
+        };
+        let mut samples = tone(0.5, 440.0, 16000);
+        settings.apply(&mut samples, 16000);
+        assert!(peak(&samples) <= PEAK_CEILING + 1e-4);
+    }
+
+    #[test]
+    fn test_noise_gate_attenuates_gaps_only() {
+        let mut samples = vec![0.002; 16000];
+        samples.extend(tone(0.3, 440.0, 8000));
+        noise_gate(&mut samples, 16000, -40.0);
+
+        // Noise well before the speech is attenuated, speech is untouched
+        assert!(peak(&samples[..8000]) < 0.002 * GATE_FLOOR + 1e-6);
+        assert!((peak(&samples[16000..]) - 0.3).abs() < 0.01);
+        // Hold keeps the frames right before speech open
+        assert!((samples[16000 - 800] - 0.002).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_off_chain_is_identity() {
+        let settings = Preprocess {
+            high_pass_hz: None,
+            noise_gate_dbfs: None,
+            normalize: Normalize::Off,
+            ..Preprocess::default()
+        };
+        let original = tone(0.2, 440.0, 1600);
+        let mut samples = original.clone();
+        settings.apply(&mut samples, 16000);
+        assert_eq!(samples, original);
+    }
+}
@@ -0,0 +1,364 @@
+//! Always-warm capture with a pre-roll ring buffer
+//!
+//! Opening a cpal stream takes a few hundred ms, which clips the first syllable when
+//! recording starts on a hotkey. `PreRoll` keeps the input stream open for the lifetime
+//! of the daemon and retains only the last `pre_roll_ms` of audio; when a recording
+//! starts, that window is prepended to the captured samples.
+//!
+//! This keeps the microphone open while the daemon runs, so it is opt-in
+//! (`audio.pre_roll_ms`, 0 = disabled). Audio outside a recording never leaves memory
+//! and is overwritten continuously.
+
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::health::{self, Action, StreamWatch, Supervisor};
+use super::{
+    AudioSetup, InputStream, TARGET_SAMPLE_RATE, TrailingBuffer, buffer_trailing_audio,
+    finalize_audio_samples, levels, recent_audio, setup_audio_device, to_mono, wait_for_stop,
+};
+
+/// Samples shared between the stream callback and the recording thread
+struct RingState {
+    /// Most recent interleaved samples while idle
+    ring: VecDeque<f32>,
+    /// Ring capacity in interleaved samples
+    capacity: usize,
+    /// Samples of the active recording (pre-roll included)
+    recording: Option<Vec<f32>>,
+    /// Format of the current stream
+    sample_rate: u32,
+    channels: u16,
+    /// Bumped when the stream is replaced; callbacks of older streams are ignored
+    generation: u64,
+    /// Recorded audio from replaced streams, already at 16kHz mono
+    earlier: Vec<f32>,
+}
+
+impl RingState {
+    fn new(capacity: usize, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            ring: VecDeque::with_capacity(capacity),
+            capacity,
+            recording: None,
+            sample_rate,
+            channels,
+            generation: 0,
+            earlier: Vec::new(),
+        }
+    }
+
+    /// Prepare for a new stream. A format change discards the pre-roll and converts
+    /// the recording so far, since samples of different formats can't be mixed.
+    /// Returns the generation the new stream's callback must push with.
+    fn replace_stream(&mut self, capacity: usize, sample_rate: u32, channels: u16) -> u64 {
+        self.generation += 1;
+        if (sample_rate, channels) == (self.sample_rate, self.channels) {
+            return self.generation;
+        }
+
+        if let Some(recording) = self.recording.as_mut() {
+            let mono_samples = to_mono(std::mem::take(recording), self.channels);
+            match finalize_audio_samples(mono_samples, self.sample_rate, TARGET_SAMPLE_RATE) {
+                Ok(samples) => self.earlier.extend(samples),
+                Err(e) => warn!("Dropping audio from the replaced stream: {:#}", e),
+            }
+        }
+        self.ring = VecDeque::with_capacity(capacity);
+        self.capacity = capacity;
+        self.sample_rate = sample_rate;
+        self.channels = channels;
+        self.generation
+    }
+
+    fn push(&mut self, data: &[f32]) {
+        if let Some(recording) = self.recording.as_mut() {
+            recording.extend_from_slice(data);
+            return;
+        }
+
+        // Only the tail of an oversized callback buffer fits
+        let data = &data[data.len().saturating_sub(self.capacity)..];
+        let overflow = (self.ring.len() + data.len()).saturating_sub(self.capacity);
+        self.ring.drain(..overflow);
+        self.ring.extend(data);
+    }
+
+    /// Start a recording seeded with the buffered pre-roll
+    fn begin(&mut self) {
+        self.earlier.clear();
+        self.recording = Some(self.ring.drain(..).collect());
+    }
+
+    /// Finish the recording and go back to ring buffering
+    fn end(&mut self) -> Vec<f32> {
+        self.recording.take().unwrap_or_default()
+    }
+}
+
+/// A warm input stream with a pre-roll ring buffer
+pub struct PreRoll {
+    state: Arc<Mutex<RingState>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PreRoll {
+    /// Open the input device and start buffering the last `pre_roll_ms` of audio.
+    ///
+    /// cpal streams are not `Send`, so the stream lives on a dedicated thread that
+    /// keeps it alive until the `PreRoll` is dropped.
+    pub fn start(device_name: Option<&str>, pre_roll_ms: u32) -> Result<Self> {
+        let device_name = device_name.map(str::to_string);
+        let stop = Arc::new(AtomicBool::new(false));
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let thread_stop = stop.clone();
+        let thread = std::thread::spawn(move || {
+            let opened = setup_audio_device(device_name.as_deref()).and_then(|setup| {
+                let capacity = ring_capacity(setup.sample_rate, setup.channels, pre_roll_ms);
+                let state = Arc::new(Mutex::new(RingState::new(
+                    capacity,
+                    setup.sample_rate,
+                    setup.channels,
+                )));
+                let watch = StreamWatch::new();
+                let stream = build_ring_stream(&setup, state.clone(), 0, &watch)?;
+                Ok((stream, watch, state))
+            });
+
+            let (stream, watch, state) = match opened {
+                Ok(opened) => opened,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                },
+            };
+            levels::start();
+            health::opened(device_name.as_deref(), false);
+            let _ = ready_tx.send(Ok(state.clone()));
+
+            let mut current = Some((stream, watch));
+            let mut supervisor = Supervisor::new(device_name.as_deref(), false, Instant::now());
+            let open = |name: Option<&str>| reopen_ring_stream(name, &state, pre_roll_ms);
+            while !thread_stop.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(100));
+
+                if let Some(reason) = current.as_ref().and_then(|(_, watch)| watch.failure()) {
+                    warn!("Pre-roll input stream failed ({}), reconnecting", reason);
+                    health::failed(&reason);
+                    current = None;
+                }
+                match supervisor.check(Instant::now(), current.is_none()) {
+                    Action::Continue => {},
+                    Action::Reconnect => current = supervisor.reconnect(open),
+                    Action::ProbeConfigured => {
+                        if let Some(reopened) = supervisor.probe_configured(open) {
+                            current = Some(reopened);
+                        }
+                    },
+                }
+            }
+            drop(current);
+            levels::stop();
+            health::closed();
+        });
+
+        let state = ready_rx
+            .recv()
+            .context("Pre-roll capture thread exited unexpectedly")??;
+
+        info!(
+            "Pre-roll enabled: microphone stays open, keeping the last {}ms in memory",
+            pre_roll_ms
+        );
+
+        Ok(Self {
+            state,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Toggle-mode capture on the warm stream, with the pre-roll prepended.
+    /// Returns f32 PCM samples at 16kHz mono like `capture_toggle`.
+    pub fn capture_toggle(
+        &self,
+        max_duration_secs: u32,
+        trailing: TrailingBuffer,
+    ) -> Result<Vec<f32>> {
+        info!(
+            "Starting toggle mode capture with pre-roll (max {}s)",
+            max_duration_secs
+        );
+
+        self.lock()?.begin();
+        info!("Recording started - speak now!");
+
+        wait_for_stop(max_duration_secs, || {});
+        buffer_trailing_audio(trailing, |ms| {
+            self.lock()
+                .ok()
+                .and_then(|state| {
+                    let recording = state.recording.as_ref()?;
+                    Some(recent_audio(
+                        recording,
+                        ms,
+                        state.sample_rate,
+                        state.channels,
+                    ))
+                })
+                .unwrap_or_default()
+        });
+
+        let (earlier, samples, sample_rate, channels) = {
+            let mut state = self.lock()?;
+            let samples = state.end();
+            (
+                std::mem::take(&mut state.earlier),
+                samples,
+                state.sample_rate,
+                state.channels,
+            )
+        };
+        info!(
+            "Captured {} samples ({:.2}s at {}Hz, pre-roll included)",
+            samples.len(),
+            samples.len() as f32 / (sample_rate * channels as u32) as f32,
+            sample_rate
+        );
+
+        let mono_samples = to_mono(samples, channels);
+        let mut all = earlier;
+        all.extend(finalize_audio_samples(
+            mono_samples,
+            sample_rate,
+            TARGET_SAMPLE_RATE,
+        )?);
+        Ok(all)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, RingState>> {
+        self.state
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Pre-roll buffer mutex poisoned: {}", e))
+    }
+}
+
+impl Drop for PreRoll {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                warn!("Pre-roll capture thread panicked");
+            }
+        }
+        info!("Pre-roll capture stopped, microphone released");
+    }
+}
+
+/// Ring size in interleaved samples for the device's rate and channel count
+fn ring_capacity(sample_rate: u32, channels: u16, pre_roll_ms: u32) -> usize {
+    (sample_rate as usize * pre_roll_ms as usize / 1000) * channels as usize
+}
+
+/// Open a replacement stream feeding the existing ring
+fn reopen_ring_stream(
+    device_name: Option<&str>,
+    state: &Arc<Mutex<RingState>>,
+    pre_roll_ms: u32,
+) -> Result<(InputStream, StreamWatch)> {
+    let setup = setup_audio_device(device_name)?;
+    let capacity = ring_capacity(setup.sample_rate, setup.channels, pre_roll_ms);
+    let generation = state
+        .lock()
+        .map_err(|e| anyhow::anyhow!("Pre-roll buffer mutex poisoned: {}", e))?
+        .replace_stream(capacity, setup.sample_rate, setup.channels);
+    let watch = StreamWatch::new();
+    let stream = build_ring_stream(&setup, state.clone(), generation, &watch)?;
+    Ok((stream, watch))
+}
+
+fn build_ring_stream(
+    setup: &AudioSetup,
+    state: Arc<Mutex<RingState>>,
+    generation: u64,
+    watch: &StreamWatch,
+) -> Result<InputStream> {
+    setup.start_stream(
+        move |data| {
+            if let Ok(mut state) = state.lock() {
+                if state.generation == generation {
+                    state.push(data);
+                }
+            }
+        },
+        watch,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_keeps_latest_samples() {
+        let mut state = RingState::new(4, 16000, 1);
+        state.push(&[1.0, 2.0, 3.0]);
+        state.push(&[4.0, 5.0, 6.0]);
+        assert_eq!(state.ring, [3.0, 4.0, 5.0, 6.0]);
+
+        // Callback buffers larger than the ring keep only their tail
+        state.push(&[7.0, 8.0, 9.0, 10.0, 11.0]);
+        assert_eq!(state.ring, [8.0, 9.0, 10.0, 11.0]);
+    }
+
+    #[test]
+    fn test_recording_starts_with_pre_roll() {
+        let mut state = RingState::new(2, 16000, 1);
+        state.push(&[1.0, 2.0, 3.0]);
+
+        state.begin();
+        state.push(&[4.0, 5.0, 6.0]);
+        assert_eq!(state.end(), [2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        // Back to ring buffering, with the old pre-roll consumed
+        assert!(state.ring.is_empty());
+        state.push(&[7.0]);
+        assert_eq!(state.ring, [7.0]);
+        assert!(state.recording.is_none());
+    }
+
+    #[test]
+    fn test_format_change_converts_recording() {
+        let mut state = RingState::new(4, 16000, 1);
+        state.begin();
+        state.push(&[0.5; 1600]);
+
+        // Same format: the recording simply continues
+        let generation = state.replace_stream(4, 16000, 1);
+        assert_eq!(generation, 1);
+        state.push(&[0.5; 1600]);
+        assert_eq!(state.recording.as_ref().unwrap().len(), 3200);
+        assert!(state.earlier.is_empty());
+
+        // Stereo 48kHz: the mono 16kHz part moves to `earlier`, the ring restarts
+        state.replace_stream(8, 48000, 2);
+        assert_eq!(state.earlier.len(), 3200);
+        assert_eq!(state.recording.as_deref(), Some(&[][..]));
+        assert_eq!(
+            (state.capacity, state.sample_rate, state.channels),
+            (8, 48000, 2)
+        );
+
+        state.end();
+        state.begin();
+        assert!(state.earlier.is_empty());
+    }
+}
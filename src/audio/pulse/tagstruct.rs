@@ -0,0 +1,341 @@
+//! PulseAudio tagstruct encoding
+//!
+//! Every command and reply on the native protocol is a "tagstruct": a sequence of
+//! values, each prefixed by a one-byte type tag. Integers are big-endian.
+
+use anyhow::{Result, bail};
+
+const TAG_STRING: u8 = b't';
+const TAG_STRING_NULL: u8 = b'N';
+const TAG_U32: u8 = b'L';
+const TAG_U8: u8 = b'B';
+const TAG_U64: u8 = b'R';
+const TAG_S64: u8 = b'r';
+const TAG_SAMPLE_SPEC: u8 = b'a';
+const TAG_ARBITRARY: u8 = b'x';
+const TAG_BOOLEAN_TRUE: u8 = b'1';
+const TAG_BOOLEAN_FALSE: u8 = b'0';
+const TAG_TIMEVAL: u8 = b'T';
+const TAG_USEC: u8 = b'U';
+const TAG_CHANNEL_MAP: u8 = b'm';
+const TAG_CVOLUME: u8 = b'v';
+const TAG_PROPLIST: u8 = b'P';
+const TAG_VOLUME: u8 = b'V';
+const TAG_FORMAT_INFO: u8 = b'f';
+
+/// Sample format, channel count and rate of a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleSpec {
+    pub format: u8,
+    pub channels: u8,
+    pub rate: u32,
+}
+
+/// Builder for an outgoing tagstruct
+#[derive(Debug, Default)]
+pub struct TagWriter {
+    data: Vec<u8>,
+}
+
+impl TagWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.data.push(TAG_U32);
+        self.data.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    pub fn bool(&mut self, value: bool) -> &mut Self {
+        self.data.push(if value {
+            TAG_BOOLEAN_TRUE
+        } else {
+            TAG_BOOLEAN_FALSE
+        });
+        self
+    }
+
+    pub fn string(&mut self, value: Option<&str>) -> &mut Self {
+        match value {
+            Some(s) => {
+                self.data.push(TAG_STRING);
+                self.data.extend_from_slice(s.as_bytes());
+                self.data.push(0);
+            },
+            None => self.data.push(TAG_STRING_NULL),
+        }
+        self
+    }
+
+    pub fn arbitrary(&mut self, bytes: &[u8]) -> &mut Self {
+        self.data.push(TAG_ARBITRARY);
+        self.data
+            .extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        self.data.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn sample_spec(&mut self, spec: SampleSpec) -> &mut Self {
+        self.data.push(TAG_SAMPLE_SPEC);
+        self.data.push(spec.format);
+        self.data.push(spec.channels);
+        self.data.extend_from_slice(&spec.rate.to_be_bytes());
+        self
+    }
+
+    pub fn channel_map(&mut self, positions: &[u8]) -> &mut Self {
+        self.data.push(TAG_CHANNEL_MAP);
+        self.data.push(positions.len() as u8);
+        self.data.extend_from_slice(positions);
+        self
+    }
+
+    /// String properties; values are stored NUL-terminated like libpulse does
+    pub fn proplist(&mut self, props: &[(&str, &str)]) -> &mut Self {
+        self.data.push(TAG_PROPLIST);
+        for (key, value) in props {
+            let mut bytes = value.as_bytes().to_vec();
+            bytes.push(0);
+            self.string(Some(key));
+            self.u32(bytes.len() as u32);
+            self.arbitrary(&bytes);
+        }
+        self.string(None)
+    }
+}
+
+/// Sequential reader over an incoming tagstruct
+pub struct TagReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> TagReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    /// Bytes not read yet
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.data.len().saturating_sub(self.pos) < n {
+            bail!("Truncated tagstruct");
+        }
+        let bytes = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn tag(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<()> {
+        let tag = self.tag()?;
+        if tag != expected {
+            bail!(
+                "Unexpected tagstruct value '{}', expected '{}'",
+                tag as char,
+                expected as char
+            );
+        }
+        Ok(())
+    }
+
+    fn be_u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        self.expect(TAG_U32)?;
+        self.be_u32()
+    }
+
+    pub fn string(&mut self) -> Result<Option<String>> {
+        match self.tag()? {
+            TAG_STRING_NULL => Ok(None),
+            TAG_STRING => {
+                let rest = &self.data[self.pos..];
+                let len = rest
+                    .iter()
+                    .position(|&b| b == 0)
+                    .ok_or_else(|| anyhow::anyhow!("Unterminated tagstruct string"))?;
+                let s = String::from_utf8_lossy(&rest[..len]).into_owned();
+                self.pos += len + 1;
+                Ok(Some(s))
+            },
+            tag => bail!(
+                "Unexpected tagstruct value '{}', expected string",
+                tag as char
+            ),
+        }
+    }
+
+    pub fn sample_spec(&mut self) -> Result<SampleSpec> {
+        self.expect(TAG_SAMPLE_SPEC)?;
+        let bytes = self.take(2)?;
+        Ok(SampleSpec {
+            format: bytes[0],
+            channels: bytes[1],
+            rate: self.be_u32()?,
+        })
+    }
+
+    fn arbitrary(&mut self) -> Result<&'a [u8]> {
+        self.expect(TAG_ARBITRARY)?;
+        let len = self.be_u32()? as usize;
+        self.take(len)
+    }
+
+    /// String properties; values that aren't NUL-terminated text are skipped
+    pub fn proplist(&mut self) -> Result<Vec<(String, String)>> {
+        self.expect(TAG_PROPLIST)?;
+        let mut props = Vec::new();
+        while let Some(key) = self.string()? {
+            let len = self.u32()? as usize;
+            let value = self.arbitrary()?;
+            if value.len() != len {
+                bail!("Proplist value length mismatch for '{}'", key);
+            }
+            if let Some((0, text)) = value.split_last() {
+                props.push((key, String::from_utf8_lossy(text).into_owned()));
+            }
+        }
+        Ok(props)
+    }
+
+    /// Skip one value of any type
+    pub fn skip(&mut self) -> Result<()> {
+        let tag = self.data.get(self.pos).copied();
+        match tag {
+            Some(TAG_STRING | TAG_STRING_NULL) => {
+                self.string()?;
+            },
+            Some(TAG_PROPLIST) => {
+                self.proplist()?;
+            },
+            Some(TAG_ARBITRARY) => {
+                self.arbitrary()?;
+            },
+            Some(TAG_FORMAT_INFO) => {
+                self.pos += 1;
+                self.expect(TAG_U8)?;
+                self.take(1)?;
+                self.proplist()?;
+            },
+            Some(tag) => {
+                self.pos += 1;
+                match tag {
+                    TAG_U32 | TAG_VOLUME => self.pos += 4,
+                    TAG_U8 => self.pos += 1,
+                    TAG_U64 | TAG_S64 | TAG_USEC | TAG_TIMEVAL => self.pos += 8,
+                    TAG_SAMPLE_SPEC => self.pos += 6,
+                    TAG_BOOLEAN_TRUE | TAG_BOOLEAN_FALSE => {},
+                    TAG_CHANNEL_MAP => {
+                        let n = self.take(1)?[0] as usize;
+                        self.pos += n;
+                    },
+                    TAG_CVOLUME => {
+                        let n = self.take(1)?[0] as usize;
+                        self.pos += n * 4;
+                    },
+                    _ => bail!("Unknown tagstruct type '{}'", tag as char),
+                }
+                if self.pos > self.data.len() {
+                    bail!("Truncated tagstruct");
+                }
+            },
+            None => bail!("Truncated tagstruct"),
+        }
+        Ok(())
+    }
+
+    pub fn skip_n(&mut self, n: usize) -> Result<()> {
+        (0..n).try_for_each(|_| self.skip())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let spec = SampleSpec {
+            format: 5,
+            channels: 1,
+            rate: 16000,
+        };
+        let mut writer = TagWriter::new();
+        writer
+            .u32(42)
+            .string(Some("alsa_input.usb"))
+            .string(None)
+            .bool(true)
+            .sample_spec(spec)
+            .channel_map(&[0])
+            .proplist(&[("application.name", "mojovoice")]);
+        let bytes = writer.into_bytes();
+
+        let mut reader = TagReader::new(&bytes);
+        assert_eq!(reader.u32().unwrap(), 42);
+        assert_eq!(reader.string().unwrap().as_deref(), Some("alsa_input.usb"));
+        assert_eq!(reader.string().unwrap(), None);
+        reader.skip().unwrap();
+        assert_eq!(reader.sample_spec().unwrap(), spec);
+        reader.skip().unwrap();
+        assert_eq!(
+            reader.proplist().unwrap(),
+            [("application.name".to_string(), "mojovoice".to_string())]
+        );
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_skip_fixed_and_variable_values() {
+        // cvolume with 2 channels, usec, volume, channel map with 2 positions
+        let mut bytes = vec![b'v', 2, 0, 1, 0, 0, 0, 1, 0, 0];
+        bytes.push(b'U');
+        bytes.extend_from_slice(&1500u64.to_be_bytes());
+        bytes.extend_from_slice(&[b'V', 0, 1, 0, 0]);
+        bytes.extend_from_slice(&[b'm', 2, 1, 2]);
+        let mut writer = TagWriter::new();
+        writer.u32(7);
+        bytes.extend(writer.into_bytes());
+
+        let mut reader = TagReader::new(&bytes);
+        reader.skip().unwrap();
+        reader.skip_n(3).unwrap();
+        assert_eq!(reader.u32().unwrap(), 7);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_type_mismatch_and_truncation_are_errors() {
+        let mut writer = TagWriter::new();
+        writer.bool(false);
+        assert!(TagReader::new(&writer.into_bytes()).u32().is_err());
+        assert!(TagReader::new(b"Z").skip().is_err());
+
+        let mut writer = TagWriter::new();
+        writer.u32(1);
+        let mut bytes = writer.into_bytes();
+        bytes.truncate(3);
+        assert!(TagReader::new(&bytes).u32().is_err());
+        assert!(TagReader::new(b"tunterminated").string().is_err());
+    }
+}
@@ -0,0 +1,444 @@
+//! Energy-based voice activity detection
+//!
+//! Splits 16kHz mono audio into speech regions at pauses. The threshold adapts to the
+//! recording's noise floor, so it works for quiet laptop mics and hot headsets alike.
+//! [`StreamSegmenter`] does the same for audio that arrives incrementally and never ends.
+
+use std::collections::VecDeque;
+
+/// Analysis frame length (30ms at 16kHz)
+const FRAME_SAMPLES: usize = 480;
+
+/// RMS below this is always treated as silence, regardless of noise floor
+const MIN_SPEECH_RMS: f32 = 0.005;
+
+/// Frames of recent audio the streaming threshold is estimated from (10s)
+const STREAM_HISTORY_FRAMES: usize = 333;
+
+/// Frames must be this many times louder than the noise floor to count as speech
+const NOISE_FLOOR_FACTOR: f32 = 3.0;
+
+/// Threshold never exceeds this fraction of the loudest frame (audio with no pauses)
+const PEAK_FRACTION: f32 = 0.25;
+
+/// Segmentation tuning
+#[derive(Debug, Clone, Copy)]
+pub struct VadParams {
+    /// Sample rate of the input audio
+    pub sample_rate: u32,
+    /// Pauses shorter than this do not split a region
+    pub min_silence_ms: u32,
+    /// Regions shorter than this are dropped as noise
+    pub min_speech_ms: u32,
+    /// Padding kept before and after each speech run (not at cuts inside a long one)
+    pub padding_ms: u32,
+    /// Regions longer than this are split at their quietest point
+    pub max_segment_ms: u32,
+}
+
+impl Default for VadParams {
+    fn default() -> Self {
+        Self {
+            sample_rate: 16000,
+            min_silence_ms: 500,
+            min_speech_ms: 250,
+            padding_ms: 200,
+            // Stay under Whisper's 30s window so each region decodes in one pass
+            max_segment_ms: 25_000,
+        }
+    }
+}
+
+/// A span of speech, as sample offsets into the analysed audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeechRegion {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// RMS of each analysis frame (the last partial frame included)
+pub fn frame_rms(samples: &[f32]) -> Vec<f32> {
+    samples
+        .chunks(FRAME_SAMPLES)
+        .map(|frame| (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt())
+        .collect()
+}
+
+/// Speech threshold for a set of frame energies: a multiple of the 10th percentile,
+/// capped relative to the peak so continuous speech without pauses is still detected
+pub fn speech_threshold(rms: &[f32]) -> f32 {
+    if rms.is_empty() {
+        return MIN_SPEECH_RMS;
+    }
+    let mut sorted = rms.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let noise_floor = sorted[sorted.len() / 10];
+    let peak = sorted[sorted.len() - 1];
+    (noise_floor * NOISE_FLOOR_FACTOR)
+        .min(peak * PEAK_FRACTION)
+        .max(MIN_SPEECH_RMS)
+}
+
+/// True if every frame of `samples` is at or below `threshold` (empty audio is not silent)
+pub fn is_silent(samples: &[f32], threshold: f32) -> bool {
+    !samples.is_empty() && frame_rms(samples).iter().all(|r| *r <= threshold)
+}
+
+/// Find speech regions in 16kHz mono audio
+pub fn detect_speech(samples: &[f32], params: &VadParams) -> Vec<SpeechRegion> {
+    let rms = frame_rms(samples);
+    let threshold = speech_threshold(&rms);
+    let frames_per_ms = params.sample_rate as f32 / 1000.0 / FRAME_SAMPLES as f32;
+    let to_frames = |ms: u32| ((ms as f32 * frames_per_ms).ceil() as usize).max(1);
+
+    // Runs of speech frames, bridging pauses shorter than min_silence
+    let min_silence = to_frames(params.min_silence_ms);
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in rms.iter().enumerate().filter(|(_, r)| **r > threshold) {
+        match runs.last_mut() {
+            Some((_, end)) if i - *end <= min_silence => *end = i + 1,
+            _ => runs.push((i, i + 1)),
+        }
+    }
+
+    let min_speech = to_frames(params.min_speech_ms);
+    let max_frames = to_frames(params.max_segment_ms);
+    let padding = (params.padding_ms as usize * params.sample_rate as usize) / 1000;
+    let mut regions: Vec<SpeechRegion> = Vec::new();
+    for (start, end) in runs.into_iter().filter(|(s, e)| e - s >= min_speech) {
+        let mut pieces = Vec::new();
+        split_long_run(&rms, start, end, max_frames, &mut pieces);
+        let last = pieces.len() - 1;
+        for (i, (start, end)) in pieces.into_iter().enumerate() {
+            // Only the outer edges of a run are padded: pieces of a split run meet
+            // exactly, so words at a cut are not transcribed twice
+            let mut region = SpeechRegion {
+                start: start * FRAME_SAMPLES,
+                end: (end * FRAME_SAMPLES).min(samples.len()),
+            };
+            if i == 0 {
+                region.start = region.start.saturating_sub(padding);
+            }
+            if i == last {
+                region.end = (region.end + padding).min(samples.len());
+            }
+            if let Some(prev) = regions.last() {
+                region.start = region.start.max(prev.end);
+            }
+            regions.push(region);
+        }
+    }
+    regions
+}
+
+/// Split a frame run longer than `max_frames` at the quietest frame in its second half
+fn split_long_run(
+    rms: &[f32],
+    start: usize,
+    end: usize,
+    max_frames: usize,
+    out: &mut Vec<(usize, usize)>,
+) {
+    if end - start <= max_frames {
+        out.push((start, end));
+        return;
+    }
+
+    let search_from = start + max_frames / 2;
+    let search_to = start + max_frames;
+    let cut = (search_from..search_to)
+        .min_by(|&a, &b| rms[a].total_cmp(&rms[b]))
+        .unwrap_or(search_to);
+
+    out.push((start, cut));
+    split_long_run(rms, cut, end, max_frames, out);
+}
+
+/// A speech segment cut from a stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamSegment {
+    /// Offset of the first sample from the start of the stream
+    pub start: u64,
+    pub samples: Vec<f32>,
+}
+
+/// Incremental [`detect_speech`]: cuts a stream into segments as soon as a pause ends
+/// them.
+///
+/// Only the segment being built is kept, and it is cut at its quietest point once it
+/// reaches `max_segment_ms`, so memory stays bounded however long the stream runs.
+/// The threshold follows the noise floor of the last 10s.
+pub struct StreamSegmenter {
+    params: VadParams,
+    history: VecDeque<f32>,
+    /// Samples that don't fill a frame yet
+    partial: Vec<f32>,
+    /// Frames of the segment being built, leading padding included
+    pending: Vec<f32>,
+    /// RMS and speech flag of each pending frame
+    frames: Vec<(f32, bool)>,
+    /// Stream offset of `pending[0]`
+    pending_start: u64,
+}
+
+impl StreamSegmenter {
+    pub fn new(params: VadParams) -> Self {
+        Self {
+            params,
+            history: VecDeque::with_capacity(STREAM_HISTORY_FRAMES),
+            partial: Vec::new(),
+            pending: Vec::new(),
+            frames: Vec::new(),
+            pending_start: 0,
+        }
+    }
+
+    fn to_frames(&self, ms: u32) -> usize {
+        let frames_per_ms = self.params.sample_rate as f32 / 1000.0 / FRAME_SAMPLES as f32;
+        ((ms as f32 * frames_per_ms).ceil() as usize).max(1)
+    }
+
+    /// Feed audio, returning the segments it completed
+    pub fn push(&mut self, samples: &[f32]) -> Vec<StreamSegment> {
+        self.partial.extend_from_slice(samples);
+        let mut out = Vec::new();
+        let whole = self.partial.len() - self.partial.len() % FRAME_SAMPLES;
+        let partial = std::mem::take(&mut self.partial);
+        for frame in partial[..whole].chunks_exact(FRAME_SAMPLES) {
+            self.push_frame(frame, &mut out);
+        }
+        self.partial = partial[whole..].to_vec();
+        out
+    }
+
+    /// End of stream: the segment in progress, if it holds enough speech
+    pub fn flush(&mut self) -> Option<StreamSegment> {
+        let partial = std::mem::take(&mut self.partial);
+        self.pending.extend(partial);
+        let last_speech = self.frames.iter().rposition(|(_, speech)| *speech);
+        let segment = last_speech.and_then(|last| {
+            let end = (last + 1 + self.to_frames(self.params.padding_ms)) * FRAME_SAMPLES;
+            self.take_segment(end.min(self.pending.len()))
+        });
+        // Whatever follows the segment (or all of it, without speech) is discarded
+        self.pending_start += self.pending.len() as u64;
+        self.pending.clear();
+        self.frames.clear();
+        segment
+    }
+
+    fn push_frame(&mut self, frame: &[f32], out: &mut Vec<StreamSegment>) {
+        let rms = frame_rms(frame)[0];
+        if self.history.len() == STREAM_HISTORY_FRAMES {
+            self.history.pop_front();
+        }
+        self.history.push_back(rms);
+        let threshold = speech_threshold(self.history.make_contiguous());
+
+        self.pending.extend_from_slice(frame);
+        self.frames.push((rms, rms > threshold));
+
+        let padding = self.to_frames(self.params.padding_ms);
+        let Some(last_speech) = self.frames.iter().rposition(|(_, speech)| *speech) else {
+            // No speech yet: keep only the leading padding
+            let excess = self.frames.len().saturating_sub(padding);
+            self.drop_frames(excess);
+            return;
+        };
+
+        let silent = self.frames.len() - last_speech - 1;
+        let cut = if silent >= self.to_frames(self.params.min_silence_ms) {
+            (last_speech + 1 + padding).min(self.frames.len())
+        } else if self.frames.len() >= self.to_frames(self.params.max_segment_ms) {
+            let half = self.frames.len() / 2;
+            (half..self.frames.len())
+                .min_by(|&a, &b| self.frames[a].0.total_cmp(&self.frames[b].0))
+                .unwrap_or(half)
+        } else {
+            return;
+        };
+        out.extend(self.take_segment(cut * FRAME_SAMPLES));
+    }
+
+    /// Cut the first `end` pending samples off as a segment, dropping it if it is
+    /// too short to be speech
+    fn take_segment(&mut self, end: usize) -> Option<StreamSegment> {
+        let frames = end.div_ceil(FRAME_SAMPLES).min(self.frames.len());
+        let speech_frames = self.frames.drain(..frames).filter(|(_, speech)| *speech).count();
+        let start = self.pending_start;
+        let samples: Vec<f32> = self.pending.drain(..end).collect();
+        self.pending_start += end as u64;
+
+        (speech_frames >= self.to_frames(self.params.min_speech_ms))
+            .then_some(StreamSegment { start, samples })
+    }
+
+    fn drop_frames(&mut self, n: usize) {
+        self.pending.drain(..n * FRAME_SAMPLES);
+        self.frames.drain(..n);
+        self.pending_start += (n * FRAME_SAMPLES) as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(secs: f32) -> Vec<f32> {
+        (0..(secs * 16000.0) as usize)
+            .map(|i| (i as f32 * 0.1).sin() * 0.3)
+            .collect()
+    }
+
+    fn silence(secs: f32) -> Vec<f32> {
+        vec![0.0; (secs * 16000.0) as usize]
+    }
+
+    #[test]
+    fn test_detect_speech_splits_at_pauses() {
+        let audio = [
+            silence(1.0),
+            tone(1.0),
+            silence(1.0),
+            tone(2.0),
+            silence(1.0),
+        ]
+        .concat();
+        let regions = detect_speech(&audio, &VadParams::default());
+
+        assert_eq!(regions.len(), 2);
+        // First region starts near 1s (minus padding)
+        assert!((12000..=16000).contains(&regions[0].start), "{:?}", regions);
+        assert!(regions[1].end > 80000 && regions[1].end <= audio.len());
+    }
+
+    #[test]
+    fn test_detect_speech_bridges_short_pauses() {
+        let audio = [tone(1.0), silence(0.2), tone(1.0)].concat();
+        let regions = detect_speech(&audio, &VadParams::default());
+        assert_eq!(regions.len(), 1);
+    }
+
+    #[test]
+    fn test_is_silent() {
+        let threshold = speech_threshold(&frame_rms(&[silence(1.0), tone(1.0)].concat()));
+        assert!(is_silent(&silence(0.3), threshold));
+        assert!(!is_silent(&[silence(0.2), tone(0.1)].concat(), threshold));
+        // Nothing captured yet is not evidence of silence
+        assert!(!is_silent(&[], threshold));
+    }
+
+    #[test]
+    fn test_detect_speech_silence_only() {
+        assert!(detect_speech(&silence(2.0), &VadParams::default()).is_empty());
+        assert!(detect_speech(&[], &VadParams::default()).is_empty());
+    }
+
+    #[test]
+    fn test_detect_speech_drops_clicks() {
+        let audio = [silence(1.0), tone(0.05), silence(1.0)].concat();
+        assert!(detect_speech(&audio, &VadParams::default()).is_empty());
+    }
+
+    #[test]
+    fn test_detect_speech_splits_long_regions() {
+        let params = VadParams {
+            max_segment_ms: 2000,
+            padding_ms: 0,
+            ..VadParams::default()
+        };
+        let regions = detect_speech(&tone(5.0), &params);
+        assert!(regions.len() >= 3, "{:?}", regions);
+        assert!(
+            regions
+                .iter()
+                .all(|r| r.end - r.start <= 32000 + FRAME_SAMPLES)
+        );
+    }
+
+    #[test]
+    fn test_detect_speech_split_regions_do_not_overlap() {
+        let params = VadParams {
+            max_segment_ms: 2000,
+            ..VadParams::default()
+        };
+        let audio = [silence(1.0), tone(5.0), silence(1.0)].concat();
+        let regions = detect_speech(&audio, &params);
+        assert!(regions.len() >= 3, "{:?}", regions);
+        // Padding only at the outer edges; the cuts in between meet exactly
+        assert!((12000..16000).contains(&regions[0].start), "{:?}", regions);
+        assert!(regions.last().unwrap().end > 96000, "{:?}", regions);
+        for pair in regions.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start, "{:?}", regions);
+        }
+    }
+
+    fn push_in_chunks(segmenter: &mut StreamSegmenter, audio: &[f32]) -> Vec<StreamSegment> {
+        // Odd chunk size so frames straddle pushes
+        audio.chunks(1234).flat_map(|c| segmenter.push(c)).collect()
+    }
+
+    #[test]
+    fn test_stream_segmenter_cuts_at_pauses() {
+        let audio = [
+            silence(1.0),
+            tone(1.0),
+            silence(1.0),
+            tone(2.0),
+            silence(0.2),
+        ]
+        .concat();
+        let mut segmenter = StreamSegmenter::new(VadParams::default());
+        let segments = push_in_chunks(&mut segmenter, &audio);
+
+        // The first segment completes at the pause; the second only at the end
+        assert_eq!(segments.len(), 1);
+        assert!((12000..=16000).contains(&segments[0].start), "{}", segments[0].start);
+        assert!(segments[0].samples.len() < 24000);
+
+        let last = segmenter.flush().unwrap();
+        assert!((44000..=48000).contains(&last.start), "{}", last.start);
+        assert!(segmenter.flush().is_none());
+    }
+
+    #[test]
+    fn test_stream_segmenter_drops_clicks_and_bounds_silence() {
+        let audio = [silence(1.0), tone(0.05), silence(30.0)].concat();
+        let mut segmenter = StreamSegmenter::new(VadParams::default());
+        assert!(push_in_chunks(&mut segmenter, &audio).is_empty());
+        // Only the leading padding is held while nobody speaks
+        assert!(segmenter.pending.len() <= 4000, "{}", segmenter.pending.len());
+        assert!(segmenter.flush().is_none());
+    }
+
+    #[test]
+    fn test_stream_segmenter_flush_without_speech_resets() {
+        let mut segmenter = StreamSegmenter::new(VadParams::default());
+        assert!(push_in_chunks(&mut segmenter, &silence(1.0)).is_empty());
+        assert!(segmenter.flush().is_none());
+        assert!(segmenter.pending.is_empty());
+        assert!(segmenter.frames.is_empty());
+
+        // Offsets still count the discarded audio
+        push_in_chunks(&mut segmenter, &[tone(1.0), silence(0.2)].concat());
+        let segment = segmenter.flush().unwrap();
+        assert!((12000..=16000).contains(&segment.start), "{}", segment.start);
+    }
+
+    #[test]
+    fn test_stream_segmenter_splits_long_speech() {
+        let params = VadParams {
+            max_segment_ms: 2000,
+            ..VadParams::default()
+        };
+        let mut segmenter = StreamSegmenter::new(params);
+        let segments = push_in_chunks(&mut segmenter, &tone(7.0));
+
+        assert!(segments.len() >= 3, "{}", segments.len());
+        assert!(segments.iter().all(|s| s.samples.len() <= 32000 + FRAME_SAMPLES));
+        // Cuts are contiguous: nothing is lost between segments
+        for pair in segments.windows(2) {
+            assert_eq!(pair[0].start + pair[0].samples.len() as u64, pair[1].start);
+        }
+    }
+}
@@ -17,7 +17,7 @@ use crate::daemon::{
     DaemonRequest, DaemonResponse, daemon_get_status, is_daemon_running, send_request,
 };
 
-use manifest::{load_audio_samples, load_manifest};
+use manifest::load_manifest;
 use metrics::{character_error_rate, exact_match, word_error_rate};
 use output::{
     BenchmarkInfo, BenchmarkResult, SampleResult, calculate_aggregates, create_output_dir,
@@ -84,7 +84,7 @@ pub fn run_benchmark(samples_dir: &Path, output_dir: &Path, stdout_only: bool) -
             sample.file
         );
 
-        match process_sample(&audio_path, sample) {
+        match process_sample(&audio_path, sample, None) {
             Ok(result) => {
                 println!(
                     "WER: {:.1}%, RTF: {:.3}{}",
@@ -229,17 +229,107 @@ pub fn run_benchmark(samples_dir: &Path, output_dir: &Path, stdout_only: bool) -
     Ok(())
 }
 
-/// Process a single audio sample.
-fn process_sample(audio_path: &Path, sample: &manifest::AudioSample) -> Result<SampleResult> {
-    // Load and resample audio
-    let audio_samples = load_audio_samples(audio_path)
+/// Run every sample with and without spectral noise suppression and compare accuracy.
+///
+/// Other daemon-side processing (`[audio.preprocess]`) stays as configured, so the
+/// difference isolates the denoiser.
+pub fn run_denoise_comparison(samples_dir: &Path) -> Result<()> {
+    if !is_daemon_running() {
+        anyhow::bail!("Daemon is not running. Start it first with: mojovoice daemon up");
+    }
+
+    let status = daemon_get_status()?;
+    let manifest = load_manifest(samples_dir)?;
+    println!(
+        "Comparing noise suppression on {} samples ({})",
+        manifest.samples.len(),
+        extract_model_name(&status.model_name)
+    );
+    println!();
+    println!("{:<32} {:>9} {:>9} {:>8}", "Sample", "WER off", "WER on", "Delta");
+    println!("{}", "-".repeat(61));
+
+    let mut plain = Vec::new();
+    let mut denoised = Vec::new();
+    for sample in &manifest.samples {
+        let audio_path = samples_dir.join(&sample.file);
+        let results = process_sample(&audio_path, sample, Some(false)).and_then(|off| {
+            process_sample(&audio_path, sample, Some(true)).map(|on| (off, on))
+        });
+
+        match results {
+            Ok((off, on)) => {
+                println!(
+                    "{:<32} {:>8.1}% {:>8.1}% {:>+7.1}%",
+                    sample.file,
+                    off.word_error_rate * 100.0,
+                    on.word_error_rate * 100.0,
+                    (on.word_error_rate - off.word_error_rate) * 100.0
+                );
+                plain.push(off);
+                denoised.push(on);
+            },
+            Err(e) => {
+                println!("{:<32} ERROR: {}", sample.file, e);
+                warn!("Failed to process {}: {}", sample.file, e);
+            },
+        }
+    }
+
+    if plain.is_empty() {
+        anyhow::bail!("No samples were successfully processed");
+    }
+
+    let off = calculate_aggregates(&plain);
+    let on = calculate_aggregates(&denoised);
+    println!();
+    println!("=== Summary ({} samples) ===", off.total_samples);
+    println!("{:<12} {:>9} {:>9} {:>8}", "", "off", "on", "delta");
+    println!(
+        "{:<12} {:>8.1}% {:>8.1}% {:>+7.1}%",
+        "Avg WER",
+        off.average_word_error_rate * 100.0,
+        on.average_word_error_rate * 100.0,
+        (on.average_word_error_rate - off.average_word_error_rate) * 100.0
+    );
+    println!(
+        "{:<12} {:>8.1}% {:>8.1}% {:>+7.1}%",
+        "Avg CER",
+        off.average_character_error_rate * 100.0,
+        on.average_character_error_rate * 100.0,
+        (on.average_character_error_rate - off.average_character_error_rate) * 100.0
+    );
+    println!(
+        "{:<12} {:>9.3} {:>9.3} {:>+8.3}",
+        "Avg RTF",
+        off.average_real_time_factor,
+        on.average_real_time_factor,
+        on.average_real_time_factor - off.average_real_time_factor
+    );
+    println!(
+        "{:<12} {:>9} {:>9}",
+        "Exact match", off.exact_match_count, on.exact_match_count
+    );
+
+    Ok(())
+}
+
+/// Process a single audio sample. `denoise` overrides `audio.denoise.files` when set.
+fn process_sample(
+    audio_path: &Path,
+    sample: &manifest::AudioSample,
+    denoise: Option<bool>,
+) -> Result<SampleResult> {
+    // Decode and denoise here so the timed interval covers only the transcription
+    let mut samples = crate::audio::load_audio(audio_path)
         .with_context(|| format!("Failed to load audio: {}", audio_path.display()))?;
+    let denoise_config = crate::config::load()?.audio.denoise;
+    if denoise.unwrap_or(denoise_config.files) {
+        crate::audio::denoise::denoise(&mut samples, denoise_config.strength);
+    }
 
-    // Time the transcription
     let start = Instant::now();
-    let response = send_request(&DaemonRequest::TranscribeAudio {
-        samples: audio_samples,
-    })?;
+    let response = send_request(&DaemonRequest::TranscribeAudio { samples })?;
     let transcription_time = start.elapsed();
 
     let transcription = match response {
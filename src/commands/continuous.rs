@@ -0,0 +1,164 @@
+//! Continuous listen: transcribe at every pause into a live transcript file
+//!
+//! Capture hands audio to a [`StreamSegmenter`] as it arrives; each completed segment
+//! is queued to a worker that transcribes it through the daemon and appends a
+//! timestamped line to the transcript. Memory is bounded by the segment in progress
+//! plus the queue, so a session can run for as long as the source does.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::audio::vad::{StreamSegment, StreamSegmenter, VadParams};
+use crate::audio::{self, TARGET_SAMPLE_RATE};
+use crate::config::Config;
+use crate::daemon::{self, DaemonRequest, DaemonResponse, NO_SPEECH_TEXT};
+
+/// Segments waiting for transcription before capture waits for the daemon
+const SEGMENT_QUEUE: usize = 8;
+
+/// Where transcribed lines go
+struct TranscriptSink {
+    file: File,
+    echo: bool,
+    started: DateTime<Local>,
+    lines: usize,
+}
+
+impl TranscriptSink {
+    fn append(&mut self, segment: &StreamSegment, text: &str) -> Result<()> {
+        let offset_ms = segment.start * 1000 / TARGET_SAMPLE_RATE as u64;
+        let at = self.started + chrono::Duration::milliseconds(offset_ms as i64);
+        let line = format_line(at, text);
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()?;
+        if self.echo {
+            print!("{}", line);
+            std::io::stdout().flush()?;
+        }
+        self.lines += 1;
+        Ok(())
+    }
+}
+
+/// Listen on `source` until the stop signal, appending a line per utterance to
+/// `path`. `keep_pending` is asked after capture stops whether the utterance in
+/// progress should still be transcribed. Returns the number of lines written.
+pub fn run(
+    cfg: &Config,
+    source: Option<&str>,
+    path: &Path,
+    echo: bool,
+    keep_pending: impl FnOnce() -> bool,
+) -> Result<usize> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open transcript {}", path.display()))?;
+    let mut sink = TranscriptSink {
+        file,
+        echo,
+        started: Local::now(),
+        lines: 0,
+    };
+
+    let (tx, rx) = mpsc::sync_channel::<StreamSegment>(SEGMENT_QUEUE);
+    let denoise = cfg.audio.denoise.clone();
+
+    std::thread::scope(|scope| {
+        let worker = scope.spawn(move || -> Result<usize> {
+            for mut segment in rx {
+                let mut samples = std::mem::take(&mut segment.samples);
+                if denoise.listen {
+                    audio::denoise::denoise(&mut samples, denoise.strength);
+                }
+                match transcribe(samples) {
+                    Ok(Some(text)) => sink.append(&segment, &text)?,
+                    Ok(None) => {},
+                    Err(e) => warn!("Skipping segment: {:#}", e),
+                }
+            }
+            Ok(sink.lines)
+        });
+
+        let mut segmenter = StreamSegmenter::new(VadParams::default());
+        let captured = audio::capture_continuous(source, |samples| {
+            for segment in segmenter.push(&samples) {
+                info!(
+                    "Segment ready ({:.1}s)",
+                    segment.samples.len() as f32 / TARGET_SAMPLE_RATE as f32
+                );
+                tx.send(segment).context("Transcription worker stopped")?;
+            }
+            Ok(())
+        });
+        if captured.is_ok() && keep_pending() {
+            if let Some(segment) = segmenter.flush() {
+                let _ = tx.send(segment);
+            }
+        }
+        drop(tx);
+
+        let lines = worker
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Transcription worker panicked")))?;
+        captured?;
+        Ok(lines)
+    })
+}
+
+/// Transcribe one segment; `None` when the daemon heard no speech in it
+fn transcribe(samples: Vec<f32>) -> Result<Option<String>> {
+    match daemon::send_request(&DaemonRequest::TranscribeAudio { samples })? {
+        DaemonResponse::Success { text } => {
+            let text = text.trim();
+            Ok((!text.is_empty() && text != NO_SPEECH_TEXT).then(|| text.to_string()))
+        },
+        DaemonResponse::Error { message } => anyhow::bail!("Transcription failed: {}", message),
+        _ => anyhow::bail!("Unexpected response from daemon"),
+    }
+}
+
+/// `[hh:mm:ss] text` in local time
+fn format_line(at: DateTime<Local>, text: &str) -> String {
+    format!("[{}] {}\n", at.format("%H:%M:%S"), text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_format_line() {
+        let at = Local.with_ymd_and_hms(2026, 3, 1, 9, 5, 7).unwrap();
+        assert_eq!(format_line(at, "Hello there."), "[09:05:07] Hello there.\n");
+    }
+
+    #[test]
+    fn test_append_offsets_from_session_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcript.txt");
+        let mut sink = TranscriptSink {
+            file: File::create(&path).unwrap(),
+            echo: false,
+            started: Local.with_ymd_and_hms(2026, 3, 1, 23, 59, 58).unwrap(),
+            lines: 0,
+        };
+        let segment = |secs: u64| StreamSegment {
+            start: secs * TARGET_SAMPLE_RATE as u64,
+            samples: Vec::new(),
+        };
+        sink.append(&segment(0), "First.").unwrap();
+        sink.append(&segment(5), "Second.").unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written, "[23:59:58] First.\n[00:00:03] Second.\n");
+        assert_eq!(sink.lines, 2);
+    }
+}
@@ -2,12 +2,16 @@
 
 use anyhow::Result;
 
-use crate::output::{self, OutputMode};
+use crate::config;
+use crate::output::injector::{self, Backend};
+use crate::output::{self, DisplayServer, OutputMode};
 
 /// Tests clipboard operations and text injection with a countdown for window focus.
-pub fn run(text: &str, clipboard: bool) -> Result<()> {
+/// `backend` types with one injection backend, `all` with each available one in turn.
+pub fn run(text: &str, clipboard: bool, backend: Option<Backend>, all: bool) -> Result<()> {
     println!("\n=== Enigo Test Suite ===\n");
 
+    let mut cfg = config::load()?.output;
     let mode = if clipboard {
         OutputMode::Clipboard
     } else {
@@ -17,8 +21,31 @@ pub fn run(text: &str, clipboard: bool) -> Result<()> {
     println!("Test text: {:?}\n", text);
 
     test_clipboard_operations();
-    countdown_to_paste();
-    execute_injection(text, mode)?;
+
+    if all {
+        let display = DisplayServer::detect(cfg.display_server.as_deref());
+        for (backend, available) in injector::availability(display, &cfg.paste) {
+            if let Err(e) = available {
+                println!("\n- Skipping {}: {:#}", backend, e);
+                continue;
+            }
+            println!("\n=== Backend: {} ===", backend);
+            cfg.injector = Some(backend);
+            countdown_to_paste();
+            if let Err(e) = execute_injection(text, mode, &cfg) {
+                println!("✗ {} failed: {:#}", backend, e);
+            }
+        }
+    } else {
+        if backend.is_some() {
+            cfg.injector = backend;
+        }
+        if mode == OutputMode::Type {
+            println!("Backend: {}", injector::select(&cfg)?.backend());
+        }
+        countdown_to_paste();
+        execute_injection(text, mode, &cfg)?;
+    }
 
     println!("\n=== Test Complete ===\n");
     Ok(())
@@ -48,10 +75,10 @@ fn countdown_to_paste() {
     sleep(Duration::from_secs(1));
 }
 
-fn execute_injection(text: &str, mode: OutputMode) -> Result<()> {
+fn execute_injection(text: &str, mode: OutputMode, cfg: &config::OutputConfig) -> Result<()> {
     println!("\nExecuting text injection...");
 
-    output::inject_text(text, mode)?;
+    output::inject_text(text, mode, cfg)?;
 
     println!("✓ inject_text completed successfully");
     let msg = if mode == OutputMode::Clipboard {
@@ -0,0 +1,165 @@
+//! Input level meter command (terminal and Waybar output)
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::audio::levels::AudioLevels;
+use crate::daemon;
+
+/// Silence longer than this while recording is reported as a muted/dead mic
+const MIC_SILENT_WARN_MS: u64 = 3000;
+
+/// Update interval for `--follow`
+const FOLLOW_INTERVAL_MS: u32 = 100;
+
+const BAR_GLYPHS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const METER_WIDTH: usize = 30;
+
+/// Print the daemon's input level once, or continuously with `follow`
+pub fn run(follow: bool, waybar: bool) -> Result<()> {
+    let print = |levels: AudioLevels| {
+        if waybar {
+            println!("{}", waybar_json(&levels));
+        } else {
+            println!("{}", meter_line(&levels));
+        }
+    };
+
+    if !follow {
+        print(daemon::daemon_get_levels()?);
+        return Ok(());
+    }
+
+    daemon::daemon_subscribe_levels(FOLLOW_INTERVAL_MS, |levels| {
+        print(levels);
+        true
+    })
+}
+
+/// Map a linear amplitude to 0.0..=1.0 on a -60..0 dBFS scale, which tracks
+/// perceived loudness better than the raw value
+fn meter_fraction(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        return 0.0;
+    }
+    let db = 20.0 * amplitude.log10();
+    ((db + 60.0) / 60.0).clamp(0.0, 1.0)
+}
+
+fn to_dbfs(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        f32::NEG_INFINITY
+    } else {
+        20.0 * amplitude.log10()
+    }
+}
+
+fn is_mic_silent(levels: &AudioLevels) -> bool {
+    levels.active && levels.silent_ms >= MIC_SILENT_WARN_MS
+}
+
+/// One-line terminal meter, e.g. `[#########-----] -24.1 dBFS (peak -18.3)`
+fn meter_line(levels: &AudioLevels) -> String {
+    if !levels.active {
+        return "inactive (no input stream open)".to_string();
+    }
+    let filled = (meter_fraction(levels.rms) * METER_WIDTH as f32).round() as usize;
+    let mut line = format!(
+        "[{}{}] {:6.1} dBFS (peak {:.1})",
+        "#".repeat(filled),
+        "-".repeat(METER_WIDTH - filled),
+        to_dbfs(levels.rms).max(-99.9),
+        to_dbfs(levels.peak).max(-99.9)
+    );
+    if is_mic_silent(levels) {
+        line.push_str(&format!(
+            "  mic silent for {:.1}s",
+            levels.silent_ms as f32 / 1000.0
+        ));
+    }
+    line
+}
+
+/// Waybar custom module output (one JSON object per line for streaming `exec`)
+fn waybar_json(levels: &AudioLevels) -> serde_json::Value {
+    if !levels.active {
+        return json!({
+            "text": "",
+            "class": "inactive",
+            "tooltip": "No input stream open",
+            "percentage": 0,
+        });
+    }
+
+    let fraction = meter_fraction(levels.rms);
+    let glyph =
+        BAR_GLYPHS[((fraction * BAR_GLYPHS.len() as f32) as usize).min(BAR_GLYPHS.len() - 1)];
+
+    if is_mic_silent(levels) {
+        return json!({
+            "text": "󰍭",
+            "class": "silent",
+            "tooltip": format!(
+                "Mic silent for {}s - muted or wrong input device?",
+                levels.silent_ms / 1000
+            ),
+            "percentage": 0,
+        });
+    }
+
+    json!({
+        "text": glyph.to_string(),
+        "class": "active",
+        "tooltip": format!(
+            "Input {:.1} dBFS (peak {:.1})",
+            to_dbfs(levels.rms),
+            to_dbfs(levels.peak)
+        ),
+        "percentage": (fraction * 100.0).round() as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(rms: f32, silent_ms: u64) -> AudioLevels {
+        AudioLevels {
+            active: true,
+            rms,
+            peak: rms,
+            silent_ms,
+        }
+    }
+
+    #[test]
+    fn test_meter_fraction() {
+        assert_eq!(meter_fraction(0.0), 0.0);
+        assert_eq!(meter_fraction(1.0), 1.0);
+        // Everything below -60 dBFS pins to the bottom
+        assert_eq!(meter_fraction(0.0001), 0.0);
+        assert!((meter_fraction(0.1) - 2.0 / 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_waybar_classes() {
+        let inactive = waybar_json(&AudioLevels::default());
+        assert_eq!(inactive["class"], "inactive");
+
+        let speaking = waybar_json(&levels(0.1, 0));
+        assert_eq!(speaking["class"], "active");
+        assert_eq!(speaking["percentage"], 67);
+
+        let silent = waybar_json(&levels(0.0, 5000));
+        assert_eq!(silent["class"], "silent");
+        assert!(silent["tooltip"].as_str().unwrap().contains("Mic silent"));
+    }
+
+    #[test]
+    fn test_meter_line() {
+        assert!(meter_line(&AudioLevels::default()).starts_with("inactive"));
+        let line = meter_line(&levels(1.0, 0));
+        assert!(line.starts_with(&format!("[{}]", "#".repeat(METER_WIDTH))));
+        assert!(meter_line(&levels(0.0, 4000)).contains("mic silent for 4.0s"));
+    }
+}
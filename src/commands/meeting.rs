@@ -0,0 +1,235 @@
+//! Meeting capture: microphone and sink monitor recorded side by side
+//!
+//! Both streams are captured at once and transcribed separately, so each line of the
+//! merged transcript can be attributed to "Me" (the microphone) or "Remote" (what the
+//! speakers played). Without headphones the mic also picks up the remote side; mic
+//! segments that repeat an overlapping remote segment are dropped as echo.
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::audio;
+use crate::config::Config;
+use crate::daemon;
+use crate::transcribe::Segment;
+
+/// Mic and remote segments this close in time can be the same speech
+const ECHO_WINDOW_SECS: f64 = 1.0;
+
+/// Share of a mic segment's words found in the remote segment to count as echo
+const ECHO_WORD_SHARE: f64 = 0.6;
+
+/// Which side of the call a segment came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Speaker {
+    Me,
+    Remote,
+}
+
+impl fmt::Display for Speaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Speaker::Me => "Me",
+            Speaker::Remote => "Remote",
+        })
+    }
+}
+
+/// A transcript segment attributed to a speaker
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabeledSegment {
+    pub speaker: Speaker,
+    pub segment: Segment,
+}
+
+/// Audio of both sides, 16kHz mono
+pub struct MeetingRecording {
+    pub me: Vec<f32>,
+    pub remote: Vec<f32>,
+}
+
+/// Record the microphone and the remote source until the listen stop signal
+pub fn record(
+    cfg: &Config,
+    mic: Option<&str>,
+    remote: &str,
+    max_duration: u32,
+) -> Result<MeetingRecording> {
+    let capture = |device: Option<&str>| {
+        audio::capture_toggle(
+            max_duration,
+            cfg.audio.sample_rate,
+            device,
+            cfg.audio.trailing_buffer(),
+        )
+    };
+
+    // Both captures stop on the same signal
+    let (me, remote) = std::thread::scope(|scope| {
+        let remote = scope.spawn(|| capture(Some(remote)));
+        let me = capture(mic);
+        let remote = remote
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Remote capture thread panicked")));
+        (me, remote)
+    });
+
+    Ok(MeetingRecording {
+        me: me.context("Microphone capture failed")?,
+        remote: remote.context("Remote capture failed")?,
+    })
+}
+
+/// Transcribe both sides through the daemon and merge them in time order
+pub fn transcribe(cfg: &Config, recording: MeetingRecording) -> Result<Vec<LabeledSegment>> {
+    let MeetingRecording { mut me, mut remote } = recording;
+    let denoise = &cfg.audio.denoise;
+    if denoise.microphone {
+        audio::denoise::denoise(&mut me, denoise.strength);
+    }
+    if denoise.listen {
+        audio::denoise::denoise(&mut remote, denoise.strength);
+    }
+
+    let transcribe_side = |samples: Vec<f32>| {
+        if samples.is_empty() {
+            Ok(Vec::new())
+        } else {
+            daemon::daemon_transcribe_segments(samples)
+        }
+    };
+    let me = transcribe_side(me).context("Failed to transcribe the microphone")?;
+    let remote = transcribe_side(remote).context("Failed to transcribe the remote audio")?;
+    Ok(merge(me, remote))
+}
+
+/// Label both sides, drop mic echo of the remote side and order by start time
+pub fn merge(me: Vec<Segment>, remote: Vec<Segment>) -> Vec<LabeledSegment> {
+    let me = me.into_iter().filter(|m| !is_echo(m, &remote));
+
+    let mut merged: Vec<LabeledSegment> = me
+        .map(|segment| LabeledSegment {
+            speaker: Speaker::Me,
+            segment,
+        })
+        .chain(remote.iter().cloned().map(|segment| LabeledSegment {
+            speaker: Speaker::Remote,
+            segment,
+        }))
+        .collect();
+    merged.sort_by(|a, b| a.segment.start_secs.total_cmp(&b.segment.start_secs));
+    merged
+}
+
+/// Whether a mic segment is mostly the remote side picked up by the microphone
+fn is_echo(mic: &Segment, remote: &[Segment]) -> bool {
+    let words = normalized_words(&mic.text);
+    if words.is_empty() {
+        return false;
+    }
+
+    remote
+        .iter()
+        .filter(|r| {
+            r.start_secs - ECHO_WINDOW_SECS < mic.end_secs
+                && mic.start_secs < r.end_secs + ECHO_WINDOW_SECS
+        })
+        .any(|r| {
+            let remote_words: HashSet<String> = normalized_words(&r.text).into_iter().collect();
+            let shared = words.iter().filter(|w| remote_words.contains(*w)).count();
+            shared as f64 / words.len() as f64 >= ECHO_WORD_SHARE
+        })
+}
+
+fn normalized_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|w| {
+            w.chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// One `[hh:mm:ss] Speaker: text` line per segment
+pub fn format_transcript(segments: &[LabeledSegment]) -> String {
+    segments
+        .iter()
+        .map(|s| {
+            let secs = s.segment.start_secs.max(0.0) as u64;
+            format!(
+                "[{:02}:{:02}:{:02}] {}: {}\n",
+                secs / 3600,
+                secs / 60 % 60,
+                secs % 60,
+                s.speaker,
+                s.segment.text
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_secs: f64, end_secs: f64, text: &str) -> Segment {
+        Segment {
+            start_secs,
+            end_secs,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_merge_orders_by_start_time() {
+        let me = vec![
+            segment(0.5, 2.0, "Morning all."),
+            segment(9.0, 11.0, "Sounds good."),
+        ];
+        let remote = vec![segment(3.0, 8.0, "Morning. Let's start with the deploy.")];
+
+        let merged = merge(me, remote);
+        let speakers: Vec<Speaker> = merged.iter().map(|s| s.speaker).collect();
+        assert_eq!(speakers, [Speaker::Me, Speaker::Remote, Speaker::Me]);
+    }
+
+    #[test]
+    fn test_mic_echo_of_remote_is_dropped() {
+        let me = vec![
+            // Speakers bleeding into the mic, slightly offset and punctuated differently
+            segment(3.2, 8.1, "morning let's start with the deploy"),
+            // Same words but long after: a real reply
+            segment(20.0, 21.0, "Let's start with the deploy."),
+        ];
+        let remote = vec![segment(3.0, 8.0, "Morning. Let's start with the deploy.")];
+
+        let merged = merge(me, remote);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].speaker, Speaker::Remote);
+        assert_eq!(merged[1].speaker, Speaker::Me);
+        assert_eq!(merged[1].segment.start_secs, 20.0);
+    }
+
+    #[test]
+    fn test_overlapping_different_speech_is_kept() {
+        let me = vec![segment(3.5, 5.0, "Sorry, go ahead.")];
+        let remote = vec![segment(3.0, 8.0, "So the release is on Thursday.")];
+        assert_eq!(merge(me, remote).len(), 2);
+    }
+
+    #[test]
+    fn test_format_transcript() {
+        let merged = merge(
+            vec![segment(3725.4, 3727.0, "Thanks, bye.")],
+            vec![segment(1.0, 2.0, "Hi!")],
+        );
+        assert_eq!(
+            format_transcript(&merged),
+            "[00:00:01] Remote: Hi!\n[01:02:05] Me: Thanks, bye.\n"
+        );
+    }
+}
@@ -1,5 +1,11 @@
 //! CLI command implementations
 
+pub mod continuous;
 pub mod enigo_test;
+pub mod levels;
+pub mod meeting;
+pub mod transcribe_batch;
 
 pub use enigo_test::run as enigo_test;
+pub use levels::run as levels;
+pub use transcribe_batch::run as transcribe_batch;
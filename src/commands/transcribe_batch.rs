@@ -0,0 +1,291 @@
+//! Batch transcription of files, directories and glob patterns via the daemon
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::warn;
+
+use crate::daemon::{self, DaemonRequest, DaemonResponse, NO_SPEECH_TEXT, TranscribeOptions};
+use crate::output::transcript::{self, OutputFormat};
+use crate::transcribe::Segment;
+
+/// File extensions picked up when expanding directories and glob patterns
+const AUDIO_EXTENSIONS: &[&str] = &[
+    "wav", "mp3", "flac", "ogg", "oga", "opus", "m4a", "mp4", "aac",
+];
+
+/// Batch run settings
+pub struct BatchOptions {
+    pub format: OutputFormat,
+    pub force: bool,
+    pub recursive: bool,
+    pub save_history: bool,
+}
+
+/// Transcribe every audio file matched by `inputs`, writing a sidecar per file.
+/// Per-file failures are reported and summarized; the run fails if any file failed.
+pub fn run(inputs: &[String], opts: &BatchOptions) -> Result<()> {
+    if !daemon::is_daemon_running() {
+        anyhow::bail!("Daemon is not running. Start it first with: mojovoice daemon &");
+    }
+
+    let files = collect_inputs(inputs, opts.recursive)?;
+    if files.is_empty() {
+        anyhow::bail!("No audio files matched: {}", inputs.join(" "));
+    }
+
+    let model_name = daemon::daemon_get_status()
+        .map(|s| s.model_name)
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    let total = files.len();
+    let mut transcribed = 0;
+    let mut skipped = 0;
+    let mut failures: Vec<(PathBuf, String)> = Vec::new();
+
+    for (i, file) in files.iter().enumerate() {
+        let sidecar = sidecar_path(file, opts.format);
+        print!("[{}/{}] {} ... ", i + 1, total, file.display());
+        let _ = std::io::stdout().flush();
+
+        if !opts.force && is_up_to_date(file, &sidecar) {
+            println!("skipped (up to date)");
+            skipped += 1;
+            continue;
+        }
+
+        let start = Instant::now();
+        match transcribe_one(file, &sidecar, &model_name, opts) {
+            Ok(()) => {
+                println!("done ({:.1}s)", start.elapsed().as_secs_f64());
+                transcribed += 1;
+            },
+            Err(e) => {
+                println!("ERROR: {:#}", e);
+                warn!("Failed to transcribe {}: {:#}", file.display(), e);
+                failures.push((file.clone(), format!("{:#}", e)));
+            },
+        }
+    }
+
+    println!();
+    println!("=== Summary ===");
+    println!("Transcribed: {}", transcribed);
+    println!("Skipped:     {}", skipped);
+    println!("Failed:      {}", failures.len());
+    for (file, error) in &failures {
+        println!("  {}: {}", file.display(), error);
+    }
+
+    if !failures.is_empty() {
+        anyhow::bail!("{} of {} file(s) failed", failures.len(), total);
+    }
+
+    Ok(())
+}
+
+/// Transcribe one file via the daemon and write its sidecar
+fn transcribe_one(file: &Path, sidecar: &Path, model: &str, opts: &BatchOptions) -> Result<()> {
+    let (text, segments) = request_transcript(file, opts.save_history, opts.format)?;
+    let contents = transcript::render(opts.format, &text, &segments, file, model)?;
+
+    std::fs::write(sidecar, contents)
+        .with_context(|| format!("Failed to write {}", sidecar.display()))
+}
+
+/// Ask the daemon to transcribe a file, requesting segment timing when `format` needs it.
+/// Files without speech yield empty text and no segments.
+pub fn request_transcript(
+    file: &Path,
+    save_history: bool,
+    format: OutputFormat,
+) -> Result<(String, Vec<Segment>)> {
+    let path = std::fs::canonicalize(file)?;
+    let response = daemon::send_request(&DaemonRequest::TranscribeFile {
+        path,
+        options: TranscribeOptions {
+            save_history,
+            timestamps: format.needs_segments(),
+            ..Default::default()
+        },
+    })?;
+
+    match response {
+        DaemonResponse::Success { text } if text == NO_SPEECH_TEXT => Ok((String::new(), vec![])),
+        DaemonResponse::Success { text } => Ok((text, vec![])),
+        DaemonResponse::Transcript { text, segments } => Ok((text, segments)),
+        DaemonResponse::Error { message } => anyhow::bail!("{}", message),
+        _ => anyhow::bail!("Unexpected response from daemon"),
+    }
+}
+
+/// Expand files, directories and glob patterns into a sorted, de-duplicated file list
+fn collect_inputs(inputs: &[String], recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for input in inputs {
+        let path = Path::new(input);
+
+        if path.is_dir() {
+            let mut found = Vec::new();
+            collect_dir(path, recursive, &mut found)?;
+            found.sort();
+            files.extend(found);
+        } else if path.exists() {
+            // Explicit files are taken as-is, whatever their extension
+            files.push(path.to_path_buf());
+        } else if input.contains(['*', '?', '[']) {
+            let mut matched: Vec<PathBuf> = glob::glob(input)
+                .with_context(|| format!("Invalid glob pattern: {}", input))?
+                .filter_map(|entry| entry.ok())
+                .filter(|p| p.is_file() && is_audio_file(p))
+                .collect();
+            if matched.is_empty() {
+                warn!("Pattern matched no files: {}", input);
+            }
+            matched.sort();
+            files.extend(matched);
+        } else {
+            anyhow::bail!("File not found: {}", input);
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|f| seen.insert(f.clone()));
+    Ok(files)
+}
+
+/// Collect audio files in a directory (optionally recursing into subdirectories)
+fn collect_dir(dir: &Path, recursive: bool, found: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            if recursive {
+                collect_dir(&path, recursive, found)?;
+            }
+        } else if is_audio_file(&path) {
+            found.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Sidecar path for an input: `memo.m4a` -> `memo.m4a.txt` (keeps `memo.mp3`/`memo.wav` apart)
+fn sidecar_path(input: &Path, format: OutputFormat) -> PathBuf {
+    let mut name = input.as_os_str().to_owned();
+    name.push(".");
+    name.push(format.extension());
+    PathBuf::from(name)
+}
+
+/// A sidecar is up to date if it is at least as new as its input
+fn is_up_to_date(input: &Path, sidecar: &Path) -> bool {
+    let modified = |p: &Path| p.metadata().and_then(|m| m.modified()).ok();
+    match (modified(input), modified(sidecar)) {
+        (Some(input_time), Some(sidecar_time)) => sidecar_time >= input_time,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn touch(path: &Path) {
+        fs::write(path, b"").unwrap();
+    }
+
+    #[test]
+    fn test_collect_inputs_directory_filters_audio() {
+        let dir = TempDir::new().unwrap();
+        touch(&dir.path().join("b.mp3"));
+        touch(&dir.path().join("a.WAV"));
+        touch(&dir.path().join("a.WAV.txt"));
+        fs::create_dir(dir.path().join("nested")).unwrap();
+        touch(&dir.path().join("nested/c.flac"));
+
+        let input = vec![dir.path().to_string_lossy().to_string()];
+        let files = collect_inputs(&input, false).unwrap();
+        let names: Vec<_> = files.iter().map(|f| f.file_name().unwrap()).collect();
+        assert_eq!(names, ["a.WAV", "b.mp3"]);
+
+        let files = collect_inputs(&input, true).unwrap();
+        assert_eq!(files.len(), 3);
+    }
+
+    #[test]
+    fn test_collect_inputs_glob_and_dedup() {
+        let dir = TempDir::new().unwrap();
+        touch(&dir.path().join("one.m4a"));
+        touch(&dir.path().join("two.m4a"));
+        touch(&dir.path().join("three.ogg"));
+
+        let explicit = dir.path().join("one.m4a").to_string_lossy().to_string();
+        let pattern = dir.path().join("*.m4a").to_string_lossy().to_string();
+        let files = collect_inputs(&[explicit, pattern], false).unwrap();
+        assert_eq!(files.len(), 2);
+    }
+
+    #[test]
+    fn test_collect_inputs_glob_skips_sidecars() {
+        // A re-run over `dir/*` must not pick up the transcripts written by the last run
+        let dir = TempDir::new().unwrap();
+        touch(&dir.path().join("memo.m4a"));
+        touch(&dir.path().join("memo.m4a.txt"));
+        touch(&dir.path().join("memo.m4a.json"));
+        touch(&dir.path().join("memo.m4a.srt"));
+
+        let pattern = dir.path().join("*").to_string_lossy().to_string();
+        let files = collect_inputs(&[pattern], false).unwrap();
+        let names: Vec<_> = files.iter().map(|f| f.file_name().unwrap()).collect();
+        assert_eq!(names, ["memo.m4a"]);
+    }
+
+    #[test]
+    fn test_collect_inputs_missing_file_errors() {
+        let result = collect_inputs(&["/nonexistent/memo.wav".to_string()], false);
+        assert!(result.unwrap_err().to_string().contains("File not found"));
+    }
+
+    #[test]
+    fn test_sidecar_path_appends_extension() {
+        let sidecar = sidecar_path(Path::new("/memos/standup.m4a"), OutputFormat::Json);
+        assert_eq!(sidecar, Path::new("/memos/standup.m4a.json"));
+    }
+
+    #[test]
+    fn test_is_up_to_date() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("memo.wav");
+        let sidecar = sidecar_path(&input, OutputFormat::Txt);
+        touch(&input);
+
+        assert!(!is_up_to_date(&input, &sidecar), "missing sidecar is stale");
+
+        touch(&sidecar);
+        assert!(is_up_to_date(&input, &sidecar));
+
+        // Re-recorded input is newer than its sidecar
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&input)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(!is_up_to_date(&input, &sidecar));
+    }
+}
@@ -2,6 +2,11 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::audio::preprocess::{Normalize, Preprocess};
+use crate::output::injector::{Backend, PasteConfig};
+use crate::output::profile::OutputProfile;
+use crate::postprocess::{LlmConfig, SpokenConfig, Step};
+
 const APP_NAME: &str = "mojovoice";
 
 /// MAINTENANCE: When adding new config fields, also update:
@@ -19,6 +24,12 @@ pub struct Config {
     pub ui: UiConfig,
     #[serde(default)]
     pub history: HistoryConfig,
+    #[serde(default)]
+    pub push_to_talk: PushToTalkConfig,
+    #[serde(default)]
+    pub hotkeys: HotkeysConfig,
+    #[serde(default)]
+    pub postprocess: PostprocessConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +61,155 @@ pub struct AudioConfig {
     /// Audio input device name (None = system default)
     #[serde(default)]
     pub device_name: Option<String>,
+    /// Keep the microphone open while the daemon runs and prepend this many ms of
+    /// audio to each recording, so the first syllable isn't clipped (0 = disabled)
+    #[serde(default)]
+    pub pre_roll_ms: u32,
+    /// Keep capturing this many ms after stop so trailing words aren't cut off
+    #[serde(default = "default_trailing_buffer_ms")]
+    pub trailing_buffer_ms: u32,
+    /// End the trailing buffer early once the last ~300ms of audio is silent
+    #[serde(default)]
+    pub smart_stop: bool,
+    /// Signal conditioning applied before transcription
+    #[serde(default)]
+    pub preprocess: PreprocessConfig,
+    /// Spectral noise suppression, enabled per source
+    #[serde(default)]
+    pub denoise: DenoiseConfig,
+}
+
+fn default_trailing_buffer_ms() -> u32 {
+    1000
+}
+
+/// Longest pre-roll kept in memory
+const MAX_PRE_ROLL_MS: u32 = 5000;
+
+/// Longest trailing buffer after stop
+const MAX_TRAILING_BUFFER_MS: u32 = 5000;
+
+impl AudioConfig {
+    /// Validate audio config values
+    pub fn validate(&mut self) {
+        if self.pre_roll_ms > MAX_PRE_ROLL_MS {
+            eprintln!(
+                "pre_roll_ms {} exceeds maximum ({}), capping",
+                self.pre_roll_ms, MAX_PRE_ROLL_MS
+            );
+            self.pre_roll_ms = MAX_PRE_ROLL_MS;
+        }
+        if self.trailing_buffer_ms > MAX_TRAILING_BUFFER_MS {
+            eprintln!(
+                "trailing_buffer_ms {} exceeds maximum ({}), capping",
+                self.trailing_buffer_ms, MAX_TRAILING_BUFFER_MS
+            );
+            self.trailing_buffer_ms = MAX_TRAILING_BUFFER_MS;
+        }
+    }
+
+    /// Trailing capture settings for `capture_toggle`
+    pub fn trailing_buffer(&self) -> crate::audio::TrailingBuffer {
+        crate::audio::TrailingBuffer {
+            max_ms: self.trailing_buffer_ms,
+            smart: self.smart_stop,
+        }
+    }
+}
+
+/// Preprocessing chain for recordings, `listen` and file transcription (`[audio.preprocess]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreprocessConfig {
+    /// Run the chain at all
+    #[serde(default)]
+    pub enabled: bool,
+    /// High-pass cutoff in Hz, removes DC offset and rumble (0 = off)
+    #[serde(default = "default_high_pass_hz")]
+    pub high_pass_hz: f32,
+    /// Attenuate frames quieter than this, e.g. -50.0 (None = off)
+    #[serde(default)]
+    pub noise_gate_dbfs: Option<f32>,
+    /// "off", "peak" or "loudness"
+    #[serde(default)]
+    pub normalize: Normalize,
+    /// Normalization target (None = -3 dBFS peak / -20 dBFS loudness)
+    #[serde(default)]
+    pub target_dbfs: Option<f32>,
+    /// Upper bound on normalization gain
+    #[serde(default = "default_max_gain_db")]
+    pub max_gain_db: f32,
+}
+
+fn default_high_pass_hz() -> f32 {
+    80.0
+}
+
+fn default_max_gain_db() -> f32 {
+    20.0
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            high_pass_hz: default_high_pass_hz(),
+            noise_gate_dbfs: None,
+            normalize: Normalize::default(),
+            target_dbfs: None,
+            max_gain_db: default_max_gain_db(),
+        }
+    }
+}
+
+impl PreprocessConfig {
+    /// Chain settings, or None when preprocessing is disabled
+    pub fn chain(&self) -> Option<Preprocess> {
+        if !self.enabled {
+            return None;
+        }
+        Some(Preprocess {
+            high_pass_hz: (self.high_pass_hz > 0.0).then_some(self.high_pass_hz),
+            noise_gate_dbfs: self.noise_gate_dbfs,
+            normalize: self.normalize,
+            target_dbfs: self
+                .target_dbfs
+                .unwrap_or_else(|| self.normalize.default_target_dbfs())
+                .min(0.0),
+            max_gain_db: self.max_gain_db.max(0.0),
+        })
+    }
+}
+
+/// Spectral noise suppression per audio source (`[audio.denoise]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DenoiseConfig {
+    /// Daemon recordings (toggle, push-to-talk, hotkeys)
+    #[serde(default)]
+    pub microphone: bool,
+    /// `mojovoice listen` captures
+    #[serde(default)]
+    pub listen: bool,
+    /// `transcribe-file`, `transcribe -` and benchmarks
+    #[serde(default)]
+    pub files: bool,
+    /// Over-subtraction scale: 1.0 default, up to ~2.0 for loud fans
+    #[serde(default = "default_denoise_strength")]
+    pub strength: f32,
+}
+
+fn default_denoise_strength() -> f32 {
+    1.0
+}
+
+impl Default for DenoiseConfig {
+    fn default() -> Self {
+        Self {
+            microphone: false,
+            listen: false,
+            files: false,
+            strength: default_denoise_strength(),
+        }
+    }
 }
 
 fn default_audio_clips_path() -> PathBuf {
@@ -64,8 +224,18 @@ pub struct OutputConfig {
     pub display_server: Option<String>,
     /// Add a space after injected text
     pub append_space: bool,
+    /// Typing backend: "enigo", "wtype", "ydotool", "xdotool", "paste", or null for
+    /// auto-detect
+    #[serde(default)]
+    pub injector: Option<Backend>,
+    /// Shortcut and clipboard restore for the paste backend and mode
+    #[serde(default)]
+    pub paste: PasteConfig,
     /// Command to refresh status bar UI (e.g., "pkill -RTMIN+8 waybar")
     pub refresh_command: Option<String>,
+    /// Per-application overrides, matched against the focused window in order
+    #[serde(default)]
+    pub profiles: Vec<OutputProfile>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -162,6 +332,93 @@ impl HistoryConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushToTalkConfig {
+    /// Key presses shorter than this are treated as accidental and discarded
+    #[serde(default = "default_min_hold_ms")]
+    pub min_hold_ms: u32,
+    /// Recordings with less detected speech than this are discarded without transcribing
+    #[serde(default = "default_min_speech_ms")]
+    pub min_speech_ms: u32,
+}
+
+fn default_min_hold_ms() -> u32 {
+    250
+}
+
+fn default_min_speech_ms() -> u32 {
+    300
+}
+
+impl Default for PushToTalkConfig {
+    fn default() -> Self {
+        Self {
+            min_hold_ms: default_min_hold_ms(),
+            min_speech_ms: default_min_speech_ms(),
+        }
+    }
+}
+
+/// Global hotkeys handled by the daemon (Linux evdev; user must be in the `input` group)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HotkeysConfig {
+    /// Read hotkeys from /dev/input in the daemon
+    #[serde(default)]
+    pub enabled: bool,
+    /// Toggle recording, e.g. "SUPER+V"
+    #[serde(default)]
+    pub toggle: Option<String>,
+    /// Hold to record, e.g. "RIGHTCTRL"
+    #[serde(default)]
+    pub push_to_talk: Option<String>,
+    /// Cancel a recording, e.g. "SUPER+ESC"
+    #[serde(default)]
+    pub cancel: Option<String>,
+    /// Only listen to input devices whose name contains this (None = all keyboards)
+    #[serde(default)]
+    pub device: Option<String>,
+    /// Copy transcriptions to the clipboard instead of typing them
+    #[serde(default)]
+    pub clipboard: bool,
+}
+
+/// Text transforms applied to every transcription before history and output
+/// (`[postprocess]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostprocessConfig {
+    /// Steps in the order they run (empty = text is used as transcribed)
+    #[serde(default = "default_postprocess_steps")]
+    pub steps: Vec<Step>,
+    /// Vocabulary for the `spoken_punctuation` step
+    #[serde(default)]
+    pub spoken: SpokenConfig,
+    /// Endpoint and prompts for the `llm` step
+    #[serde(default)]
+    pub llm: LlmConfig,
+}
+
+fn default_postprocess_steps() -> Vec<Step> {
+    vec![Step::Trim]
+}
+
+impl Default for PostprocessConfig {
+    fn default() -> Self {
+        Self {
+            steps: default_postprocess_steps(),
+            spoken: SpokenConfig::default(),
+            llm: LlmConfig::default(),
+        }
+    }
+}
+
+impl PostprocessConfig {
+    /// Compile the configured steps; `language` is the transcription language
+    /// (`model.language`)
+    pub fn pipeline(&self, language: &str) -> Result<crate::postprocess::Pipeline> {
+        crate::postprocess::Pipeline::new(&self.steps, &self.spoken, language)
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         let data_dir = directories::BaseDirs::new()
@@ -182,17 +439,28 @@ impl Default for Config {
                 save_audio_clips: false,
                 audio_clips_path: default_audio_clips_path(),
                 device_name: None,
+                pre_roll_ms: 0,
+                trailing_buffer_ms: default_trailing_buffer_ms(),
+                smart_stop: false,
+                preprocess: PreprocessConfig::default(),
+                denoise: DenoiseConfig::default(),
             },
             output: OutputConfig {
                 display_server: None,
                 append_space: true,
+                injector: None,
+                paste: PasteConfig::default(),
                 refresh_command: Some("pkill -RTMIN+8 waybar".to_string()),
+                profiles: Vec::new(),
             },
             ui: UiConfig {
                 scale_preset: default_scale_preset(),
                 custom_scale: default_custom_scale(),
             },
             history: HistoryConfig::default(),
+            push_to_talk: PushToTalkConfig::default(),
+            hotkeys: HotkeysConfig::default(),
+            postprocess: PostprocessConfig::default(),
         }
     }
 }
@@ -201,6 +469,7 @@ impl Default for Config {
 pub fn load() -> Result<Config> {
     let mut config: Config = confy::load(APP_NAME, "config")?;
     // Validate and sanitize config values
+    config.audio.validate();
     config.ui.validate();
     config.history.validate();
     Ok(config)
@@ -210,6 +479,7 @@ pub fn load() -> Result<Config> {
 pub fn save(config: &Config) -> Result<()> {
     let mut validated_config = config.clone();
     // Validate config before saving
+    validated_config.audio.validate();
     validated_config.ui.validate();
     validated_config.history.validate();
     confy::store(APP_NAME, "config", &validated_config)?;
@@ -233,5 +503,64 @@ mod tests {
         assert_eq!(config.model.language, "en");
         assert_eq!(config.ui.scale_preset, "medium");
         assert_eq!(config.ui.custom_scale, 1.0);
+        // Pre-roll keeps the mic open, so it must be opt-in
+        assert_eq!(config.audio.pre_roll_ms, 0);
+        assert_eq!(config.audio.trailing_buffer_ms, 1000);
+        assert!(!config.audio.smart_stop);
+        // Conditioning changes what the model hears, so it is opt-in too
+        assert!(!config.audio.preprocess.enabled);
+        assert!(!config.audio.denoise.microphone);
+        assert!(!config.audio.denoise.listen);
+        assert!(!config.audio.denoise.files);
+    }
+
+    #[test]
+    fn test_audio_buffers_capped() {
+        let mut audio = Config::default().audio;
+        audio.pre_roll_ms = 60_000;
+        audio.trailing_buffer_ms = 60_000;
+        audio.validate();
+        assert_eq!(audio.pre_roll_ms, MAX_PRE_ROLL_MS);
+        assert_eq!(audio.trailing_buffer_ms, MAX_TRAILING_BUFFER_MS);
+    }
+
+    #[test]
+    fn test_preprocess_config() {
+        let mut preprocess = PreprocessConfig::default();
+        assert_eq!(preprocess.chain(), None);
+
+        preprocess.enabled = true;
+        let chain = preprocess.chain().unwrap();
+        assert_eq!(chain.high_pass_hz, Some(80.0));
+        assert_eq!(chain.target_dbfs, -3.0);
+
+        let parsed: PreprocessConfig = toml::from_str(
+            "enabled = true\nhigh_pass_hz = 0\nnormalize = \"loudness\"\nnoise_gate_dbfs = -50.0",
+        )
+        .unwrap();
+        let chain = parsed.chain().unwrap();
+        assert_eq!(chain.high_pass_hz, None);
+        assert_eq!(chain.normalize, Normalize::Loudness);
+        assert_eq!(chain.target_dbfs, -20.0);
+        assert_eq!(chain.noise_gate_dbfs, Some(-50.0));
+    }
+
+    #[test]
+    fn test_postprocess_config() {
+        // Older configs without the section only get whitespace trimmed
+        let defaults = PostprocessConfig::default();
+        assert_eq!(defaults.steps, [Step::Trim]);
+        assert_eq!(defaults.pipeline("en").unwrap().apply("  hi  "), "hi");
+
+        let parsed: PostprocessConfig = toml::from_str(
+            r#"steps = [{ type = "spoken_punctuation" }, { type = "capitalize" }]"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.pipeline("en").unwrap().apply("hi comma you"), "Hi, you");
+
+        let broken: PostprocessConfig =
+            toml::from_str(r#"steps = [{ type = "replace", pattern = "[", replacement = "" }]"#)
+                .unwrap();
+        assert!(broken.pipeline("en").is_err());
     }
 }
@@ -4,19 +4,32 @@ use std::os::unix::net::UnixStream;
 use std::time::Duration;
 use tracing::info;
 
+use crate::audio::health::DeviceHealth;
+use crate::audio::levels::AudioLevels;
+use crate::transcribe::Segment;
 use super::protocol::{DaemonRequest, DaemonResponse};
 use super::server::{get_socket_path, is_daemon_running};
 
 const DAEMON_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Transcription requests may carry arbitrarily long audio (files, stdin, listen sessions)
+const TRANSCRIBE_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
 pub fn send_request(request: &DaemonRequest) -> Result<DaemonResponse> {
     let socket_path = get_socket_path()?;
 
     let mut stream =
         UnixStream::connect(&socket_path).context("Failed to connect to daemon. Is it running?")?;
 
+    let read_timeout = match request {
+        DaemonRequest::TranscribeAudio { .. }
+        | DaemonRequest::TranscribeSegments { .. }
+        | DaemonRequest::TranscribeFile { .. } => TRANSCRIBE_TIMEOUT,
+        _ => DAEMON_TIMEOUT,
+    };
+
     stream
-        .set_read_timeout(Some(DAEMON_TIMEOUT))
+        .set_read_timeout(Some(read_timeout))
         .context("Failed to set read timeout")?;
     stream
         .set_write_timeout(Some(DAEMON_TIMEOUT))
@@ -24,9 +37,10 @@ pub fn send_request(request: &DaemonRequest) -> Result<DaemonResponse> {
 
     let request_json = serde_json::to_string(request)?;
     match request {
-        DaemonRequest::TranscribeAudio { samples } => {
+        DaemonRequest::TranscribeAudio { samples }
+        | DaemonRequest::TranscribeSegments { samples } => {
             info!(
-                "Sending TranscribeAudio request ({} samples, {} bytes)",
+                "Sending transcription request ({} samples, {} bytes)",
                 samples.len(),
                 request_json.len()
             );
@@ -58,7 +72,7 @@ pub fn daemon_stop_recording() -> Result<()> {
     if !is_daemon_running() {
         anyhow::bail!("Daemon is not running");
     }
-    let response = send_request(&DaemonRequest::StopRecording)?;
+    let response = send_request(&DaemonRequest::StopRecording { window: None })?;
     expect_ok_response(response, "Stop")
 }
 
@@ -87,6 +101,7 @@ pub struct DaemonStatusInfo {
     pub gpu_enabled: bool,
     pub gpu_name: String,
     pub uptime_secs: u64,
+    pub device: DeviceHealth,
 }
 
 /// Get daemon status (model, GPU info)
@@ -101,13 +116,69 @@ pub fn daemon_get_status() -> Result<DaemonStatusInfo> {
             gpu_enabled,
             gpu_name,
             uptime_secs,
+            device,
         } => Ok(DaemonStatusInfo {
             model_name,
             gpu_enabled,
             gpu_name,
             uptime_secs,
+            device,
         }),
         DaemonResponse::Error { message } => anyhow::bail!("Status error: {}", message),
         _ => anyhow::bail!("Unexpected response from daemon"),
     }
 }
+
+/// Get the daemon's current input level
+pub fn daemon_get_levels() -> Result<AudioLevels> {
+    if !is_daemon_running() {
+        anyhow::bail!("Daemon is not running");
+    }
+    match send_request(&DaemonRequest::GetLevels)? {
+        DaemonResponse::Levels { levels } => Ok(levels),
+        DaemonResponse::Error { message } => anyhow::bail!("Levels error: {}", message),
+        _ => anyhow::bail!("Unexpected response from daemon"),
+    }
+}
+
+/// Transcribe 16kHz mono samples into timed segments
+pub fn daemon_transcribe_segments(samples: Vec<f32>) -> Result<Vec<Segment>> {
+    match send_request(&DaemonRequest::TranscribeSegments { samples })? {
+        DaemonResponse::Transcript { segments, .. } => Ok(segments),
+        DaemonResponse::Error { message } => anyhow::bail!("Transcription failed: {}", message),
+        _ => anyhow::bail!("Unexpected response from daemon"),
+    }
+}
+
+/// Subscribe to level updates, calling `on_levels` for each one until it returns false
+/// or the daemon goes away
+pub fn daemon_subscribe_levels(
+    interval_ms: u32,
+    mut on_levels: impl FnMut(AudioLevels) -> bool,
+) -> Result<()> {
+    let socket_path = get_socket_path()?;
+    let mut stream =
+        UnixStream::connect(&socket_path).context("Failed to connect to daemon. Is it running?")?;
+    stream
+        .set_write_timeout(Some(DAEMON_TIMEOUT))
+        .context("Failed to set write timeout")?;
+
+    let request_json = serde_json::to_string(&DaemonRequest::SubscribeLevels { interval_ms })?;
+    stream.write_all(request_json.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.flush()?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line.context("Lost connection to daemon")?;
+        match serde_json::from_str(line.trim()).context("Failed to parse daemon response")? {
+            DaemonResponse::Levels { levels } => {
+                if !on_levels(levels) {
+                    return Ok(());
+                }
+            },
+            DaemonResponse::Error { message } => anyhow::bail!("Levels error: {}", message),
+            other => anyhow::bail!("Unexpected response: {:?}", other),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,508 @@
+//! Global hotkeys read directly from keyboards via evdev
+//!
+//! Compositor keybinds spawn a `mojovoice` process per key press and don't exist on
+//! every desktop. With `[hotkeys] enabled = true` the daemon opens the keyboards under
+//! `/dev/input` (the user must be in the `input` group) and triggers toggle,
+//! push-to-talk and cancel itself. Actions go through the daemon's own socket, so they
+//! are serialized with CLI requests exactly like `mojovoice start`/`ptt`/`cancel`.
+//!
+//! Keyboards plugged in after the daemon starts are not picked up until it restarts.
+
+use anyhow::{Context, Result};
+use evdev::{Device, EventSummary, KeyCode};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, Sender};
+use tracing::{error, info, warn};
+
+use crate::config::HotkeysConfig;
+use crate::daemon::{self, DaemonRequest, DaemonResponse};
+use crate::output;
+
+/// What a hotkey does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    Toggle,
+    PushToTalkPress,
+    PushToTalkRelease,
+    Cancel,
+}
+
+/// Modifier groups; either the left or right key satisfies a modifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Modifier {
+    Ctrl,
+    Shift,
+    Alt,
+    Super,
+}
+
+impl Modifier {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "CTRL" | "CONTROL" => Some(Modifier::Ctrl),
+            "SHIFT" => Some(Modifier::Shift),
+            "ALT" => Some(Modifier::Alt),
+            "SUPER" | "META" | "WIN" | "LOGO" => Some(Modifier::Super),
+            _ => None,
+        }
+    }
+
+    fn of_key(key: KeyCode) -> Option<Self> {
+        match key {
+            KeyCode::KEY_LEFTCTRL | KeyCode::KEY_RIGHTCTRL => Some(Modifier::Ctrl),
+            KeyCode::KEY_LEFTSHIFT | KeyCode::KEY_RIGHTSHIFT => Some(Modifier::Shift),
+            KeyCode::KEY_LEFTALT | KeyCode::KEY_RIGHTALT => Some(Modifier::Alt),
+            KeyCode::KEY_LEFTMETA | KeyCode::KEY_RIGHTMETA => Some(Modifier::Super),
+            _ => None,
+        }
+    }
+}
+
+/// A key combination such as `SUPER+SHIFT+V` or a single key such as `RIGHTCTRL`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
+    modifiers: HashSet<Modifier>,
+    key: KeyCode,
+}
+
+impl FromStr for Chord {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<String> = s.split('+').map(|p| p.trim().to_uppercase()).collect();
+        let (key_name, modifier_names) = parts
+            .split_last()
+            .filter(|(key, _)| !key.is_empty())
+            .with_context(|| format!("Empty hotkey: '{}'", s))?;
+
+        let modifiers = modifier_names
+            .iter()
+            .map(|name| {
+                Modifier::parse(name).with_context(|| {
+                    format!(
+                        "Unknown modifier '{}' in '{}' (use CTRL, SHIFT, ALT, SUPER)",
+                        name, s
+                    )
+                })
+            })
+            .collect::<Result<HashSet<_>>>()?;
+
+        Ok(Self {
+            modifiers,
+            key: parse_key(key_name)
+                .with_context(|| format!("Unknown key '{}' in hotkey '{}'", key_name, s))?,
+        })
+    }
+}
+
+/// Key names are evdev names without the `KEY_` prefix (`V`, `F12`, `RIGHTCTRL`)
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let name = match name {
+        "ESCAPE" => "ESC",
+        "RETURN" => "ENTER",
+        "CAPS" => "CAPSLOCK",
+        other => other,
+    };
+    KeyCode::from_str(&format!("KEY_{}", name)).ok()
+}
+
+/// Configured hotkeys
+#[derive(Debug, Clone, Default)]
+pub struct Bindings {
+    toggle: Option<Chord>,
+    push_to_talk: Option<Chord>,
+    cancel: Option<Chord>,
+}
+
+impl Bindings {
+    pub fn from_config(config: &HotkeysConfig) -> Result<Self> {
+        let parse = |chord: &Option<String>| chord.as_deref().map(Chord::from_str).transpose();
+        let bindings = Self {
+            toggle: parse(&config.toggle)?,
+            push_to_talk: parse(&config.push_to_talk)?,
+            cancel: parse(&config.cancel)?,
+        };
+        if bindings.keys().is_empty() {
+            anyhow::bail!("No hotkeys configured (set toggle, push_to_talk or cancel)");
+        }
+        Ok(bindings)
+    }
+
+    /// Main (non-modifier) keys of every binding
+    fn keys(&self) -> Vec<KeyCode> {
+        [&self.toggle, &self.push_to_talk, &self.cancel]
+            .into_iter()
+            .flatten()
+            .map(|chord| chord.key)
+            .collect()
+    }
+}
+
+/// Turns raw key events into hotkey actions
+#[derive(Debug, Default)]
+pub struct Matcher {
+    bindings: Bindings,
+    held_modifiers: Vec<KeyCode>,
+    push_to_talk_held: bool,
+}
+
+impl Matcher {
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            ..Self::default()
+        }
+    }
+
+    /// Feed one key event (`value`: 1 = press, 0 = release, 2 = autorepeat)
+    pub fn handle(&mut self, key: KeyCode, value: i32) -> Option<HotkeyAction> {
+        if Modifier::of_key(key).is_some() {
+            match value {
+                1 if !self.held_modifiers.contains(&key) => self.held_modifiers.push(key),
+                0 => self.held_modifiers.retain(|k| *k != key),
+                _ => {},
+            }
+        }
+
+        match value {
+            1 => {
+                let modifiers: HashSet<Modifier> = self
+                    .held_modifiers
+                    .iter()
+                    .filter(|k| **k != key)
+                    .filter_map(|k| Modifier::of_key(*k))
+                    .collect();
+                let matches = |chord: &Option<Chord>| {
+                    chord
+                        .as_ref()
+                        .is_some_and(|c| c.key == key && c.modifiers == modifiers)
+                };
+
+                if matches(&self.bindings.push_to_talk) {
+                    self.push_to_talk_held = true;
+                    Some(HotkeyAction::PushToTalkPress)
+                } else if matches(&self.bindings.toggle) {
+                    Some(HotkeyAction::Toggle)
+                } else if matches(&self.bindings.cancel) {
+                    Some(HotkeyAction::Cancel)
+                } else {
+                    None
+                }
+            },
+            // Releasing the main key ends push-to-talk even if modifiers went first
+            0 if self.push_to_talk_held
+                && self
+                    .bindings
+                    .push_to_talk
+                    .as_ref()
+                    .is_some_and(|c| c.key == key) =>
+            {
+                self.push_to_talk_held = false;
+                Some(HotkeyAction::PushToTalkRelease)
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Keyboards that can produce every configured key (optionally filtered by name)
+fn matching_devices(
+    config: &HotkeysConfig,
+    bindings: &Bindings,
+) -> Result<Vec<(std::path::PathBuf, Device)>> {
+    let keys = bindings.keys();
+    let devices: Vec<_> = evdev::enumerate()
+        .filter(|(_, device)| {
+            let name = device.name().unwrap_or_default();
+            let name_matches = config
+                .device
+                .as_deref()
+                .is_none_or(|want| name.contains(want));
+            let has_keys = device
+                .supported_keys()
+                .is_some_and(|supported| keys.iter().all(|k| supported.contains(*k)));
+            name_matches && has_keys
+        })
+        .collect();
+
+    if devices.is_empty() {
+        anyhow::bail!(
+            "No readable keyboard found in /dev/input. \
+            Add your user to the 'input' group (sudo usermod -aG input $USER) and log in again."
+        );
+    }
+    Ok(devices)
+}
+
+/// Validate the hotkey config and return the names of the keyboards it would use
+pub fn check(config: &HotkeysConfig) -> Result<Vec<String>> {
+    let bindings = Bindings::from_config(config)?;
+    Ok(matching_devices(config, &bindings)?
+        .iter()
+        .map(|(path, device)| {
+            format!(
+                "{} ({})",
+                device.name().unwrap_or("unnamed"),
+                path.display()
+            )
+        })
+        .collect())
+}
+
+/// Open matching keyboards and start reading them. Returns the action stream.
+pub fn listen(config: &HotkeysConfig) -> Result<Receiver<HotkeyAction>> {
+    let bindings = Bindings::from_config(config)?;
+    let devices = matching_devices(config, &bindings)?;
+
+    let (key_tx, key_rx) = mpsc::channel::<(KeyCode, i32)>();
+    for (path, device) in devices {
+        info!(
+            "Hotkeys: listening on {} ({})",
+            path.display(),
+            device.name().unwrap_or("unnamed")
+        );
+        let key_tx = key_tx.clone();
+        std::thread::spawn(move || read_device(device, path, key_tx));
+    }
+    drop(key_tx);
+
+    let (action_tx, action_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut matcher = Matcher::new(bindings);
+        for (key, value) in key_rx {
+            if let Some(action) = matcher.handle(key, value) {
+                if action_tx.send(action).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(action_rx)
+}
+
+fn read_device(mut device: Device, path: std::path::PathBuf, tx: Sender<(KeyCode, i32)>) {
+    loop {
+        let events = match device.fetch_events() {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("Hotkeys: stopped reading {}: {}", path.display(), e);
+                return;
+            },
+        };
+        for event in events {
+            if let EventSummary::Key(_, key, value) = event.destructure() {
+                if tx.send((key, value)).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Start the hotkey listener and perform actions via the daemon socket
+pub fn start(config: &HotkeysConfig) -> Result<()> {
+    let actions = listen(config)?;
+    let clipboard = config.clipboard;
+
+    std::thread::spawn(move || {
+        for action in actions {
+            info!("Hotkey: {:?}", action);
+            if let Err(e) = perform(action, clipboard) {
+                error!("Hotkey {:?} failed: {:#}", action, e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Run one action the same way the corresponding CLI command does
+fn perform(action: HotkeyAction, clipboard: bool) -> Result<()> {
+    let config = crate::config::load()?;
+    let timeout_secs = config.audio.timeout_secs;
+    let window = match action {
+        HotkeyAction::Toggle | HotkeyAction::PushToTalkRelease => output::window::focused_window(),
+        _ => None,
+    };
+
+    let request = match action {
+        HotkeyAction::Toggle if crate::state::is_recording()?.is_some() => {
+            DaemonRequest::StopRecording {
+                window: window.clone(),
+            }
+        },
+        HotkeyAction::Toggle => DaemonRequest::StartRecording {
+            max_duration: timeout_secs,
+            code: None,
+            llm: None,
+        },
+        HotkeyAction::PushToTalkPress => DaemonRequest::PushToTalkPress {
+            max_duration: timeout_secs,
+        },
+        HotkeyAction::PushToTalkRelease => DaemonRequest::PushToTalkRelease {
+            window: window.clone(),
+        },
+        HotkeyAction::Cancel => DaemonRequest::CancelRecording,
+    };
+
+    match daemon::send_request(&request)? {
+        DaemonResponse::Success { text } => {
+            info!("Transcribed: {}", text);
+            output::inject_dictation(&text, clipboard, window.as_ref(), &config.output)?;
+        },
+        DaemonResponse::Error { message } if message == "Not recording" => {},
+        DaemonResponse::Error { message } => anyhow::bail!("{}", message),
+        _ => {},
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(toggle: &str, push_to_talk: &str, cancel: &str) -> Matcher {
+        Matcher::new(
+            Bindings::from_config(&HotkeysConfig {
+                toggle: Some(toggle.to_string()),
+                push_to_talk: Some(push_to_talk.to_string()),
+                cancel: Some(cancel.to_string()),
+                ..HotkeysConfig::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_parse_chord() {
+        let chord: Chord = "super + shift+V".parse().unwrap();
+        assert_eq!(chord.key, KeyCode::KEY_V);
+        assert_eq!(
+            chord.modifiers,
+            HashSet::from([Modifier::Super, Modifier::Shift])
+        );
+
+        assert_eq!("escape".parse::<Chord>().unwrap().key, KeyCode::KEY_ESC);
+        assert_eq!("F12".parse::<Chord>().unwrap().key, KeyCode::KEY_F12);
+    }
+
+    #[test]
+    fn test_parse_chord_errors() {
+        assert!("HYPER+V".parse::<Chord>().is_err());
+        assert!("SUPER+NOPE".parse::<Chord>().is_err());
+        assert!("SUPER+".parse::<Chord>().is_err());
+        assert!(Bindings::from_config(&HotkeysConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_chord_requires_exact_modifiers() {
+        let mut m = matcher("SUPER+V", "RIGHTCTRL", "SUPER+ESC");
+
+        // V alone does nothing
+        assert_eq!(m.handle(KeyCode::KEY_V, 1), None);
+        assert_eq!(m.handle(KeyCode::KEY_V, 0), None);
+
+        // Either Super key works
+        assert_eq!(m.handle(KeyCode::KEY_RIGHTMETA, 1), None);
+        assert_eq!(m.handle(KeyCode::KEY_V, 1), Some(HotkeyAction::Toggle));
+        assert_eq!(m.handle(KeyCode::KEY_V, 2), None, "autorepeat is ignored");
+        assert_eq!(m.handle(KeyCode::KEY_V, 0), None);
+
+        // Extra modifiers don't match
+        assert_eq!(m.handle(KeyCode::KEY_LEFTSHIFT, 1), None);
+        assert_eq!(m.handle(KeyCode::KEY_V, 1), None);
+        assert_eq!(m.handle(KeyCode::KEY_LEFTSHIFT, 0), None);
+
+        assert_eq!(m.handle(KeyCode::KEY_ESC, 1), Some(HotkeyAction::Cancel));
+    }
+
+    #[test]
+    fn test_push_to_talk_press_and_release() {
+        let mut m = matcher("SUPER+V", "SUPER+SPACE", "SUPER+ESC");
+
+        assert_eq!(m.handle(KeyCode::KEY_LEFTMETA, 1), None);
+        assert_eq!(
+            m.handle(KeyCode::KEY_SPACE, 1),
+            Some(HotkeyAction::PushToTalkPress)
+        );
+        assert_eq!(m.handle(KeyCode::KEY_SPACE, 2), None);
+        // Letting go of Super first still ends on the main key's release
+        assert_eq!(m.handle(KeyCode::KEY_LEFTMETA, 0), None);
+        assert_eq!(
+            m.handle(KeyCode::KEY_SPACE, 0),
+            Some(HotkeyAction::PushToTalkRelease)
+        );
+        assert_eq!(m.handle(KeyCode::KEY_SPACE, 0), None);
+    }
+
+    #[test]
+    fn test_modifier_key_as_push_to_talk() {
+        let mut m = matcher("SUPER+V", "RIGHTCTRL", "SUPER+ESC");
+        assert_eq!(
+            m.handle(KeyCode::KEY_RIGHTCTRL, 1),
+            Some(HotkeyAction::PushToTalkPress)
+        );
+        assert_eq!(
+            m.handle(KeyCode::KEY_RIGHTCTRL, 0),
+            Some(HotkeyAction::PushToTalkRelease)
+        );
+    }
+
+    /// End-to-end through the kernel: a uinput virtual keyboard feeds `listen`
+    #[test]
+    #[ignore = "needs write access to /dev/uinput and read access to /dev/input"]
+    fn test_listen_with_uinput_keyboard() {
+        use evdev::uinput::VirtualDevice;
+        use evdev::{AttributeSet, KeyEvent};
+        use std::time::Duration;
+
+        let mut keys = AttributeSet::<KeyCode>::new();
+        for key in [KeyCode::KEY_LEFTMETA, KeyCode::KEY_V, KeyCode::KEY_F9] {
+            keys.insert(key);
+        }
+        let mut keyboard = VirtualDevice::builder()
+            .unwrap()
+            .name("mojovoice-hotkey-test")
+            .with_keys(&keys)
+            .unwrap()
+            .build()
+            .unwrap();
+        // Give udev a moment to create the device node
+        std::thread::sleep(Duration::from_millis(500));
+
+        let actions = listen(&HotkeysConfig {
+            enabled: true,
+            toggle: Some("SUPER+V".to_string()),
+            push_to_talk: Some("F9".to_string()),
+            device: Some("mojovoice-hotkey-test".to_string()),
+            ..HotkeysConfig::default()
+        })
+        .unwrap();
+
+        let key = |code, value| *KeyEvent::new(code, value);
+        keyboard
+            .emit(&[key(KeyCode::KEY_LEFTMETA, 1), key(KeyCode::KEY_V, 1)])
+            .unwrap();
+        keyboard
+            .emit(&[key(KeyCode::KEY_V, 0), key(KeyCode::KEY_LEFTMETA, 0)])
+            .unwrap();
+        keyboard.emit(&[key(KeyCode::KEY_F9, 1)]).unwrap();
+        keyboard.emit(&[key(KeyCode::KEY_F9, 0)]).unwrap();
+
+        let timeout = Duration::from_secs(2);
+        let received: Vec<HotkeyAction> = (0..3)
+            .map(|_| actions.recv_timeout(timeout).unwrap())
+            .collect();
+        assert_eq!(
+            received,
+            [
+                HotkeyAction::Toggle,
+                HotkeyAction::PushToTalkPress,
+                HotkeyAction::PushToTalkRelease
+            ]
+        );
+    }
+}
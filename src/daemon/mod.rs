@@ -1,4 +1,6 @@
 pub mod client;
+#[cfg(target_os = "linux")]
+pub mod hotkeys;
 pub mod protocol;
 pub mod server;
 
@@ -1,26 +1,94 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::audio::health::DeviceHealth;
+use crate::audio::levels::AudioLevels;
+use crate::output::window::FocusedWindow;
+use crate::postprocess::LlmMode;
+use crate::postprocess::identifier::IdentifierCase;
+use crate::transcribe::Segment;
+
+/// Text returned for file/sample transcriptions that contain no speech
+pub const NO_SPEECH_TEXT: &str = "(no speech detected)";
 
 /// Request from client to daemon
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum DaemonRequest {
     #[serde(rename = "start_recording")]
-    StartRecording { max_duration: u32 },
+    StartRecording {
+        max_duration: u32,
+        /// Format the whole transcription as a code identifier
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        code: Option<IdentifierCase>,
+        /// Rewrite the transcription with the `llm` step in this mode
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        llm: Option<LlmMode>,
+    },
     #[serde(rename = "stop_recording")]
-    StopRecording,
+    StopRecording {
+        /// Where the text will go, for output profiles (the daemon looks it up if unset)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        window: Option<FocusedWindow>,
+    },
     #[serde(rename = "cancel_recording")]
     CancelRecording,
+    /// Push-to-talk key-down: start recording (ignores key repeat and bounces)
+    #[serde(rename = "push_to_talk_press")]
+    PushToTalkPress { max_duration: u32 },
+    /// Push-to-talk key-up: stop and transcribe, or discard an accidental tap
+    #[serde(rename = "push_to_talk_release")]
+    PushToTalkRelease {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        window: Option<FocusedWindow>,
+    },
     #[serde(rename = "transcribe_audio")]
     TranscribeAudio {
         /// Audio samples (16kHz mono f32)
         samples: Vec<f32>,
     },
+    /// Like `transcribe_audio`, but split at pauses and answered with a `transcript`
+    #[serde(rename = "transcribe_segments")]
+    TranscribeSegments {
+        /// Audio samples (16kHz mono f32)
+        samples: Vec<f32>,
+    },
+    #[serde(rename = "transcribe_file")]
+    TranscribeFile {
+        /// Absolute path to an audio file readable by the daemon
+        path: PathBuf,
+        #[serde(default)]
+        options: TranscribeOptions,
+    },
     #[serde(rename = "shutdown")]
     Shutdown,
     #[serde(rename = "ping")]
     Ping,
     #[serde(rename = "get_status")]
     GetStatus,
+    /// Current input level (inactive when no stream is open)
+    #[serde(rename = "get_levels")]
+    GetLevels,
+    /// Keep the connection open and receive a `levels` line every `interval_ms`
+    #[serde(rename = "subscribe_levels")]
+    SubscribeLevels { interval_ms: u32 },
+}
+
+/// Options for daemon-side file transcription
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranscribeOptions {
+    /// Record the transcription in history (like a dictation)
+    #[serde(default)]
+    pub save_history: bool,
+    /// Return timed segments (`Transcript` response) instead of plain text
+    #[serde(default)]
+    pub timestamps: bool,
+    /// Override `audio.denoise.files` for this request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub denoise: Option<bool>,
+    /// Run the `[postprocess]` pipeline over the text, except `llm` steps
+    #[serde(default)]
+    pub postprocess: bool,
 }
 
 /// Response from daemon to client
@@ -33,6 +101,8 @@ pub enum DaemonResponse {
     Recording,
     #[serde(rename = "success")]
     Success { text: String },
+    #[serde(rename = "transcript")]
+    Transcript { text: String, segments: Vec<Segment> },
     #[serde(rename = "error")]
     Error { message: String },
     #[serde(rename = "status")]
@@ -41,5 +111,10 @@ pub enum DaemonResponse {
         gpu_enabled: bool,
         gpu_name: String,
         uptime_secs: u64,
+        /// Input device health (absent from older daemons)
+        #[serde(default)]
+        device: DeviceHealth,
     },
+    #[serde(rename = "levels")]
+    Levels { levels: AudioLevels },
 }
@@ -6,11 +6,18 @@ use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 
-use crate::audio::{capture_toggle, list_input_devices};
-use crate::daemon::protocol::{DaemonRequest, DaemonResponse};
+use crate::audio::diagnostics::{self, CaptureStats};
+use crate::audio::vad::{self, VadParams};
+use crate::audio::{PreRoll, TARGET_SAMPLE_RATE, capture_toggle, list_input_devices};
+use crate::daemon::protocol::{DaemonRequest, DaemonResponse, NO_SPEECH_TEXT, TranscribeOptions};
+use crate::config::AudioConfig;
 use crate::history::{self, HistoryEntry, enforce_max_entries};
+use crate::output::{self, window::FocusedWindow, window::focused_window};
+use crate::postprocess::identifier::IdentifierCase;
+use crate::postprocess::{self, LlmMode, Pipeline};
 use crate::state;
 // Transcriber trait is now used via Box<dyn ...>
 
@@ -108,12 +115,22 @@ pub fn is_daemon_running() -> bool {
 struct RecordingState {
     handle: Option<JoinHandle<Result<Vec<f32>>>>,
     audio: Option<Vec<f32>>,
+    /// When the current recording started
+    started_at: Option<Instant>,
+    /// Last push-to-talk release that found no recording (press/release race)
+    orphan_release_at: Option<Instant>,
+    /// Identifier case requested for the current recording (`start --code`)
+    code: Option<IdentifierCase>,
+    /// LLM cleanup mode requested for the current recording (`start --llm`)
+    llm: Option<LlmMode>,
 }
 
 /// Daemon server state
 struct DaemonServer {
     transcriber: Arc<Mutex<Box<dyn crate::transcribe::Transcriber>>>,
     recording_state: Arc<Mutex<RecordingState>>,
+    /// Warm input stream when `audio.pre_roll_ms` is set
+    pre_roll: Option<Arc<PreRoll>>,
     shutdown: Arc<AtomicBool>,
     model_name: String,
     gpu_enabled: bool,
@@ -164,12 +181,34 @@ impl DaemonServer {
             .unwrap_or("unknown")
             .to_string();
 
+        // Pre-roll keeps the mic open for the daemon's lifetime (opt-in)
+        let pre_roll = if config.audio.pre_roll_ms > 0 {
+            let device_name = validate_audio_device(config.audio.device_name.clone());
+            match PreRoll::start(device_name.as_deref(), config.audio.pre_roll_ms) {
+                Ok(pre_roll) => Some(Arc::new(pre_roll)),
+                Err(e) => {
+                    warn!(
+                        "Failed to start pre-roll capture: {:#}. Opening the device per recording.",
+                        e
+                    );
+                    None
+                },
+            }
+        } else {
+            None
+        };
+
         Ok(Self {
             transcriber: Arc::new(Mutex::new(Box::new(transcriber))),
             recording_state: Arc::new(Mutex::new(RecordingState {
                 handle: None,
                 audio: None,
+                started_at: None,
+                orphan_release_at: None,
+                code: None,
+                llm: None,
             })),
+            pre_roll,
             shutdown: Arc::new(AtomicBool::new(false)),
             model_name,
             gpu_enabled,
@@ -249,6 +288,12 @@ impl DaemonServer {
                     samples.len()
                 );
             },
+            DaemonRequest::TranscribeSegments { samples } => {
+                info!(
+                    "Received TranscribeSegments request ({} samples)",
+                    samples.len()
+                );
+            },
             _ => {
                 info!("Received from client: {}", line.trim());
             },
@@ -258,12 +303,31 @@ impl DaemonServer {
             DaemonRequest::Ping => DaemonResponse::Ok {
                 message: "pong".to_string(),
             },
-            DaemonRequest::StartRecording { max_duration } => {
-                self.handle_start_recording(max_duration)?
-            },
-            DaemonRequest::StopRecording => self.handle_stop_recording()?,
+            DaemonRequest::StartRecording {
+                max_duration,
+                code,
+                llm,
+            } => self.handle_start_recording(max_duration, code, llm)?,
+            DaemonRequest::StopRecording { window } => self.handle_stop_recording(window)?,
             DaemonRequest::CancelRecording => self.handle_cancel_recording()?,
-            DaemonRequest::TranscribeAudio { samples } => self.handle_transcribe_audio(samples)?,
+            DaemonRequest::PushToTalkPress { max_duration } => {
+                self.handle_push_to_talk_press(max_duration)?
+            },
+            DaemonRequest::PushToTalkRelease { window } => {
+                self.handle_push_to_talk_release(window)?
+            },
+            DaemonRequest::TranscribeAudio { mut samples } => {
+                // Clients denoise these themselves, they know where the audio came from
+                Self::condition_samples(&mut samples, &Self::audio_config(), false);
+                self.handle_transcribe_audio(samples)?
+            },
+            DaemonRequest::TranscribeSegments { mut samples } => {
+                Self::condition_samples(&mut samples, &Self::audio_config(), false);
+                self.handle_transcribe_segments(&samples)?
+            },
+            DaemonRequest::TranscribeFile { path, options } => {
+                self.handle_transcribe_file(&path, options)?
+            },
             DaemonRequest::Shutdown => {
                 info!("Shutdown requested");
                 self.shutdown.store(true, Ordering::SeqCst);
@@ -276,6 +340,15 @@ impl DaemonServer {
                 gpu_enabled: self.gpu_enabled,
                 gpu_name: self.gpu_name.clone(),
                 uptime_secs: self.start_time.elapsed().as_secs(),
+                device: crate::audio::health::current(),
+            },
+            DaemonRequest::GetLevels => DaemonResponse::Levels {
+                levels: crate::audio::levels::current_levels(),
+            },
+            DaemonRequest::SubscribeLevels { interval_ms } => {
+                // Subscriptions outlive this call; serve them from their own thread
+                Self::spawn_levels_stream(stream, interval_ms);
+                return Ok(());
             },
         };
 
@@ -287,7 +360,38 @@ impl DaemonServer {
         Ok(())
     }
 
-    fn handle_start_recording(&self, max_duration: u32) -> Result<DaemonResponse> {
+    /// Stream level updates to a subscriber until it disconnects
+    fn spawn_levels_stream(mut stream: UnixStream, interval_ms: u32) {
+        // Faster than ~60 updates/s only burns CPU
+        let interval = Duration::from_millis(interval_ms.max(16) as u64);
+        thread::spawn(move || {
+            info!("Level subscriber connected ({}ms interval)", interval.as_millis());
+            loop {
+                let response = DaemonResponse::Levels {
+                    levels: crate::audio::levels::current_levels(),
+                };
+                let Ok(json) = serde_json::to_string(&response) else {
+                    return;
+                };
+                let sent = stream
+                    .write_all(json.as_bytes())
+                    .and_then(|_| stream.write_all(b"\n"))
+                    .and_then(|_| stream.flush());
+                if sent.is_err() {
+                    info!("Level subscriber disconnected");
+                    return;
+                }
+                thread::sleep(interval);
+            }
+        });
+    }
+
+    fn handle_start_recording(
+        &self,
+        max_duration: u32,
+        code: Option<IdentifierCase>,
+        llm: Option<LlmMode>,
+    ) -> Result<DaemonResponse> {
         // Atomic check-and-set: mutex ensures no race between check and state update
         let mut state = self
             .recording_state
@@ -306,6 +410,7 @@ impl DaemonServer {
         // Load config and validate device exists
         let config = crate::config::load()?;
         let device_name = validate_audio_device(config.audio.device_name.clone());
+        let trailing = config.audio.trailing_buffer();
 
         // Create PID file for UI state (Waybar uses this)
         state::toggle::start_recording()?;
@@ -313,12 +418,22 @@ impl DaemonServer {
         // Set up signal handler for this recording session
         state::toggle::setup_signal_handler()?;
 
-        // Spawn recording thread
-        let handle =
-            thread::spawn(move || capture_toggle(max_duration, 16000, device_name.as_deref()));
+        // Spawn recording thread (on the warm pre-roll stream if enabled)
+        let handle = match &self.pre_roll {
+            Some(pre_roll) => {
+                let pre_roll = pre_roll.clone();
+                thread::spawn(move || pre_roll.capture_toggle(max_duration, trailing))
+            },
+            None => thread::spawn(move || {
+                capture_toggle(max_duration, 16000, device_name.as_deref(), trailing)
+            }),
+        };
 
         state.handle = Some(handle);
         state.audio = None;
+        state.started_at = Some(Instant::now());
+        state.code = code;
+        state.llm = llm;
 
         Ok(DaemonResponse::Recording)
     }
@@ -340,6 +455,7 @@ impl DaemonServer {
             },
         };
 
+        state.started_at = None;
         info!("Cancel requested - discarding recording");
 
         // Send stop signal
@@ -354,6 +470,17 @@ impl DaemonServer {
         // Reset stop flag for next recording
         state::toggle::STOP_RECORDING.store(false, Ordering::SeqCst);
 
+        Self::reset_recording_ui()?;
+
+        info!("Recording cancelled");
+
+        Ok(DaemonResponse::Ok {
+            message: "cancelled".to_string(),
+        })
+    }
+
+    /// Return Waybar to idle after a recording that won't be transcribed
+    fn reset_recording_ui() -> Result<()> {
         // CRITICAL: Clean up state files so waybar returns to idle
         // Remove recording.pid file (waybar checks this first)
         let pid_file = state::paths::get_pid_file()?;
@@ -367,15 +494,99 @@ impl DaemonServer {
 
         // Trigger waybar refresh to return to idle
         state::toggle::refresh_waybar();
+        Ok(())
+    }
 
-        info!("Recording cancelled");
+    /// Push-to-talk key-down
+    fn handle_push_to_talk_press(&self, max_duration: u32) -> Result<DaemonResponse> {
+        let min_hold = Duration::from_millis(Self::push_to_talk_config().min_hold_ms as u64);
+        {
+            let mut state = self
+                .recording_state
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Recording state mutex poisoned: {}", e))?;
+
+            // Key repeat while held
+            if state.handle.is_some() {
+                return Ok(DaemonResponse::Ok {
+                    message: "already recording".to_string(),
+                });
+            }
 
-        Ok(DaemonResponse::Ok {
-            message: "cancelled".to_string(),
-        })
+            // The release of a very short tap can overtake its press
+            if state
+                .orphan_release_at
+                .take()
+                .is_some_and(|t| t.elapsed() < min_hold)
+            {
+                info!("Push-to-talk press arrived after its release, ignoring tap");
+                return Ok(DaemonResponse::Ok {
+                    message: "discarded".to_string(),
+                });
+            }
+        }
+
+        self.handle_start_recording(max_duration, None, None)
+    }
+
+    /// Push-to-talk key-up: discard short taps, otherwise stop and transcribe
+    fn handle_push_to_talk_release(
+        &self,
+        window: Option<FocusedWindow>,
+    ) -> Result<DaemonResponse> {
+        let ptt = Self::push_to_talk_config();
+
+        let held = {
+            let mut state = self
+                .recording_state
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Recording state mutex poisoned: {}", e))?;
+
+            match state.started_at {
+                Some(started_at) if state.handle.is_some() => started_at.elapsed(),
+                _ => {
+                    state.orphan_release_at = Some(Instant::now());
+                    return Ok(DaemonResponse::Error {
+                        message: "Not recording".to_string(),
+                    });
+                },
+            }
+        };
+
+        if held < Duration::from_millis(ptt.min_hold_ms as u64) {
+            info!(
+                "Push-to-talk released after {}ms (< {}ms), discarding",
+                held.as_millis(),
+                ptt.min_hold_ms
+            );
+            self.handle_cancel_recording()?;
+            return Ok(DaemonResponse::Ok {
+                message: "discarded".to_string(),
+            });
+        }
+
+        self.stop_and_transcribe(Some(ptt.min_speech_ms), window)
     }
 
-    fn handle_stop_recording(&self) -> Result<DaemonResponse> {
+    fn push_to_talk_config() -> crate::config::PushToTalkConfig {
+        crate::config::load()
+            .map(|c| c.push_to_talk)
+            .unwrap_or_default()
+    }
+
+    fn handle_stop_recording(&self, window: Option<FocusedWindow>) -> Result<DaemonResponse> {
+        self.stop_and_transcribe(None, window)
+    }
+
+    /// Stop the recording thread and transcribe. With `min_speech_ms`, recordings
+    /// without that much detected speech are discarded before reaching the model. The
+    /// output profile for `window` (looked up here when the client didn't send it)
+    /// chooses the post-processing.
+    fn stop_and_transcribe(
+        &self,
+        min_speech_ms: Option<u32>,
+        window: Option<FocusedWindow>,
+    ) -> Result<DaemonResponse> {
         let mut state = self
             .recording_state
             .lock()
@@ -390,6 +601,9 @@ impl DaemonServer {
                 });
             },
         };
+        state.started_at = None;
+        let code = state.code.take();
+        let llm = state.llm.take();
 
         info!("Stop requested - signaling recording thread");
 
@@ -398,7 +612,8 @@ impl DaemonServer {
 
         // Wait for recording thread to finish
         drop(state); // Release lock while waiting
-        let samples = handle
+        let window = window.or_else(focused_window);
+        let mut samples = handle
             .join()
             .map_err(|_| anyhow::anyhow!("Recording thread panicked"))??;
 
@@ -413,8 +628,29 @@ impl DaemonServer {
             });
         }
 
-        // Save audio if enabled in config, capture the saved path
+        if let Some(min_speech_ms) = min_speech_ms {
+            let params = VadParams {
+                min_speech_ms,
+                ..VadParams::default()
+            };
+            if vad::detect_speech(&samples, &params).is_empty() {
+                info!("No speech of at least {}ms detected, discarding", min_speech_ms);
+                Self::reset_recording_ui()?;
+                return Ok(DaemonResponse::Ok {
+                    message: "discarded".to_string(),
+                });
+            }
+        }
+
+        // Levels are measured before denoising and preprocessing alter them; per-channel
+        // stats of the raw capture are only logged by the audio module
+        let capture = diagnostics::analyze(&samples, TARGET_SAMPLE_RATE);
+        Self::report_capture_levels(&capture);
+
         let config = crate::config::load()?;
+        Self::condition_samples(&mut samples, &config.audio, config.audio.denoise.microphone);
+
+        // Save audio if enabled in config, capture the saved path
         let saved_audio_path = if config.audio.save_audio_clips {
             match Self::save_audio_recording(
                 &samples,
@@ -460,6 +696,15 @@ impl DaemonServer {
             },
         };
 
+        let profile = window
+            .as_ref()
+            .and_then(|w| output::profile::select(&config.output.profiles, w));
+        if let (Some(profile), Some(window)) = (profile, &window) {
+            info!("Output profile '{}' for {:?}", profile.name, window.class);
+        }
+
+        let raw_text = text;
+        let text = postprocess::dictation(&raw_text, &config, profile, llm, code);
         if text.is_empty() {
             let _ = state::toggle::cleanup_processing();
             return Ok(DaemonResponse::Error {
@@ -468,6 +713,9 @@ impl DaemonServer {
         }
 
         info!("Transcribed: {}", text);
+        if text != raw_text {
+            info!("Before post-processing: {}", raw_text);
+        }
 
         // Calculate recording duration from sample count
         // samples / sample_rate * 1000 = duration_ms
@@ -477,28 +725,236 @@ impl DaemonServer {
         let audio_path = saved_audio_path.map(|p| p.to_string_lossy().to_string());
 
         // Save to history
-        let history_entry = HistoryEntry::new(
-            text.clone(),
+        self.save_history_entry(
+            &text,
+            &raw_text,
             duration_ms,
-            self.model_name.clone(),
             audio_path,
+            Some(capture),
+            config.history.max_entries,
+        );
+
+        // Clean up processing state file (recording.pid already removed above)
+        state::toggle::cleanup_processing()?;
+
+        Ok(DaemonResponse::Success { text })
+    }
+
+    /// Audio settings for request handling, falling back to defaults if the config is unreadable
+    fn audio_config() -> AudioConfig {
+        crate::config::load()
+            .map(|config| config.audio)
+            .unwrap_or_else(|e| {
+                warn!("Failed to load config, using default audio settings: {}", e);
+                crate::config::Config::default().audio
+            })
+    }
+
+    /// The `[postprocess]` pipeline for file transcriptions that ask for it, without `llm`
+    /// steps (a model round trip per segment would stall the daemon); text passes
+    /// through unchanged if the config can't be read or a step doesn't compile
+    fn text_pipeline() -> Pipeline {
+        crate::config::load()
+            .and_then(|config| {
+                let pipeline = config.postprocess.pipeline(&config.model.language)?;
+                Ok(pipeline
+                    .without_llm()
+                    .with_vocabulary(&postprocess::vocabulary_terms()))
+            })
+            .unwrap_or_else(|e| {
+                warn!("Text post-processing disabled: {:#}", e);
+                Pipeline::default()
+            })
+    }
+
+    /// Post-process the text of a transcription response
+    fn postprocess_response(response: DaemonResponse, pipeline: &Pipeline) -> DaemonResponse {
+        match response {
+            DaemonResponse::Success { text } if text != NO_SPEECH_TEXT => DaemonResponse::Success {
+                text: pipeline.apply(&text),
+            },
+            DaemonResponse::Transcript { segments, .. } => {
+                let segments: Vec<_> = segments
+                    .into_iter()
+                    .map(|mut segment| {
+                        segment.text = pipeline.apply(&segment.text);
+                        segment
+                    })
+                    .filter(|segment| !segment.text.is_empty())
+                    .collect();
+                let text = segments
+                    .iter()
+                    .map(|s| s.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                DaemonResponse::Transcript { text, segments }
+            },
+            other => other,
+        }
+    }
+
+    /// Noise suppression (when `denoise`) followed by the `[audio.preprocess]` chain,
+    /// on 16kHz samples
+    fn condition_samples(samples: &mut [f32], audio: &AudioConfig, denoise: bool) {
+        if denoise {
+            crate::audio::denoise::denoise(samples, audio.denoise.strength);
+        }
+        if let Some(chain) = audio.preprocess.chain() {
+            chain.apply(samples, 16000);
+        }
+    }
+
+    /// Log capture levels and notify about levels likely to hurt transcription
+    fn report_capture_levels(capture: &CaptureStats) {
+        info!("Recording levels: {}", capture);
+
+        let warnings = capture.warnings();
+        if warnings.is_empty() {
+            return;
+        }
+        for warning in &warnings {
+            warn!("{}", warning);
+        }
+        crate::output::send_notification(
+            "Microphone level warning",
+            &warnings.join("\n"),
+            "normal",
         );
+    }
+
+    /// Append a transcription (post-processed and raw) to history and enforce the
+    /// max_entries limit
+    fn save_history_entry(
+        &self,
+        text: &str,
+        raw_text: &str,
+        duration_ms: u64,
+        audio_path: Option<String>,
+        capture: Option<CaptureStats>,
+        max_entries: Option<u32>,
+    ) {
+        let mut history_entry = HistoryEntry::new(
+            text.to_string(),
+            duration_ms,
+            self.model_name.clone(),
+            audio_path,
+        )
+        .with_raw_text(raw_text.to_string());
+        if let Some(capture) = capture {
+            history_entry = history_entry.with_capture(capture);
+        }
 
         if let Err(e) = history::append_entry(&history_entry) {
             warn!("Failed to save history entry: {}", e);
         } else {
             // Enforce max_entries limit if set
-            if let Some(max) = config.history.max_entries {
+            if let Some(max) = max_entries {
                 if let Err(e) = enforce_max_entries(max as usize) {
                     warn!("Failed to enforce max_entries: {}", e);
                 }
             }
         }
+    }
 
-        // Clean up processing state file (recording.pid already removed above)
-        state::toggle::cleanup_processing()?;
+    /// Handle transcribe file request (daemon decodes the file itself)
+    fn handle_transcribe_file(
+        &self,
+        path: &Path,
+        options: TranscribeOptions,
+    ) -> Result<DaemonResponse> {
+        // The daemon's working directory is unrelated to the client's
+        if !path.is_absolute() {
+            return Ok(DaemonResponse::Error {
+                message: format!("File path must be absolute: {}", path.display()),
+            });
+        }
 
-        Ok(DaemonResponse::Success { text })
+        let mut samples = match crate::audio::load_audio(path) {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("Failed to load {}: {:#}", path.display(), e);
+                return Ok(DaemonResponse::Error {
+                    message: format!("Failed to load audio: {:#}", e),
+                });
+            },
+        };
+
+        let audio_config = Self::audio_config();
+        let denoise = options.denoise.unwrap_or(audio_config.denoise.files);
+        Self::condition_samples(&mut samples, &audio_config, denoise);
+
+        let duration_ms = (samples.len() as u64 * 1000) / 16000;
+        let response = if options.timestamps {
+            self.handle_transcribe_segments(&samples)?
+        } else {
+            self.handle_transcribe_audio(samples)?
+        };
+
+        let raw_text = match &response {
+            DaemonResponse::Success { text } | DaemonResponse::Transcript { text, .. } => {
+                Some(text.clone())
+            },
+            _ => None,
+        };
+        let response = if options.postprocess {
+            Self::postprocess_response(response, &Self::text_pipeline())
+        } else {
+            response
+        };
+
+        if options.save_history {
+            let text = match &response {
+                DaemonResponse::Success { text } | DaemonResponse::Transcript { text, .. } => {
+                    Some(text)
+                },
+                _ => None,
+            };
+            if let (Some(text), Some(raw_text)) = (text, raw_text) {
+                if !text.is_empty() && text != NO_SPEECH_TEXT {
+                    let max_entries = crate::config::load()
+                        .map(|c| c.history.max_entries)
+                        .unwrap_or_else(|_| crate::config::HistoryConfig::default().max_entries);
+                    self.save_history_entry(
+                        text,
+                        &raw_text,
+                        duration_ms,
+                        Some(path.to_string_lossy().to_string()),
+                        None,
+                        max_entries,
+                    );
+                }
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// Transcribe audio into timed segments (subtitle export)
+    fn handle_transcribe_segments(&self, samples: &[f32]) -> Result<DaemonResponse> {
+        info!("Transcribing {} samples with timestamps...", samples.len());
+
+        let mut transcriber = self
+            .transcriber
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Transcriber mutex poisoned: {}", e))?;
+
+        let segments = match transcriber.transcribe_segments(samples) {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Transcription failed: {}", e);
+                return Ok(DaemonResponse::Error {
+                    message: format!("Transcription error: {}", e),
+                });
+            },
+        };
+
+        let text = segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        info!("Transcribed {} segment(s)", segments.len());
+        Ok(DaemonResponse::Transcript { text, segments })
     }
 
     /// Handle transcribe audio request (for file transcription)
@@ -532,7 +988,7 @@ impl DaemonServer {
 
         if text.is_empty() {
             return Ok(DaemonResponse::Success {
-                text: "(no speech detected)".to_string(),
+                text: NO_SPEECH_TEXT.to_string(),
             });
         }
 
@@ -592,6 +1048,18 @@ pub fn run_daemon(model_path: &Path) -> Result<()> {
 
     let server = DaemonServer::new(model_path)?;
 
+    // Global hotkeys go through the socket, so start them once it is bound
+    if let Ok(config) = crate::config::load() {
+        if config.hotkeys.enabled {
+            #[cfg(target_os = "linux")]
+            if let Err(e) = crate::daemon::hotkeys::start(&config.hotkeys) {
+                warn!("Global hotkeys disabled: {:#}", e);
+            }
+            #[cfg(not(target_os = "linux"))]
+            warn!("Global hotkeys are only supported on Linux");
+        }
+    }
+
     loop {
         // Check shutdown flag
         if server.shutdown.load(Ordering::SeqCst) {
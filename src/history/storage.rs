@@ -7,6 +7,7 @@ use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 use uuid::Uuid;
 
+use crate::audio::diagnostics::CaptureStats;
 use crate::state::paths::get_history_file;
 
 /// Get the lock file path for the history file
@@ -134,8 +135,11 @@ pub struct HistoryEntry {
     pub id: String,
     /// Unix timestamp in milliseconds
     pub timestamp: i64,
-    /// Transcribed text
+    /// Transcribed text, after post-processing
     pub text: String,
+    /// Text as the model produced it, before post-processing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_text: Option<String>,
     /// Recording duration in milliseconds
     pub duration_ms: u64,
     /// Model name used for transcription
@@ -143,6 +147,9 @@ pub struct HistoryEntry {
     /// Path to saved audio file (if save_audio_clips is enabled)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub audio_path: Option<String>,
+    /// Input level diagnostics for microphone recordings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<CaptureStats>,
 }
 
 impl HistoryEntry {
@@ -152,11 +159,25 @@ impl HistoryEntry {
             id: Uuid::new_v4().to_string(),
             timestamp: chrono::Utc::now().timestamp_millis(),
             text,
+            raw_text: None,
             duration_ms,
             model,
             audio_path,
+            capture: None,
         }
     }
+
+    /// Record the text before post-processing
+    pub fn with_raw_text(mut self, raw_text: String) -> Self {
+        self.raw_text = Some(raw_text);
+        self
+    }
+
+    /// Attach capture diagnostics
+    pub fn with_capture(mut self, capture: CaptureStats) -> Self {
+        self.capture = Some(capture);
+        self
+    }
 }
 
 /// Response containing history entries with pagination info
@@ -365,4 +386,35 @@ mod tests {
         assert_eq!(entry.model, "whisper-large");
         assert!(entry.audio_path.is_none());
     }
+
+    #[test]
+    fn test_capture_stats_roundtrip() {
+        let entry = HistoryEntry::new("Hi".to_string(), 500, "tiny".to_string(), None);
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(!json.contains("capture"));
+
+        let stats = CaptureStats {
+            peak: 1.0,
+            clipped_pct: 2.5,
+            ..CaptureStats::default()
+        };
+        let json = serde_json::to_string(&entry.with_capture(stats)).unwrap();
+        let parsed: HistoryEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.capture, Some(stats));
+    }
+
+    #[test]
+    fn test_raw_text_roundtrip() {
+        // Entries written before post-processing existed have no raw text
+        let legacy = r#"{"id":"a","timestamp":1,"text":"hi","duration_ms":5,"model":"tiny"}"#;
+        let parsed: HistoryEntry = serde_json::from_str(legacy).unwrap();
+        assert_eq!(parsed.raw_text, None);
+
+        let entry = HistoryEntry::new("Hello, world.".to_string(), 500, "tiny".to_string(), None)
+            .with_raw_text("hello comma world period".to_string());
+        let parsed: HistoryEntry =
+            serde_json::from_str(&serde_json::to_string(&entry).unwrap()).unwrap();
+        assert_eq!(parsed.text, "Hello, world.");
+        assert_eq!(parsed.raw_text.as_deref(), Some("hello comma world period"));
+    }
 }
@@ -6,6 +6,7 @@ pub mod error;
 pub mod history;
 pub mod model;
 pub mod output;
+pub mod postprocess;
 pub mod state;
 pub mod transcribe;
 pub mod vocab;
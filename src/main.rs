@@ -14,6 +14,7 @@ mod error;
 mod history;
 mod model;
 mod output;
+mod postprocess;
 mod state;
 mod transcribe;
 mod vocab;
@@ -80,6 +81,14 @@ enum Commands {
         /// Copy to clipboard instead of typing
         #[arg(short, long)]
         clipboard: bool,
+
+        /// Format the transcription as a code identifier (e.g. snake: user_account_id)
+        #[arg(long, value_name = "CASE")]
+        code: Option<postprocess::identifier::IdentifierCase>,
+
+        /// Rewrite the transcription with the [postprocess.llm] model in this mode
+        #[arg(long, value_name = "MODE")]
+        llm: Option<postprocess::LlmMode>,
     },
 
     /// Stop a running recording
@@ -88,6 +97,16 @@ enum Commands {
     /// Cancel a running recording (discard without transcribing)
     Cancel,
 
+    /// Push-to-talk: record while a key is held (for compositor press/release binds)
+    ///
+    /// Hyprland example:
+    ///   bind  = SUPER, V, exec, mojovoice ptt press
+    ///   bindr = SUPER, V, exec, mojovoice ptt release
+    Ptt {
+        #[command(subcommand)]
+        action: PttAction,
+    },
+
     /// Download a whisper model
     Download {
         /// Model name (e.g. large-v3-turbo, distil-large-v3, base.en)
@@ -132,16 +151,67 @@ enum Commands {
         /// Test clipboard mode instead of paste
         #[arg(short, long)]
         clipboard: bool,
+
+        /// Type with this backend instead of the configured or detected one
+        #[arg(short, long, value_enum, conflicts_with_all = ["clipboard", "all"])]
+        backend: Option<output::injector::Backend>,
+
+        /// Type with every available backend in turn
+        #[arg(short, long, conflicts_with = "clipboard")]
+        all: bool,
     },
 
-    /// Transcribe a WAV file (for testing/debugging)
+    /// Transcribe audio files (WAV, MP3, FLAC, OGG/Vorbis, OGG/Opus, M4A) via the daemon
+    ///
+    /// A single file prints its transcript. Multiple files, directories or glob
+    /// patterns run in batch mode and write a sidecar (e.g. memo.m4a.txt) per input.
     TranscribeFile {
-        /// Path to WAV file to transcribe
-        path: std::path::PathBuf,
+        /// Audio files, directories or glob patterns to transcribe
+        #[arg(required = true)]
+        paths: Vec<String>,
 
         /// Override model path
         #[arg(short, long)]
         model: Option<String>,
+
+        /// Record the transcription in history
+        #[arg(long)]
+        save_history: bool,
+
+        /// Transcript format: txt, json, srt or vtt (forces batch mode for a single file
+        /// unless --output is given; default: txt, or inferred from the --output extension)
+        #[arg(long, value_enum)]
+        output_format: Option<output::transcript::OutputFormat>,
+
+        /// Write the transcript of a single file here ("-" for stdout)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+
+        /// Re-transcribe files whose sidecar is already up to date
+        #[arg(long)]
+        force: bool,
+
+        /// Recurse into subdirectories
+        #[arg(short, long)]
+        recursive: bool,
+    },
+
+    /// Transcribe audio from stdin ("-") or a file and print the text (for shell pipelines)
+    Transcribe {
+        /// Input: "-" for stdin, or a path to an audio file
+        input: String,
+
+        /// Raw PCM sample format of stdin (u8, s16le, s32le, f32le); omit for WAV/MP3/FLAC/OGG/M4A
+        #[arg(short, long)]
+        format: Option<audio::PcmFormat>,
+
+        /// Sample rate of raw PCM input in Hz
+        #[arg(short, long, default_value = "16000", requires = "format")]
+        rate: u32,
+
+        /// Channel count of raw PCM input
+        #[arg(short, long, default_value = "1", requires = "format")]
+        channels: u16,
     },
 
     /// Benchmark the current model against test audio samples
@@ -161,6 +231,10 @@ enum Commands {
         /// Generate HTML report from all benchmark results
         #[arg(long)]
         report: bool,
+
+        /// Run each sample with and without noise suppression and compare WER
+        #[arg(long, conflicts_with = "report")]
+        compare_denoise: bool,
     },
 
     /// Manage vocabulary terms used to bias transcription
@@ -186,6 +260,70 @@ enum Commands {
         /// Cancel a running listen session without transcribing
         #[arg(long)]
         cancel: bool,
+
+        /// Meeting mode: record the microphone and the source (default sink monitor)
+        /// together and save a transcript labeled "Me" / "Remote"
+        #[arg(short, long, group = "transcript")]
+        meeting: bool,
+
+        /// Microphone for meeting mode. Defaults to audio.device_name.
+        #[arg(long, requires = "meeting")]
+        mic: Option<String>,
+
+        /// Continuous mode: transcribe at every pause and append timestamped lines to a
+        /// transcript file until stopped. Ignores --max-duration.
+        #[arg(long, group = "transcript")]
+        continuous: bool,
+
+        /// Also print continuous transcript lines to stdout
+        #[arg(long, requires = "continuous")]
+        stdout: bool,
+
+        /// Transcript file for meeting or continuous mode (continuous mode appends).
+        /// Defaults to a new file in ~/.local/share/mojovoice/transcripts/
+        #[arg(short, long, requires = "transcript")]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Run text through the [postprocess] pipeline, as the daemon does after transcribing
+    Postprocess {
+        /// Text to process
+        text: String,
+
+        /// Show the output of every step, not just the result
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Run the llm step in this mode (added last if not configured)
+        #[arg(long, value_name = "MODE")]
+        llm: Option<postprocess::LlmMode>,
+    },
+
+    /// Show the daemon's live input level (RMS/peak)
+    ///
+    /// Waybar example (streaming module, no interval):
+    ///   "custom/mojovoice-level": { "exec": "mojovoice levels --follow --waybar", "return-type": "json" }
+    Levels {
+        /// Keep printing updates until interrupted
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Print Waybar JSON instead of a text meter
+        #[arg(long)]
+        waybar: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PttAction {
+    /// Key down: start recording
+    Press,
+
+    /// Key up: stop and transcribe (short taps are discarded)
+    Release {
+        /// Copy to clipboard instead of typing
+        #[arg(short, long)]
+        clipboard: bool,
     },
 }
 
@@ -264,9 +402,15 @@ fn main() -> Result<()> {
             model,
             duration,
             clipboard,
-        } => cmd_start(model, duration, clipboard)?,
+            code,
+            llm,
+        } => cmd_start(model, duration, clipboard, code, llm)?,
         Commands::Stop => cmd_stop()?,
         Commands::Cancel => cmd_cancel()?,
+        Commands::Ptt { action } => match action {
+            PttAction::Press => cmd_ptt_press()?,
+            PttAction::Release { clipboard } => cmd_ptt_release(clipboard)?,
+        },
         Commands::Download { model } => cmd_download(&model)?,
         Commands::Config {
             path,
@@ -276,21 +420,65 @@ fn main() -> Result<()> {
         } => cmd_config(path, reset, check, migrate)?,
         Commands::Doctor => cmd_doctor()?,
         Commands::Daemon { command } => cmd_daemon(command)?,
-        Commands::EnigoTest { text, clipboard } => commands::enigo_test(&text, clipboard)?,
-        Commands::TranscribeFile { path, model } => cmd_transcribe_file(&path, model)?,
+        Commands::EnigoTest {
+            text,
+            clipboard,
+            backend,
+            all,
+        } => commands::enigo_test(&text, clipboard, backend, all)?,
+        Commands::TranscribeFile {
+            paths,
+            model,
+            save_history,
+            output_format,
+            output,
+            force,
+            recursive,
+        } => cmd_transcribe_files(
+            &paths,
+            model,
+            save_history,
+            output_format,
+            output,
+            force,
+            recursive,
+        )?,
+        Commands::Transcribe {
+            input,
+            format,
+            rate,
+            channels,
+        } => cmd_transcribe(&input, format, rate, channels)?,
         Commands::Benchmark {
             samples_dir,
             output_dir,
             stdout_only,
             report,
-        } => cmd_benchmark(samples_dir, output_dir, stdout_only, report)?,
+            compare_denoise,
+        } => cmd_benchmark(samples_dir, output_dir, stdout_only, report, compare_denoise)?,
         Commands::Vocab { command } => cmd_vocab(command)?,
         Commands::Listen {
             source,
             max_duration,
             clipboard,
             cancel,
-        } => cmd_listen(source, max_duration, clipboard, cancel)?,
+            meeting,
+            mic,
+            continuous,
+            stdout,
+            output,
+        } => {
+            let starting = !cancel && state::toggle::is_listening()?.is_none();
+            if meeting && starting {
+                cmd_listen_meeting(source, mic, max_duration, output)?
+            } else if continuous && starting {
+                cmd_listen_continuous(source, output, stdout)?
+            } else {
+                cmd_listen(source, max_duration, clipboard, cancel)?
+            }
+        },
+        Commands::Postprocess { text, dry_run, llm } => cmd_postprocess(&text, dry_run, llm)?,
+        Commands::Levels { follow, waybar } => commands::levels(follow, waybar)?,
     }
 
     Ok(())
@@ -310,7 +498,10 @@ fn init_logging(verbose: bool) -> Result<()> {
         .with_ansi(false)
         .with_target(false);
 
-    let console_layer = tracing_subscriber::fmt::layer().with_target(false);
+    // Logs go to stderr so stdout stays clean for piping transcripts
+    let console_layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_target(false);
 
     // Respect RUST_LOG env var, fallback to default filter
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
@@ -326,18 +517,30 @@ fn init_logging(verbose: bool) -> Result<()> {
     Ok(())
 }
 
-fn cmd_start(model_override: Option<String>, duration: u32, clipboard: bool) -> Result<()> {
+fn cmd_start(
+    model_override: Option<String>,
+    duration: u32,
+    clipboard: bool,
+    code: Option<postprocess::identifier::IdentifierCase>,
+    llm: Option<postprocess::LlmMode>,
+) -> Result<()> {
     // Check if toggle mode (duration = 0)
     if duration == 0 {
-        return cmd_start_toggle(model_override, clipboard);
+        return cmd_start_toggle(model_override, clipboard, code, llm);
     }
 
     // Fixed duration mode
-    cmd_start_fixed(model_override, duration, clipboard)
+    cmd_start_fixed(model_override, duration, clipboard, code, llm)
 }
 
-/// Toggle mode: first call starts, second call stops
-fn cmd_start_toggle(model_override: Option<String>, clipboard: bool) -> Result<()> {
+/// Toggle mode: first call starts, second call stops. `code` and `llm` apply to the
+/// recording being started.
+fn cmd_start_toggle(
+    model_override: Option<String>,
+    clipboard: bool,
+    code: Option<postprocess::identifier::IdentifierCase>,
+    llm: Option<postprocess::LlmMode>,
+) -> Result<()> {
     let mut cfg = config::load()?;
     if let Some(model_path) = model_override {
         cfg.model.path = model_path.into();
@@ -347,7 +550,7 @@ fn cmd_start_toggle(model_override: Option<String>, clipboard: bool) -> Result<(
     if state::is_recording()?.is_some() {
         cmd_stop_recording(clipboard)
     } else {
-        cmd_start_recording(cfg.audio.timeout_secs)
+        cmd_start_recording(cfg.audio.timeout_secs, code, llm)
     }
 }
 
@@ -366,9 +569,22 @@ fn cmd_stop_recording(clipboard: bool) -> Result<()> {
         let _ = std::fs::remove_file(&processing_file);
     });
 
-    let response = daemon::send_request(&daemon::DaemonRequest::StopRecording)?;
+    // Looked up before the daemon is busy transcribing, while the target still has focus
+    let window = output::window::focused_window();
+    let response = daemon::send_request(&daemon::DaemonRequest::StopRecording {
+        window: window.clone(),
+    })?;
     let _ = state::cleanup_processing();
 
+    output_transcription(response, clipboard, window.as_ref())
+}
+
+/// Inject a transcription returned by the daemon into `window` and notify
+fn output_transcription(
+    response: daemon::DaemonResponse,
+    clipboard: bool,
+    window: Option<&output::window::FocusedWindow>,
+) -> Result<()> {
     match response {
         daemon::DaemonResponse::Success { text } => {
             if text.is_empty() {
@@ -376,12 +592,12 @@ fn cmd_stop_recording(clipboard: bool) -> Result<()> {
                 return Ok(());
             }
 
-            let output_mode = output_mode_from_clipboard(clipboard);
             info!("Transcribed: {}", text);
-            output::inject_text(&text, output_mode)?;
+            let output_mode =
+                output::inject_dictation(&text, clipboard, window, &config::load()?.output)?;
             info!("Text output via {:?}", output_mode);
 
-            send_notification("Transcription Complete", &truncate_preview(&text), "normal");
+            output::send_notification("Transcription Complete", &truncate_preview(&text), "normal");
             Ok(())
         },
         daemon::DaemonResponse::Error { message } => anyhow::bail!("Daemon error: {}", message),
@@ -389,8 +605,66 @@ fn cmd_stop_recording(clipboard: bool) -> Result<()> {
     }
 }
 
+/// Push-to-talk key down: start recording via the daemon
+fn cmd_ptt_press() -> Result<()> {
+    if !daemon::is_daemon_running() {
+        anyhow::bail!("Daemon is not running. Start it first with: mojovoice daemon &");
+    }
+
+    let cfg = config::load()?;
+    let response = daemon::send_request(&daemon::DaemonRequest::PushToTalkPress {
+        max_duration: cfg.audio.timeout_secs,
+    })?;
+
+    match response {
+        daemon::DaemonResponse::Recording => {
+            info!("Push-to-talk recording started");
+            Ok(())
+        },
+        // Key repeat or a bounced tap
+        daemon::DaemonResponse::Ok { message } => {
+            info!("Push-to-talk press: {}", message);
+            Ok(())
+        },
+        daemon::DaemonResponse::Error { message } => {
+            anyhow::bail!("Failed to start recording: {}", message)
+        },
+        _ => anyhow::bail!("Unexpected response from daemon"),
+    }
+}
+
+/// Push-to-talk key up: stop, transcribe and output (short taps are discarded)
+fn cmd_ptt_release(clipboard: bool) -> Result<()> {
+    if !daemon::is_daemon_running() {
+        anyhow::bail!("Daemon is not running. Start it first with: mojovoice daemon &");
+    }
+
+    let window = output::window::focused_window();
+    let response = daemon::send_request(&daemon::DaemonRequest::PushToTalkRelease {
+        window: window.clone(),
+    })?;
+    let _ = state::cleanup_processing();
+
+    match response {
+        daemon::DaemonResponse::Ok { message } => {
+            info!("Push-to-talk release: {}", message);
+            Ok(())
+        },
+        // Release without a recording (e.g. the press failed); nothing to do
+        daemon::DaemonResponse::Error { message } if message == "Not recording" => {
+            info!("Push-to-talk release: not recording");
+            Ok(())
+        },
+        response => output_transcription(response, clipboard, window.as_ref()),
+    }
+}
+
 /// Start recording (called from toggle mode)
-fn cmd_start_recording(timeout_secs: u32) -> Result<()> {
+fn cmd_start_recording(
+    timeout_secs: u32,
+    code: Option<postprocess::identifier::IdentifierCase>,
+    llm: Option<postprocess::LlmMode>,
+) -> Result<()> {
     info!(
         "Starting recording via daemon (max {} seconds)",
         timeout_secs
@@ -403,6 +677,8 @@ fn cmd_start_recording(timeout_secs: u32) -> Result<()> {
 
     let response = daemon::send_request(&daemon::DaemonRequest::StartRecording {
         max_duration: timeout_secs,
+        code,
+        llm,
     })?;
 
     match response {
@@ -419,7 +695,13 @@ fn cmd_start_recording(timeout_secs: u32) -> Result<()> {
 }
 
 /// Fixed duration recording mode
-fn cmd_start_fixed(model_override: Option<String>, duration: u32, clipboard: bool) -> Result<()> {
+fn cmd_start_fixed(
+    model_override: Option<String>,
+    duration: u32,
+    clipboard: bool,
+    code: Option<postprocess::identifier::IdentifierCase>,
+    llm: Option<postprocess::LlmMode>,
+) -> Result<()> {
     use transcribe::Transcriber;
 
     info!("Loading configuration...");
@@ -430,9 +712,6 @@ fn cmd_start_fixed(model_override: Option<String>, duration: u32, clipboard: boo
     info!("Model: {}", cfg.model.path.display());
     validate_model_path(&cfg)?;
 
-    let output_mode = output_mode_from_clipboard(clipboard);
-    info!("Output mode: {:?}", output_mode);
-
     if let Some(ref p) = cfg.model.prompt {
         if !p.is_empty() {
             warn!("model.prompt in config is deprecated and will be ignored; use mojovoice vocab add instead.");
@@ -455,12 +734,18 @@ fn cmd_start_fixed(model_override: Option<String>, duration: u32, clipboard: boo
     info!("Model loaded successfully");
 
     info!("Recording for {} seconds...", duration);
-    let audio_data = audio::capture(
+    let mut audio_data = audio::capture(
         duration,
         cfg.audio.sample_rate,
         cfg.audio.device_name.as_deref(),
     )?;
     info!("Captured {} samples", audio_data.len());
+    if cfg.audio.denoise.microphone {
+        audio::denoise::denoise(&mut audio_data, cfg.audio.denoise.strength);
+    }
+    if let Some(chain) = cfg.audio.preprocess.chain() {
+        chain.apply(&mut audio_data, audio::TARGET_SAMPLE_RATE);
+    }
 
     let processing_file = state::get_state_dir()?.join("processing");
     std::fs::write(&processing_file, "")?;
@@ -469,7 +754,12 @@ fn cmd_start_fixed(model_override: Option<String>, duration: u32, clipboard: boo
     });
 
     info!("Transcribing...");
-    let text = transcriber.transcribe(&audio_data)?;
+    let raw_text = transcriber.transcribe(&audio_data)?;
+    let window = output::window::focused_window();
+    let profile = window
+        .as_ref()
+        .and_then(|w| output::profile::select(&cfg.output.profiles, w));
+    let text = postprocess::dictation(&raw_text, &cfg, profile, llm, code);
 
     if text.is_empty() {
         info!("No speech detected");
@@ -477,10 +767,28 @@ fn cmd_start_fixed(model_override: Option<String>, duration: u32, clipboard: boo
     }
 
     info!("Transcribed: {}", text);
-    output::inject_text(&text, output_mode)?;
+    let duration_ms = audio_data.len() as u64 * 1000 / audio::TARGET_SAMPLE_RATE as u64;
+    let model_name = cfg
+        .model
+        .path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let entry = history::HistoryEntry::new(text.clone(), duration_ms, model_name, None)
+        .with_raw_text(raw_text);
+    if let Err(e) = history::append_entry(&entry) {
+        warn!("Failed to save history entry: {}", e);
+    } else if let Some(max) = cfg.history.max_entries {
+        if let Err(e) = history::enforce_max_entries(max as usize) {
+            warn!("Failed to enforce max_entries: {}", e);
+        }
+    }
+
+    let output_mode = output::inject_dictation(&text, clipboard, window.as_ref(), &cfg.output)?;
     info!("Text output via {:?}", output_mode);
 
-    send_notification("Transcription Complete", &truncate_preview(&text), "normal");
+    output::send_notification("Transcription Complete", &truncate_preview(&text), "normal");
     Ok(())
 }
 
@@ -615,6 +923,36 @@ fn cmd_config_check() -> Result<()> {
         },
     }
 
+    match output::injector::select(&current.output) {
+        Ok(injector) if current.output.injector.is_some() => {
+            println!("✓ output.injector = {}", injector.backend())
+        },
+        Ok(injector) => println!("ℹ output.injector = (auto, {})", injector.backend()),
+        Err(e) => {
+            println!("✗ output.injector: {:#}", e);
+            has_errors = true;
+        },
+    }
+
+    for profile in &current.output.profiles {
+        match profile.validate() {
+            Ok(()) => println!("✓ output profile '{}'", profile.name),
+            Err(e) => {
+                println!("✗ output profile: {:#}", e);
+                has_errors = true;
+            },
+        }
+    }
+    if !current.output.profiles.is_empty() {
+        match output::window::focused_window() {
+            Some(window) => println!(
+                "ℹ focused window: class \"{}\", title \"{}\"",
+                window.class, window.title
+            ),
+            None => println!("ℹ focused window: unknown (profiles need Hyprland, Sway or X11)"),
+        }
+    }
+
     match current.history.max_entries {
         Some(n) => println!("✓ history.max_entries = {} entries", n),
         None => {
@@ -634,6 +972,87 @@ fn cmd_config_check() -> Result<()> {
         None => println!("ℹ audio.device_name = (not set, using system default)"),
     }
 
+    match current.audio.pre_roll_ms {
+        0 => println!("ℹ audio.pre_roll_ms = 0 (disabled, optional)"),
+        ms => println!(
+            "✓ audio.pre_roll_ms = {} ms (microphone stays open while daemon runs)",
+            ms
+        ),
+    }
+
+    println!(
+        "✓ audio.trailing_buffer_ms = {} ms (smart_stop = {})",
+        current.audio.trailing_buffer_ms, current.audio.smart_stop
+    );
+
+    match current.audio.preprocess.chain() {
+        Some(chain) => println!(
+            "✓ audio.preprocess (high_pass = {}, noise_gate = {}, normalize = {:?} to {} dBFS)",
+            chain
+                .high_pass_hz
+                .map_or("off".to_string(), |hz| format!("{} Hz", hz)),
+            chain
+                .noise_gate_dbfs
+                .map_or("off".to_string(), |db| format!("{} dBFS", db)),
+            chain.normalize,
+            chain.target_dbfs
+        ),
+        None => println!("ℹ audio.preprocess disabled (optional)"),
+    }
+
+    let denoise = &current.audio.denoise;
+    if denoise.microphone || denoise.listen || denoise.files {
+        println!(
+            "✓ audio.denoise (microphone = {}, listen = {}, files = {}, strength = {})",
+            denoise.microphone, denoise.listen, denoise.files, denoise.strength
+        );
+    } else {
+        println!("ℹ audio.denoise disabled for all sources (optional)");
+    }
+
+    println!(
+        "✓ push_to_talk.min_hold_ms = {} ms, min_speech_ms = {} ms",
+        current.push_to_talk.min_hold_ms, current.push_to_talk.min_speech_ms
+    );
+
+    if current.hotkeys.enabled {
+        println!(
+            "✓ hotkeys.enabled (toggle = {}, push_to_talk = {}, cancel = {})",
+            current.hotkeys.toggle.as_deref().unwrap_or("-"),
+            current.hotkeys.push_to_talk.as_deref().unwrap_or("-"),
+            current.hotkeys.cancel.as_deref().unwrap_or("-")
+        );
+    } else {
+        println!("ℹ hotkeys.enabled = false (using compositor keybinds)");
+    }
+
+    let steps = &current.postprocess.steps;
+    match current.postprocess.pipeline(&current.model.language) {
+        Err(e) => {
+            println!("✗ postprocess.steps: {:#}", e);
+            has_errors = true;
+        },
+        Ok(_) if steps.is_empty() => {
+            println!("ℹ postprocess.steps = [] (text used as transcribed)")
+        },
+        Ok(_) => println!(
+            "✓ postprocess.steps = {}",
+            steps.iter().map(|s| s.label()).collect::<Vec<_>>().join(" → ")
+        ),
+    }
+
+    let llm = &current.postprocess.llm;
+    match llm.check_endpoint() {
+        Ok(()) => println!(
+            "✓ postprocess.llm.endpoint = {} (mode = {:?}, timeout = {} ms)",
+            llm.endpoint, llm.mode, llm.timeout_ms
+        ),
+        Err(e) => {
+            println!("✗ postprocess.llm: {:#}", e);
+            has_errors = true;
+        },
+    }
+
     // Summary
     println!();
     if has_errors {
@@ -725,22 +1144,6 @@ fn cmd_config_migrate() -> Result<()> {
     Ok(())
 }
 
-/// Send desktop notification
-fn send_notification(title: &str, body: &str, urgency: &str) {
-    let _ = std::process::Command::new("notify-send")
-        .args([
-            "-a",
-            "mojovoice",
-            "-i",
-            "audio-input-microphone",
-            "-u",
-            urgency,
-            title,
-            body,
-        ])
-        .spawn();
-}
-
 fn cmd_daemon(command: Option<DaemonCommands>) -> Result<()> {
     match command {
         None => {
@@ -849,6 +1252,18 @@ fn cmd_daemon_status() -> Result<()> {
     println!("Model:  {}", status.model_name);
     println!("GPU:    {} ({})", gpu_status, status.gpu_name);
 
+    let device = &status.device;
+    match device.device.as_deref() {
+        Some(name) => println!("Input:  {} ({:?})", name, device.state),
+        None => println!("Input:  {:?}", device.state),
+    }
+    if device.reconnects > 0 {
+        println!("        {} reconnect(s) since start", device.reconnects);
+    }
+    if let Some(ref error) = device.last_error {
+        println!("        Last error: {}", error);
+    }
+
     if let Ok(pid_file) = state::get_daemon_pid_file() {
         if let Ok(pid) = std::fs::read_to_string(&pid_file) {
             println!("PID:    {}", pid.trim());
@@ -926,10 +1341,27 @@ fn cmd_daemon_pid() -> Result<()> {
 fn cmd_doctor() -> Result<()> {
     println!("Checking system dependencies...\n");
 
-    println!("[OK] Text injection (enigo - cross-platform, built-in)");
+    let cfg = config::load()?;
+    let display = output::DisplayServer::detect(cfg.output.display_server.as_deref());
+    let selected = output::injector::select(&cfg.output).map(|injector| injector.backend());
+    println!("Text injection ({:?}):", display);
+    for (backend, available) in output::injector::availability(display, &cfg.output.paste) {
+        let marker = if selected.as_ref().ok() == Some(&backend) {
+            " (selected)"
+        } else {
+            ""
+        };
+        match available {
+            Ok(()) => println!("[OK] {}{}", backend, marker),
+            Err(e) => println!("[MISSING] {}{}: {:#}", backend, marker, e),
+        }
+    }
+    if let Err(e) = selected {
+        println!("[ERROR] {:#}", e);
+    }
+    #[cfg(not(target_os = "linux"))]
     println!("[OK] Clipboard (arboard - cross-platform, built-in)");
 
-    let cfg = config::load()?;
     let model_ok = cfg.model.path.exists();
     println!(
         "\n[{}] Whisper model: {}",
@@ -941,12 +1373,51 @@ fn cmd_doctor() -> Result<()> {
         println!("\nDownload a model with: mojovoice download base.en");
     }
 
-    let pw_ok = std::process::Command::new("pw-cli")
-        .arg("info")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
-    println!("\n[{}] PipeWire", if pw_ok { "OK" } else { "MISSING" });
+    #[cfg(target_os = "linux")]
+    match audio::pulse::Client::connect().and_then(|mut client| client.server_info()) {
+        Ok(server) => {
+            println!("\n[OK] Sound server: {} {}", server.package_name, server.package_version);
+            println!(
+                "     Default source: {}",
+                server.default_source.as_deref().unwrap_or("none")
+            );
+        },
+        Err(e) => println!(
+            "\n[MISSING] PipeWire/PulseAudio: {:#} (capture falls back to ALSA)",
+            e
+        ),
+    }
+
+    if cfg.hotkeys.enabled {
+        #[cfg(target_os = "linux")]
+        match daemon::hotkeys::check(&cfg.hotkeys) {
+            Ok(keyboards) => {
+                println!("\n[OK] Global hotkeys: {} keyboard(s)", keyboards.len());
+                for keyboard in keyboards {
+                    println!("     {}", keyboard);
+                }
+            },
+            Err(e) => println!("\n[ERROR] Global hotkeys: {:#}", e),
+        }
+        #[cfg(not(target_os = "linux"))]
+        println!("\n[ERROR] Global hotkeys: only supported on Linux");
+    }
+
+    // Levels of the most recent microphone recording
+    let last_capture = history::load_entries(50, 0, None, None)
+        .ok()
+        .and_then(|h| h.entries.into_iter().find_map(|e| e.capture));
+    if let Some(capture) = last_capture {
+        let warnings = capture.warnings();
+        if warnings.is_empty() {
+            println!("\n[OK] Last recording levels: {}", capture);
+        } else {
+            println!("\n[WARN] Last recording levels: {}", capture);
+            for warning in warnings {
+                println!("     {}", warning);
+            }
+        }
+    }
 
     // Show log location
     if let Ok(log_dir) = state::get_log_dir() {
@@ -963,6 +1434,7 @@ fn cmd_benchmark(
     output_dir: Option<std::path::PathBuf>,
     stdout_only: bool,
     report: bool,
+    compare_denoise: bool,
 ) -> Result<()> {
     // Default paths relative to current working directory
     let samples_dir =
@@ -983,6 +1455,10 @@ fn cmd_benchmark(
         );
     }
 
+    if compare_denoise {
+        return benchmark::run_denoise_comparison(&samples_dir);
+    }
+
     benchmark::run_benchmark(&samples_dir, &output_dir, stdout_only)
 }
 
@@ -1023,6 +1499,34 @@ fn cmd_vocab(command: VocabCommands) -> Result<()> {
     Ok(())
 }
 
+fn cmd_postprocess(text: &str, dry_run: bool, llm: Option<postprocess::LlmMode>) -> Result<()> {
+    let cfg = config::load()?;
+    let terms = vocab::VocabStore::open()
+        .and_then(|s| s.terms())
+        .unwrap_or_default();
+    let pipeline = cfg
+        .postprocess
+        .pipeline(&cfg.model.language)?
+        .with_llm(&cfg.postprocess.llm, llm)
+        .with_vocabulary(&terms);
+
+    if !dry_run {
+        println!("{}", pipeline.apply(text));
+        return Ok(());
+    }
+
+    let trace = pipeline.trace(text);
+    let width = trace.iter().map(|(label, _)| label.len()).max().unwrap_or(0).max(5);
+    println!("{:width$}  {:?}", "input", text);
+    for (label, output) in &trace {
+        println!("{:width$}  {:?}", label, output);
+    }
+    if trace.is_empty() {
+        println!("(no [postprocess] steps configured)");
+    }
+    Ok(())
+}
+
 fn cmd_listen(source: Option<String>, max_duration: u32, clipboard: bool, cancel: bool) -> Result<()> {
     if cancel {
         return cmd_listen_cancel();
@@ -1058,7 +1562,7 @@ fn cmd_listen_start(source: Option<String>, max_duration: u32, clipboard: bool)
     let output_mode = output_mode_from_clipboard(clipboard);
 
     // On Linux: default to monitor of current default sink (captures app audio like Discord)
-    // Falls back to default mic input if pactl unavailable.
+    // Falls back to default mic input if the sound server is unreachable.
     #[cfg(target_os = "linux")]
     let source = source.or_else(|| audio::get_default_sink_monitor());
 
@@ -1072,7 +1576,12 @@ fn cmd_listen_start(source: Option<String>, max_duration: u32, clipboard: bool)
         max_duration
     );
 
-    let samples = audio::capture_toggle(max_duration, cfg.audio.sample_rate, source.as_deref());
+    let samples = audio::capture_toggle(
+        max_duration,
+        cfg.audio.sample_rate,
+        source.as_deref(),
+        cfg.audio.trailing_buffer(),
+    );
 
     // Always clean up PID file, even if capture failed
     let _ = state::toggle::cleanup_listen();
@@ -1083,13 +1592,17 @@ fn cmd_listen_start(source: Option<String>, max_duration: u32, clipboard: bool)
         return Ok(());
     }
 
-    let samples = samples?;
+    let mut samples = samples?;
 
     if samples.is_empty() {
         println!("No audio captured.");
         return Ok(());
     }
 
+    if cfg.audio.denoise.listen {
+        audio::denoise::denoise(&mut samples, cfg.audio.denoise.strength);
+    }
+
     info!("Captured {} samples, sending to daemon for transcription...", samples.len());
 
     let response = daemon::send_request(&daemon::DaemonRequest::TranscribeAudio { samples })?;
@@ -1101,8 +1614,8 @@ fn cmd_listen_start(source: Option<String>, max_duration: u32, clipboard: bool)
                 return Ok(());
             }
             info!("Transcribed: {}", text);
-            output::inject_text(&text, output_mode)?;
-            send_notification("Listen Transcription", &truncate_preview(&text), "normal");
+            output::inject_text(&text, output_mode, &cfg.output)?;
+            output::send_notification("Listen Transcription", &truncate_preview(&text), "normal");
             Ok(())
         }
         daemon::DaemonResponse::Error { message } => {
@@ -1112,6 +1625,116 @@ fn cmd_listen_start(source: Option<String>, max_duration: u32, clipboard: bool)
     }
 }
 
+/// Record mic and sink monitor together, then save a merged "Me"/"Remote" transcript
+fn cmd_listen_meeting(
+    source: Option<String>,
+    mic: Option<String>,
+    max_duration: u32,
+    output: Option<std::path::PathBuf>,
+) -> Result<()> {
+    if !daemon::is_daemon_running() {
+        anyhow::bail!("daemon is not running — start it first with: mojovoice daemon up");
+    }
+
+    let cfg = config::load()?;
+    #[cfg(target_os = "linux")]
+    let source = source.or_else(audio::get_default_sink_monitor);
+    let Some(source) = source else {
+        anyhow::bail!("no sink monitor found — pass the remote side with --source");
+    };
+    let mic = mic.or_else(|| cfg.audio.device_name.clone());
+
+    state::toggle::STOP_RECORDING.store(false, std::sync::atomic::Ordering::SeqCst);
+    state::toggle::setup_signal_handler()?;
+    state::toggle::start_listen()?;
+
+    println!(
+        "Recording meeting: {} (me) + {} (remote).",
+        mic.as_deref().unwrap_or("default input"),
+        source
+    );
+    println!("Run 'mojovoice listen' again to stop (max {}s).", max_duration);
+
+    let recording = commands::meeting::record(&cfg, mic.as_deref(), &source, max_duration);
+
+    // Always clean up PID file, even if capture failed
+    let _ = state::toggle::cleanup_listen();
+
+    if check_and_clear_cancel_file() {
+        println!("Meeting capture cancelled.");
+        return Ok(());
+    }
+
+    println!("Transcribing...");
+    let segments = commands::meeting::transcribe(&cfg, recording?)?;
+    if segments.is_empty() {
+        println!("No speech detected.");
+        return Ok(());
+    }
+
+    let transcript = commands::meeting::format_transcript(&segments);
+    let path = match output {
+        Some(path) => path,
+        None => state::paths::new_transcript_path("meeting")?,
+    };
+    std::fs::write(&path, &transcript)?;
+
+    print!("{}", transcript);
+    println!("\nSaved to {}", path.display());
+    output::send_notification(
+        "Meeting Transcript",
+        &format!("{} lines saved to {}", segments.len(), path.display()),
+        "normal",
+    );
+    Ok(())
+}
+
+/// Transcribe at every pause into a transcript file until stopped
+fn cmd_listen_continuous(
+    source: Option<String>,
+    output: Option<std::path::PathBuf>,
+    stdout: bool,
+) -> Result<()> {
+    if !daemon::is_daemon_running() {
+        anyhow::bail!("daemon is not running — start it first with: mojovoice daemon up");
+    }
+
+    let cfg = config::load()?;
+    #[cfg(target_os = "linux")]
+    let source = source.or_else(audio::get_default_sink_monitor);
+    let path = match output {
+        Some(path) => path,
+        None => state::paths::new_transcript_path("listen")?,
+    };
+
+    state::toggle::STOP_RECORDING.store(false, std::sync::atomic::Ordering::SeqCst);
+    state::toggle::setup_signal_handler()?;
+    state::toggle::start_listen()?;
+
+    eprintln!(
+        "Listening continuously on {}, writing to {}.",
+        source.as_deref().unwrap_or("default input"),
+        path.display()
+    );
+    eprintln!("Run 'mojovoice listen' again to stop.");
+
+    // A cancel only drops the utterance in progress; finished lines are already saved
+    let result = commands::continuous::run(&cfg, source.as_deref(), &path, stdout, || {
+        !check_and_clear_cancel_file()
+    });
+
+    let _ = state::toggle::cleanup_listen();
+    let lines = result?;
+
+    eprintln!("Stopped. {} line(s) in {}", lines, path.display());
+    output::send_notification(
+        "Listen Transcript",
+        &format!("{} lines saved to {}", lines, path.display()),
+        "normal",
+    );
+    Ok(())
+}
+
 /// Stop a running listen session (sends SIGUSR1 to the listen process)
 fn cmd_listen_stop() -> Result<()> {
     match state::toggle::is_listening()? {
@@ -1154,95 +1777,110 @@ fn cmd_listen_cancel() -> Result<()> {
     }
 }
 
-/// Transcribe a WAV file via daemon (for testing/debugging)
-fn cmd_transcribe_file(path: &std::path::Path, _model_override: Option<String>) -> Result<()> {
-    use hound::WavReader;
-    use rubato::{
-        Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
-    };
+/// Transcribe one file (printing or writing the transcript) or run a batch (writing sidecars)
+fn cmd_transcribe_files(
+    paths: &[String],
+    model_override: Option<String>,
+    save_history: bool,
+    output_format: Option<output::transcript::OutputFormat>,
+    output: Option<std::path::PathBuf>,
+    force: bool,
+    recursive: bool,
+) -> Result<()> {
+    use commands::transcribe_batch::BatchOptions;
+    use output::transcript::OutputFormat;
 
-    const TARGET_SAMPLE_RATE: u32 = 16000;
+    if let Some(output) = output {
+        let [path] = paths else {
+            anyhow::bail!("--output takes exactly one input file");
+        };
+        let format = output_format
+            .or_else(|| OutputFormat::from_path(&output))
+            .unwrap_or(OutputFormat::Txt);
+        return cmd_transcribe_file_to(std::path::Path::new(path), &output, format, save_history);
+    }
+
+    // A lone plain file without a sidecar format keeps the print-to-terminal behavior
+    if let [path] = paths {
+        let path = std::path::Path::new(path);
+        if output_format.is_none() && path.is_file() {
+            return cmd_transcribe_file(path, model_override, save_history);
+        }
+    }
+
+    commands::transcribe_batch(
+        paths,
+        &BatchOptions {
+            format: output_format.unwrap_or(OutputFormat::Txt),
+            force,
+            recursive,
+            save_history,
+        },
+    )
+}
+
+/// Transcribe one file and write it in `format` to `output` ("-" for stdout)
+fn cmd_transcribe_file_to(
+    path: &std::path::Path,
+    output: &std::path::Path,
+    format: output::transcript::OutputFormat,
+    save_history: bool,
+) -> Result<()> {
+    use anyhow::Context;
 
     if !daemon::is_daemon_running() {
         anyhow::bail!("Daemon is not running. Start it first with: mojovoice daemon &");
     }
 
-    if !path.exists() {
+    if !path.is_file() {
         anyhow::bail!("File not found: {}", path.display());
     }
 
-    info!("Loading WAV file: {}", path.display());
-    let mut reader = WavReader::open(path)?;
-    let spec = reader.spec();
+    let model_name = daemon::daemon_get_status()
+        .map(|s| s.model_name)
+        .unwrap_or_else(|_| "unknown".to_string());
 
-    info!(
-        "WAV spec: {} channels, {}Hz, {}-bit {:?}",
-        spec.channels, spec.sample_rate, spec.bits_per_sample, spec.sample_format
-    );
+    info!("Sending {} to daemon for transcription...", path.display());
+    let (text, segments) =
+        commands::transcribe_batch::request_transcript(path, save_history, format)?;
+    let contents = output::transcript::render(format, &text, &segments, path, &model_name)?;
 
-    let samples: Vec<f32> = match spec.sample_format {
-        hound::SampleFormat::Float => reader.samples::<f32>().map(|s| s.unwrap()).collect(),
-        hound::SampleFormat::Int => {
-            let max_val = (1 << (spec.bits_per_sample - 1)) as f32;
-            reader
-                .samples::<i32>()
-                .map(|s| s.unwrap() as f32 / max_val)
-                .collect()
-        },
-    };
-    info!("Loaded {} raw samples", samples.len());
-
-    let mono_samples: Vec<f32> = if spec.channels == 2 {
-        info!("Converting stereo to mono...");
-        samples
-            .chunks(2)
-            .map(|chunk| (chunk[0] + chunk[1]) / 2.0)
-            .collect()
+    if output == std::path::Path::new("-") {
+        print!("{}", contents);
     } else {
-        samples
-    };
-    info!("Mono samples: {}", mono_samples.len());
+        std::fs::write(output, contents)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+        println!("Wrote {}", output.display());
+    }
 
-    let audio_16k: Vec<f32> = if spec.sample_rate != TARGET_SAMPLE_RATE {
-        info!(
-            "Resampling from {}Hz to {}Hz...",
-            spec.sample_rate, TARGET_SAMPLE_RATE
-        );
+    Ok(())
+}
 
-        let params = SincInterpolationParameters {
-            sinc_len: 256,
-            f_cutoff: 0.95,
-            interpolation: SincInterpolationType::Linear,
-            oversampling_factor: 256,
-            window: WindowFunction::BlackmanHarris2,
-        };
+/// Transcribe an audio file via daemon (daemon decodes the file itself)
+fn cmd_transcribe_file(
+    path: &std::path::Path,
+    _model_override: Option<String>,
+    save_history: bool,
+) -> Result<()> {
+    if !daemon::is_daemon_running() {
+        anyhow::bail!("Daemon is not running. Start it first with: mojovoice daemon &");
+    }
 
-        let mut resampler = SincFixedIn::<f32>::new(
-            TARGET_SAMPLE_RATE as f64 / spec.sample_rate as f64,
-            2.0,
-            params,
-            mono_samples.len(),
-            1,
-        )?;
-
-        let waves_in = vec![mono_samples];
-        let mut waves_out = resampler.process(&waves_in, None)?;
-        waves_out.remove(0)
-    } else {
-        mono_samples
-    };
+    if !path.exists() {
+        anyhow::bail!("File not found: {}", path.display());
+    }
 
-    let duration_secs = audio_16k.len() as f32 / TARGET_SAMPLE_RATE as f32;
-    info!(
-        "Final audio: {} samples ({:.2}s at {}Hz)",
-        audio_16k.len(),
-        duration_secs,
-        TARGET_SAMPLE_RATE
-    );
+    // Daemon resolves paths against its own working directory, so send an absolute path
+    let path = std::fs::canonicalize(path)?;
 
-    info!("Sending to daemon for transcription...");
-    let response =
-        daemon::send_request(&daemon::DaemonRequest::TranscribeAudio { samples: audio_16k })?;
+    info!("Sending {} to daemon for transcription...", path.display());
+    let response = daemon::send_request(&daemon::DaemonRequest::TranscribeFile {
+        path,
+        options: daemon::TranscribeOptions {
+            save_history,
+            ..Default::default()
+        },
+    })?;
 
     match response {
         daemon::DaemonResponse::Success { text } => println!("\n=== Transcription ===\n{}\n", text),
@@ -1255,6 +1893,176 @@ fn cmd_transcribe_file(path: &std::path::Path, _model_override: Option<String>)
     Ok(())
 }
 
+/// Transcribe stdin ("-") or a file and print only the text to stdout
+fn cmd_transcribe(
+    input: &str,
+    format: Option<audio::PcmFormat>,
+    rate: u32,
+    channels: u16,
+) -> Result<()> {
+    use std::io::Read;
+
+    if !daemon::is_daemon_running() {
+        anyhow::bail!("Daemon is not running. Start it first with: mojovoice daemon &");
+    }
+
+    let request = if input == "-" {
+        let mut data = Vec::new();
+        std::io::stdin().lock().read_to_end(&mut data)?;
+        if data.is_empty() {
+            anyhow::bail!("No audio received on stdin");
+        }
+        info!("Read {} bytes from stdin", data.len());
+
+        let mut samples = match format {
+            Some(format) => audio::load_raw_pcm(&data, format, rate, channels)?,
+            None => audio::load_audio_bytes(data)?,
+        };
+        // Stdin counts as a file source; the daemon only denoises files it reads itself
+        let denoise = config::load()?.audio.denoise;
+        if denoise.files {
+            audio::denoise::denoise(&mut samples, denoise.strength);
+        }
+        daemon::DaemonRequest::TranscribeAudio { samples }
+    } else {
+        let path = std::path::Path::new(input);
+        if !path.exists() {
+            anyhow::bail!("File not found: {}", path.display());
+        }
+        daemon::DaemonRequest::TranscribeFile {
+            path: std::fs::canonicalize(path)?,
+            options: daemon::TranscribeOptions::default(),
+        }
+    };
+
+    match daemon::send_request(&request)? {
+        daemon::DaemonResponse::Success { text } => println!("{}", text),
+        daemon::DaemonResponse::Error { message } => {
+            anyhow::bail!("Transcription failed: {}", message)
+        },
+        _ => anyhow::bail!("Unexpected response from daemon"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod transcribe_tests {
+    use super::*;
+
+    #[test]
+    fn test_transcribe_stdin_raw_pcm_flags_parse() {
+        let cli = Cli::parse_from([
+            "mojovoice",
+            "transcribe",
+            "-",
+            "--format",
+            "s16le",
+            "--rate",
+            "48000",
+            "--channels",
+            "2",
+        ]);
+        if let Commands::Transcribe {
+            input,
+            format,
+            rate,
+            channels,
+        } = cli.command
+        {
+            assert_eq!(input, "-");
+            assert_eq!(format, Some(audio::PcmFormat::S16le));
+            assert_eq!(rate, 48000);
+            assert_eq!(channels, 2);
+        } else {
+            panic!("Expected Transcribe command");
+        }
+    }
+
+    #[test]
+    fn test_transcribe_file_accepts_multiple_inputs() {
+        let cli = Cli::parse_from([
+            "mojovoice",
+            "transcribe-file",
+            "memos/",
+            "*.m4a",
+            "--output-format",
+            "json",
+        ]);
+        if let Commands::TranscribeFile {
+            paths,
+            output_format,
+            ..
+        } = cli.command
+        {
+            assert_eq!(paths, ["memos/", "*.m4a"]);
+            assert_eq!(
+                output_format,
+                Some(output::transcript::OutputFormat::Json)
+            );
+        } else {
+            panic!("Expected TranscribeFile command");
+        }
+    }
+
+    #[test]
+    fn test_transcribe_file_output_parse() {
+        let cli = Cli::parse_from([
+            "mojovoice",
+            "transcribe-file",
+            "demo.mp4",
+            "-o",
+            "demo.vtt",
+        ]);
+        if let Commands::TranscribeFile {
+            output,
+            output_format,
+            ..
+        } = cli.command
+        {
+            assert_eq!(output, Some(std::path::PathBuf::from("demo.vtt")));
+            assert_eq!(output_format, None);
+        } else {
+            panic!("Expected TranscribeFile command");
+        }
+    }
+
+    #[test]
+    fn test_transcribe_rate_requires_format() {
+        let result = Cli::try_parse_from(["mojovoice", "transcribe", "-", "--rate", "48000"]);
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod ptt_tests {
+    use super::*;
+
+    #[test]
+    fn test_ptt_press_and_release_parse() {
+        let cli = Cli::parse_from(["mojovoice", "ptt", "press"]);
+        assert!(matches!(
+            cli.command,
+            Commands::Ptt {
+                action: PttAction::Press
+            }
+        ));
+
+        let cli = Cli::parse_from(["mojovoice", "ptt", "release", "--clipboard"]);
+        assert!(matches!(
+            cli.command,
+            Commands::Ptt {
+                action: PttAction::Release { clipboard: true }
+            }
+        ));
+    }
+
+    #[test]
+    fn test_ptt_requires_action() {
+        assert!(Cli::try_parse_from(["mojovoice", "ptt"]).is_err());
+    }
+}
+
 #[cfg(test)]
 mod listen_tests {
     use super::*;
@@ -1292,7 +2100,7 @@ mod listen_tests {
                 source
             );
         }
-        // None is acceptable if pactl unavailable
+        // None is acceptable if the sound server is unreachable
     }
 
     #[test]
@@ -1323,6 +2131,45 @@ mod listen_tests {
         }
     }
 
+    #[test]
+    fn test_listen_transcript_modes_parse() {
+        let args = ["mojovoice", "listen", "--continuous", "--stdout", "-o", "t.txt"];
+        let cli = Cli::parse_from(args);
+        if let Commands::Listen {
+            continuous,
+            stdout,
+            output,
+            ..
+        } = cli.command
+        {
+            assert!(continuous && stdout);
+            assert_eq!(output, Some(std::path::PathBuf::from("t.txt")));
+        } else {
+            panic!("Expected Listen command");
+        }
+
+        // One transcript mode at a time, and --output needs one of them
+        assert!(Cli::try_parse_from(["mojovoice", "listen", "--continuous", "--meeting"]).is_err());
+        assert!(Cli::try_parse_from(["mojovoice", "listen", "-o", "t.txt"]).is_err());
+        assert!(Cli::try_parse_from(["mojovoice", "listen", "--stdout"]).is_err());
+        assert!(Cli::try_parse_from(["mojovoice", "listen", "-m", "-o", "t.txt"]).is_ok());
+    }
+
+    #[test]
+    fn test_start_dictation_options_parse() {
+        use postprocess::identifier::IdentifierCase;
+
+        let args = ["mojovoice", "start", "--code", "screaming-snake", "--llm", "commit"];
+        let cli = Cli::parse_from(args);
+        if let Commands::Start { code, llm, .. } = cli.command {
+            assert_eq!(code, Some(IdentifierCase::ScreamingSnake));
+            assert_eq!(llm, Some(postprocess::LlmMode::Commit));
+        } else {
+            panic!("Expected Start command");
+        }
+        assert!(Cli::try_parse_from(["mojovoice", "start", "--code", "title"]).is_err());
+    }
+
     #[test]
     fn test_cmd_listen_cancel_no_session_prints_message() {
         let _ = state::toggle::cleanup_listen();
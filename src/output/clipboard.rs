@@ -0,0 +1,197 @@
+//! Clipboard snapshots, so pasting a dictation can put back what the user had copied
+//!
+//! A snapshot keeps one type of the clipboard contents: an image if one is offered,
+//! otherwise text, otherwise the first MIME type (e.g. a file list). wl-copy and
+//! xclip can only offer a single type, so the others are lost on restore.
+
+use anyhow::{Context, Result};
+
+use super::DisplayServer;
+
+/// Saved clipboard contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// MIME type, or None for text (offered as every text type on restore)
+    mime: Option<String>,
+    data: Vec<u8>,
+}
+
+/// Targets xclip lists that describe the selection rather than hold its contents
+const X11_META_TARGETS: &[&str] = &["TARGETS", "TIMESTAMP", "MULTIPLE", "SAVE_TARGETS"];
+
+/// Text types, most preferred first
+const TEXT_TYPES: &[&str] = &[
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "STRING",
+    "TEXT",
+];
+
+/// The type to save out of those offered: (type to read, MIME type to restore as)
+fn choose_type(types: &[String]) -> Option<(&str, Option<&str>)> {
+    let types: Vec<&str> = types
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty() && !X11_META_TARGETS.contains(t))
+        .collect();
+
+    if let Some(image) = types.iter().find(|t| t.starts_with("image/")) {
+        return Some((image, Some(image)));
+    }
+    if let Some(text) = TEXT_TYPES.iter().find(|t| types.contains(t)) {
+        return Some((text, None));
+    }
+    types
+        .iter()
+        .find(|t| t.contains('/'))
+        .map(|other| (*other, Some(*other)))
+}
+
+#[cfg(target_os = "linux")]
+mod tools {
+    use anyhow::{Context, Result};
+    use std::process::{Command, Stdio};
+
+    use super::DisplayServer;
+
+    /// Stdout of a clipboard tool, or None if it fails (e.g. nothing is copied)
+    pub fn read(display: DisplayServer, args: &[&str]) -> Result<Option<Vec<u8>>> {
+        let (program, base) = match display {
+            DisplayServer::Wayland => ("wl-paste", &[][..]),
+            DisplayServer::X11 => ("xclip", &["-selection", "clipboard", "-o"][..]),
+        };
+        let output = Command::new(program)
+            .args(base)
+            .args(args)
+            .stderr(Stdio::null())
+            .output()
+            .with_context(|| format!("Failed to run {}", program))?;
+        Ok(output.status.success().then_some(output.stdout))
+    }
+
+    pub fn clear(display: DisplayServer) -> Result<()> {
+        match display {
+            DisplayServer::Wayland => {
+                let status = Command::new("wl-copy").arg("--clear").status()?;
+                if !status.success() {
+                    anyhow::bail!("wl-copy --clear exited with status: {}", status);
+                }
+                Ok(())
+            },
+            DisplayServer::X11 => super::super::write_clipboard(display, None, b""),
+        }
+    }
+}
+
+/// The current clipboard contents; None when the clipboard is empty
+#[cfg(target_os = "linux")]
+pub fn save(display: DisplayServer) -> Result<Option<Snapshot>> {
+    let list: &[&str] = match display {
+        DisplayServer::Wayland => &["--list-types"],
+        DisplayServer::X11 => &["-t", "TARGETS"],
+    };
+    let Some(types) = tools::read(display, list)? else {
+        return Ok(None);
+    };
+    let types: Vec<String> = String::from_utf8_lossy(&types)
+        .lines()
+        .map(String::from)
+        .collect();
+    let Some((read_as, mime)) = choose_type(&types) else {
+        return Ok(None);
+    };
+
+    let data = match display {
+        DisplayServer::Wayland => tools::read(display, &["--no-newline", "--type", read_as])?,
+        DisplayServer::X11 => tools::read(display, &["-t", read_as])?,
+    }
+    .with_context(|| format!("Failed to read the clipboard as {}", read_as))?;
+    Ok(Some(Snapshot {
+        mime: mime.map(String::from),
+        data,
+    }))
+}
+
+/// Put back a saved clipboard, or clear it if it was empty
+#[cfg(target_os = "linux")]
+pub fn restore(snapshot: Option<&Snapshot>, display: DisplayServer) -> Result<()> {
+    match snapshot {
+        Some(snapshot) => super::write_clipboard(display, snapshot.mime.as_deref(), &snapshot.data),
+        None => tools::clear(display),
+    }
+}
+
+/// Whether the clipboard still holds `text`, i.e. nothing was copied since it was set
+#[cfg(target_os = "linux")]
+pub fn holds_text(text: &str, display: DisplayServer) -> Result<bool> {
+    let current = match display {
+        DisplayServer::Wayland => tools::read(display, &["--no-newline"])?,
+        DisplayServer::X11 => tools::read(display, &[])?,
+    };
+    Ok(current.as_deref() == Some(text.as_bytes()))
+}
+
+/// The current clipboard text; None when there is no text
+#[cfg(not(target_os = "linux"))]
+pub fn save(_display: DisplayServer) -> Result<Option<Snapshot>> {
+    let mut clipboard = arboard::Clipboard::new().context("Failed to access clipboard")?;
+    Ok(clipboard.get_text().ok().map(|text| Snapshot {
+        mime: None,
+        data: text.into_bytes(),
+    }))
+}
+
+/// Put back saved clipboard text, or clear the clipboard if it had none
+#[cfg(not(target_os = "linux"))]
+pub fn restore(snapshot: Option<&Snapshot>, _display: DisplayServer) -> Result<()> {
+    let mut clipboard = arboard::Clipboard::new().context("Failed to access clipboard")?;
+    match snapshot {
+        Some(snapshot) => clipboard
+            .set_text(String::from_utf8_lossy(&snapshot.data).into_owned())
+            .context("Failed to restore clipboard text"),
+        None => clipboard.clear().context("Failed to clear clipboard"),
+    }
+}
+
+/// Whether the clipboard still holds `text`, i.e. nothing was copied since it was set
+#[cfg(not(target_os = "linux"))]
+pub fn holds_text(text: &str, _display: DisplayServer) -> Result<bool> {
+    let mut clipboard = arboard::Clipboard::new().context("Failed to access clipboard")?;
+    Ok(clipboard.get_text().is_ok_and(|current| current == text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types(list: &[&str]) -> Vec<String> {
+        list.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_choose_type() {
+        // A browser selection: saved as plain text, not HTML
+        let browser = types(&["text/html", "text/plain;charset=utf-8", "text/plain"]);
+        assert_eq!(
+            choose_type(&browser),
+            Some(("text/plain;charset=utf-8", None))
+        );
+
+        // A screenshot also offered as text by some tools
+        let image = types(&["TARGETS", "TIMESTAMP", "UTF8_STRING", "image/png"]);
+        assert_eq!(choose_type(&image), Some(("image/png", Some("image/png"))));
+
+        let files = types(&["x-special/gnome-copied-files", "text/uri-list"]);
+        assert_eq!(
+            choose_type(&files),
+            Some((
+                "x-special/gnome-copied-files",
+                Some("x-special/gnome-copied-files")
+            ))
+        );
+
+        assert_eq!(choose_type(&types(&["TARGETS", "TIMESTAMP"])), None);
+        assert_eq!(choose_type(&[]), None);
+    }
+}
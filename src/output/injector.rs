@@ -0,0 +1,473 @@
+//! Text injection backends
+//!
+//! Dictations are typed by enigo (built in), the wtype, ydotool or xdotool command
+//! line tools, or by pasting through the clipboard. `output.injector` picks one;
+//! unset, the first backend available on the display server is used: wtype, ydotool,
+//! enigo, paste on Wayland and xdotool, enigo, ydotool, paste on X11.
+
+use anyhow::{Context, Result};
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use super::{DisplayServer, clipboard};
+use crate::config::OutputConfig;
+
+/// Types text into the focused window
+pub trait Injector {
+    fn backend(&self) -> Backend;
+    /// Ok if the backend can type on `display`, otherwise the reason it can't
+    fn check(&self, display: DisplayServer) -> Result<()>;
+    fn type_text(&self, text: &str) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// Built in: virtual keyboard on Wayland, native APIs on macOS and Windows
+    Enigo,
+    /// wtype (Wayland compositors with the virtual keyboard protocol)
+    Wtype,
+    /// ydotool through /dev/uinput (any display server; needs ydotoold running)
+    Ydotool,
+    /// xdotool (X11)
+    Xdotool,
+    /// Paste through the clipboard (`[output.paste]`), restoring its contents
+    Paste,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 5] = [
+        Backend::Enigo,
+        Backend::Wtype,
+        Backend::Ydotool,
+        Backend::Xdotool,
+        Backend::Paste,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Enigo => "enigo",
+            Backend::Wtype => "wtype",
+            Backend::Ydotool => "ydotool",
+            Backend::Xdotool => "xdotool",
+            Backend::Paste => "paste",
+        }
+    }
+
+    pub fn injector(self, display: DisplayServer, paste: &PasteConfig) -> Box<dyn Injector> {
+        match self {
+            Backend::Enigo => Box::new(EnigoInjector),
+            Backend::Wtype => Box::new(Wtype),
+            Backend::Ydotool => Box::new(Ydotool),
+            Backend::Xdotool => Box::new(Xdotool),
+            Backend::Paste => Box::new(Paste {
+                display,
+                config: paste.clone(),
+            }),
+        }
+    }
+
+    /// Auto-detection order
+    fn priority(display: DisplayServer) -> &'static [Backend] {
+        match display {
+            DisplayServer::Wayland => &[
+                Backend::Wtype,
+                Backend::Ydotool,
+                Backend::Enigo,
+                Backend::Paste,
+            ],
+            DisplayServer::X11 => &[
+                Backend::Xdotool,
+                Backend::Enigo,
+                Backend::Ydotool,
+                Backend::Paste,
+            ],
+        }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The first backend in priority order that `available` accepts
+fn auto_detect(display: DisplayServer, available: impl Fn(Backend) -> bool) -> Option<Backend> {
+    Backend::priority(display)
+        .iter()
+        .copied()
+        .find(|&backend| available(backend))
+}
+
+/// The `output.injector` backend, or the first one available
+pub fn select(config: &OutputConfig) -> Result<Box<dyn Injector>> {
+    let server = DisplayServer::detect(config.display_server.as_deref());
+
+    if let Some(backend) = config.injector {
+        let injector = backend.injector(server, &config.paste);
+        // Checks can be wrong about unusual setups; the configured backend still gets a try
+        if let Err(e) = injector.check(server) {
+            warn!("output.injector = \"{}\" may not work: {:#}", backend, e);
+        }
+        return Ok(injector);
+    }
+
+    let backend = auto_detect(server, |backend| {
+        match backend.injector(server, &config.paste).check(server) {
+            Ok(()) => true,
+            Err(e) => {
+                debug!("Injector {} unavailable: {:#}", backend, e);
+                false
+            },
+        }
+    })
+    .context("No text injection backend available (see mojovoice doctor)")?;
+    debug!("Injector: {} ({:?})", backend, server);
+    Ok(backend.injector(server, &config.paste))
+}
+
+/// Every backend with its availability on `display`, for `doctor`
+pub fn availability(display: DisplayServer, paste: &PasteConfig) -> Vec<(Backend, Result<()>)> {
+    Backend::ALL
+        .iter()
+        .map(|&backend| (backend, backend.injector(display, paste).check(display)))
+        .collect()
+}
+
+/// Full path of `program` on PATH
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
+
+fn require(program: &str) -> Result<()> {
+    find_in_path(program)
+        .map(|_| ())
+        .with_context(|| format!("{} not found in PATH", program))
+}
+
+/// Run `program`, feeding `input` on stdin
+fn run(program: &str, args: &[&str], input: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to spawn {}", program))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("{} exited with status: {}", program, status);
+    }
+    Ok(())
+}
+
+struct EnigoInjector;
+
+impl Injector for EnigoInjector {
+    fn backend(&self) -> Backend {
+        Backend::Enigo
+    }
+
+    fn check(&self, _display: DisplayServer) -> Result<()> {
+        Ok(())
+    }
+
+    fn type_text(&self, text: &str) -> Result<()> {
+        let mut enigo = Enigo::new(&Settings::default()).context("Failed to initialize enigo")?;
+        enigo.text(text).context("Failed to type text")?;
+        Ok(())
+    }
+}
+
+struct Wtype;
+
+impl Injector for Wtype {
+    fn backend(&self) -> Backend {
+        Backend::Wtype
+    }
+
+    fn check(&self, display: DisplayServer) -> Result<()> {
+        if display != DisplayServer::Wayland {
+            anyhow::bail!("needs Wayland");
+        }
+        require("wtype")
+    }
+
+    fn type_text(&self, text: &str) -> Result<()> {
+        // "-" reads the text from stdin
+        run("wtype", &["-"], text)
+    }
+}
+
+struct Ydotool;
+
+impl Ydotool {
+    /// Where ydotoold listens: $YDOTOOL_SOCKET, or its defaults
+    fn sockets() -> Vec<PathBuf> {
+        if let Some(socket) = std::env::var_os("YDOTOOL_SOCKET") {
+            return vec![socket.into()];
+        }
+        std::env::var_os("XDG_RUNTIME_DIR")
+            .map(|dir| PathBuf::from(dir).join(".ydotool_socket"))
+            .into_iter()
+            .chain([PathBuf::from("/tmp/.ydotool_socket")])
+            .collect()
+    }
+}
+
+impl Injector for Ydotool {
+    fn backend(&self) -> Backend {
+        Backend::Ydotool
+    }
+
+    fn check(&self, _display: DisplayServer) -> Result<()> {
+        require("ydotool")?;
+        if !Self::sockets().iter().any(|socket| socket.exists()) {
+            anyhow::bail!("ydotoold is not running (no socket found)");
+        }
+        Ok(())
+    }
+
+    fn type_text(&self, text: &str) -> Result<()> {
+        run("ydotool", &["type", "--file", "-"], text)
+    }
+}
+
+struct Xdotool;
+
+impl Injector for Xdotool {
+    fn backend(&self) -> Backend {
+        Backend::Xdotool
+    }
+
+    fn check(&self, display: DisplayServer) -> Result<()> {
+        // Under Wayland it only reaches XWayland windows
+        if display != DisplayServer::X11 {
+            anyhow::bail!("needs X11");
+        }
+        require("xdotool")
+    }
+
+    fn type_text(&self, text: &str) -> Result<()> {
+        run(
+            "xdotool",
+            &["type", "--clearmodifiers", "--file", "-"],
+            text,
+        )
+    }
+}
+
+/// Key combination that pastes in the focused application
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteShortcut {
+    /// Ctrl+V (Cmd+V on macOS)
+    #[default]
+    CtrlV,
+    /// Ctrl+Shift+V, for terminals
+    CtrlShiftV,
+}
+
+/// Paste settings (`[output.paste]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PasteConfig {
+    /// Overridable per output profile
+    #[serde(default)]
+    pub shortcut: PasteShortcut,
+    /// Put back what was on the clipboard after pasting
+    #[serde(default = "default_restore_clipboard")]
+    pub restore_clipboard: bool,
+    /// How long the application gets to read the dictation before the restore
+    #[serde(default = "default_restore_delay_ms")]
+    pub restore_delay_ms: u64,
+}
+
+fn default_restore_clipboard() -> bool {
+    true
+}
+
+fn default_restore_delay_ms() -> u64 {
+    300
+}
+
+impl Default for PasteConfig {
+    fn default() -> Self {
+        Self {
+            shortcut: PasteShortcut::default(),
+            restore_clipboard: default_restore_clipboard(),
+            restore_delay_ms: default_restore_delay_ms(),
+        }
+    }
+}
+
+/// Sets the clipboard, presses the paste shortcut and puts the clipboard back
+struct Paste {
+    display: DisplayServer,
+    config: PasteConfig,
+}
+
+impl Paste {
+    /// Press the shortcut with the key tool for the display server, or enigo
+    fn press_shortcut(&self) -> Result<()> {
+        let shift = self.config.shortcut == PasteShortcut::CtrlShiftV;
+
+        if Wtype.check(self.display).is_ok() {
+            let args: &[&str] = if shift {
+                &[
+                    "-M", "ctrl", "-M", "shift", "-k", "v", "-m", "shift", "-m", "ctrl",
+                ]
+            } else {
+                &["-M", "ctrl", "-k", "v", "-m", "ctrl"]
+            };
+            return run("wtype", args, "");
+        }
+        if Xdotool.check(self.display).is_ok() {
+            let keys = if shift { "ctrl+shift+v" } else { "ctrl+v" };
+            return run("xdotool", &["key", "--clearmodifiers", keys], "");
+        }
+        if Ydotool.check(self.display).is_ok() {
+            // Linux keycodes: 29 left Ctrl, 42 left Shift, 47 V
+            let args: &[&str] = if shift {
+                &["key", "29:1", "42:1", "47:1", "47:0", "42:0", "29:0"]
+            } else {
+                &["key", "29:1", "47:1", "47:0", "29:0"]
+            };
+            return run("ydotool", args, "");
+        }
+
+        let mut modifiers = vec![if cfg!(target_os = "macos") {
+            Key::Meta
+        } else {
+            Key::Control
+        }];
+        if shift {
+            modifiers.push(Key::Shift);
+        }
+        let mut enigo = Enigo::new(&Settings::default()).context("Failed to initialize enigo")?;
+        let mut press = || -> enigo::InputResult<()> {
+            for &modifier in &modifiers {
+                enigo.key(modifier, Direction::Press)?;
+            }
+            enigo.key(Key::Unicode('v'), Direction::Click)?;
+            for &modifier in modifiers.iter().rev() {
+                enigo.key(modifier, Direction::Release)?;
+            }
+            Ok(())
+        };
+        press().context("Failed to press the paste shortcut")
+    }
+}
+
+impl Injector for Paste {
+    fn backend(&self) -> Backend {
+        Backend::Paste
+    }
+
+    fn check(&self, display: DisplayServer) -> Result<()> {
+        if cfg!(target_os = "linux") {
+            require(super::clipboard_command(display).0)?;
+            if display == DisplayServer::Wayland && self.config.restore_clipboard {
+                require("wl-paste")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn type_text(&self, text: &str) -> Result<()> {
+        let saved = if self.config.restore_clipboard {
+            match clipboard::save(self.display) {
+                Ok(saved) => Some(saved),
+                Err(e) => {
+                    warn!("Clipboard not saved, it will keep the dictation: {:#}", e);
+                    None
+                },
+            }
+        } else {
+            None
+        };
+
+        super::copy_to_clipboard(text, self.display)?;
+        // Let the clipboard owner settle before the target asks for the contents
+        std::thread::sleep(Duration::from_millis(50));
+        let pasted = self.press_shortcut();
+
+        if let Some(saved) = saved {
+            std::thread::sleep(Duration::from_millis(self.config.restore_delay_ms));
+            // Something copied in the meantime is newer than the saved contents
+            let restored = clipboard::holds_text(text, self.display).and_then(|holds| {
+                if holds {
+                    clipboard::restore(saved.as_ref(), self.display)?;
+                }
+                Ok(holds)
+            });
+            match restored {
+                Ok(true) => debug!("Clipboard restored"),
+                Ok(false) => debug!("Clipboard changed since the paste, not restoring it"),
+                Err(e) => warn!("Failed to restore the clipboard: {:#}", e),
+            }
+        }
+        pasted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_detect_follows_priority() {
+        let all = |_: Backend| true;
+        assert_eq!(
+            auto_detect(DisplayServer::Wayland, all),
+            Some(Backend::Wtype)
+        );
+        assert_eq!(auto_detect(DisplayServer::X11, all), Some(Backend::Xdotool));
+
+        let no_tools = |backend: Backend| matches!(backend, Backend::Enigo | Backend::Paste);
+        assert_eq!(
+            auto_detect(DisplayServer::Wayland, no_tools),
+            Some(Backend::Enigo)
+        );
+        assert_eq!(auto_detect(DisplayServer::X11, |_| false), None);
+    }
+
+    #[test]
+    fn test_backend_names() {
+        for backend in Backend::ALL {
+            let json = serde_json::to_string(&backend).unwrap();
+            assert_eq!(json, format!("\"{}\"", backend.name()));
+            let injector = backend.injector(DisplayServer::Wayland, &PasteConfig::default());
+            assert_eq!(injector.backend(), backend);
+        }
+    }
+
+    #[test]
+    fn test_display_server_checks() {
+        assert!(Wtype.check(DisplayServer::X11).is_err());
+        assert!(Xdotool.check(DisplayServer::Wayland).is_err());
+        assert!(EnigoInjector.check(DisplayServer::X11).is_ok());
+    }
+
+    #[test]
+    fn test_paste_config_from_toml() {
+        let config: PasteConfig = toml::from_str(r#"shortcut = "ctrl_shift_v""#).unwrap();
+        assert_eq!(config.shortcut, PasteShortcut::CtrlShiftV);
+        assert!(config.restore_clipboard);
+        assert_eq!(config.restore_delay_ms, 300);
+    }
+
+    #[test]
+    fn test_find_in_path() {
+        assert!(find_in_path("sh").is_some());
+        assert!(find_in_path("mojovoice-no-such-program").is_none());
+    }
+}
@@ -1,16 +1,27 @@
+pub mod clipboard;
+pub mod injector;
+pub mod profile;
+pub mod transcript;
+pub mod window;
+
 use anyhow::{Context, Result};
 #[cfg(not(target_os = "linux"))]
 use arboard::Clipboard;
-use enigo::{Enigo, Keyboard, Settings};
+use serde::{Deserialize, Serialize};
 #[cfg(test)]
 use std::str::FromStr;
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::config::OutputConfig;
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputMode {
     #[default]
     Type,
     Clipboard,
+    /// Paste through the clipboard, then restore it (`[output.paste]`)
+    Paste,
 }
 
 #[cfg(test)]
@@ -21,64 +32,169 @@ impl FromStr for OutputMode {
         match s.to_lowercase().as_str() {
             "type" | "inject" => Ok(Self::Type),
             "clipboard" | "copy" => Ok(Self::Clipboard),
+            "paste" => Ok(Self::Paste),
             _ => Err(format!("Unknown output mode: {}", s)),
         }
     }
 }
 
-pub fn inject_text(text: &str, mode: OutputMode) -> Result<()> {
+/// Display server that output goes through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayServer {
+    Wayland,
+    X11,
+}
+
+impl DisplayServer {
+    /// `output.display_server` ("wayland" or "x11"), or the session's when unset
+    pub fn detect(configured: Option<&str>) -> Self {
+        match configured.map(str::to_lowercase).as_deref() {
+            Some("wayland") => DisplayServer::Wayland,
+            Some("x11") => DisplayServer::X11,
+            configured => {
+                if let Some(other) = configured {
+                    warn!("Unknown output.display_server \"{}\", detecting", other);
+                }
+                if std::env::var("WAYLAND_DISPLAY").is_ok() {
+                    DisplayServer::Wayland
+                } else {
+                    DisplayServer::X11
+                }
+            },
+        }
+    }
+}
+
+/// Send a desktop notification (best effort, via notify-send)
+pub fn send_notification(title: &str, body: &str, urgency: &str) {
+    let _ = std::process::Command::new("notify-send")
+        .args([
+            "-a",
+            "mojovoice",
+            "-i",
+            "audio-input-microphone",
+            "-u",
+            urgency,
+            title,
+            body,
+        ])
+        .spawn();
+}
+
+pub fn inject_text(text: &str, mode: OutputMode, config: &OutputConfig) -> Result<()> {
     if text.is_empty() {
         return Ok(());
     }
 
+    let display = DisplayServer::detect(config.display_server.as_deref());
     match mode {
         OutputMode::Clipboard => {
-            copy_to_clipboard(text)?;
+            copy_to_clipboard(text, display)?;
             info!("Copied to clipboard: {} chars", text.len());
         },
+        OutputMode::Paste => {
+            injector::Backend::Paste
+                .injector(display, &config.paste)
+                .type_text(text)?;
+            info!("Pasted {} chars at cursor", text.len());
+        },
         OutputMode::Type => {
-            type_text(text)?;
-            info!("Typed {} chars at cursor", text.len());
+            let injector = injector::select(config)?;
+            injector.type_text(text)?;
+            info!(
+                "Typed {} chars at cursor via {}",
+                text.len(),
+                injector.backend()
+            );
         },
     }
     Ok(())
 }
 
-fn copy_to_clipboard(text: &str) -> Result<()> {
+/// Output a dictation into `window` with the mode, trailing space and paste shortcut of
+/// its profile. `clipboard` (an explicit `--clipboard`) always copies. Returns the mode
+/// used.
+pub fn inject_dictation(
+    text: &str,
+    clipboard: bool,
+    window: Option<&window::FocusedWindow>,
+    config: &OutputConfig,
+) -> Result<OutputMode> {
+    let profile = window.and_then(|w| profile::select(&config.profiles, w));
+    let mode = if clipboard {
+        OutputMode::Clipboard
+    } else {
+        profile.and_then(|p| p.mode).unwrap_or_default()
+    };
+    let append_space = profile
+        .and_then(|p| p.append_space)
+        .unwrap_or(config.append_space);
+    let mut config = config.clone();
+    if let Some(shortcut) = profile.and_then(|p| p.paste_shortcut) {
+        config.paste.shortcut = shortcut;
+    }
+
+    if append_space && !text.is_empty() {
+        inject_text(&format!("{} ", text), mode, &config)?;
+    } else {
+        inject_text(text, mode, &config)?;
+    }
+    Ok(mode)
+}
+
+/// Clipboard tool for `display`: command, arguments to set the clipboard from stdin
+/// and the package providing it
+fn clipboard_command(
+    display: DisplayServer,
+) -> (&'static str, &'static [&'static str], &'static str) {
+    match display {
+        DisplayServer::Wayland => ("wl-copy", &[], "wl-clipboard"),
+        DisplayServer::X11 => ("xclip", &["-selection", "clipboard"], "xclip"),
+    }
+}
+
+/// Set the clipboard to `data` with wl-copy or xclip, offered as `mime` or as text
+#[cfg(target_os = "linux")]
+fn write_clipboard(display: DisplayServer, mime: Option<&str>, data: &[u8]) -> Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let (cmd, args, install_hint) = clipboard_command(display);
+    let type_flag = match display {
+        DisplayServer::Wayland => "--type",
+        DisplayServer::X11 => "-t",
+    };
+
+    let mut child = Command::new(cmd)
+        .args(args)
+        .args(mime.map(|mime| [type_flag, mime]).into_iter().flatten())
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "Failed to spawn {}. Install with: sudo dnf install {}",
+                cmd, install_hint
+            )
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(data)?;
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("{} exited with status: {}", cmd, status);
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn copy_to_clipboard(text: &str, display: DisplayServer) -> Result<()> {
     #[cfg(target_os = "linux")]
     {
         // Use wl-copy (Wayland) or xclip (X11) - arboard has Wayland issues
-        use std::io::Write;
-        use std::process::{Command, Stdio};
-
-        let is_wayland = std::env::var("WAYLAND_DISPLAY").is_ok();
-        let (cmd, args, install_hint) = if is_wayland {
-            ("wl-copy", vec![], "wl-clipboard")
-        } else {
-            ("xclip", vec!["-selection", "clipboard"], "xclip")
-        };
-
-        let mut child = Command::new(cmd)
-            .args(&args)
-            .stdin(Stdio::piped())
-            .spawn()
-            .with_context(|| {
-                format!(
-                    "Failed to spawn {}. Install with: sudo dnf install {}",
-                    cmd, install_hint
-                )
-            })?;
-
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin.write_all(text.as_bytes())?;
-        }
-
-        let status = child.wait()?;
-        if !status.success() {
-            anyhow::bail!("{} exited with status: {}", cmd, status);
-        }
-
-        Ok(())
+        write_clipboard(display, None, text.as_bytes())
     }
 
     #[cfg(not(target_os = "linux"))]
@@ -91,12 +207,6 @@ fn copy_to_clipboard(text: &str) -> Result<()> {
     }
 }
 
-fn type_text(text: &str) -> Result<()> {
-    let mut enigo = Enigo::new(&Settings::default()).context("Failed to initialize enigo")?;
-    enigo.text(text).context("Failed to type text")?;
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,12 +216,26 @@ mod tests {
         assert_eq!(OutputMode::from_str("type"), Ok(OutputMode::Type));
         assert_eq!(OutputMode::from_str("clipboard"), Ok(OutputMode::Clipboard));
         assert_eq!(OutputMode::from_str("copy"), Ok(OutputMode::Clipboard));
+        assert_eq!(OutputMode::from_str("paste"), Ok(OutputMode::Paste));
         assert!(OutputMode::from_str("invalid").is_err());
     }
 
     #[test]
     fn test_empty_text() {
-        let result = inject_text("", OutputMode::Type);
+        let result = inject_text(
+            "",
+            OutputMode::Type,
+            &crate::config::Config::default().output,
+        );
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_configured_display_server() {
+        assert_eq!(DisplayServer::detect(Some("X11")), DisplayServer::X11);
+        assert_eq!(
+            DisplayServer::detect(Some("wayland")),
+            DisplayServer::Wayland
+        );
+    }
 }
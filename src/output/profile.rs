@@ -0,0 +1,179 @@
+//! Per-application output profiles
+//!
+//! `[[output.profiles]]` entries match the focused window by class and/or title
+//! (case-insensitive regular expressions) and change how a dictation is processed
+//! and output there: a terminal can skip capitalization and the trailing space, an
+//! editor can get code identifiers. The first matching profile wins; one without
+//! `class` or `title` matches every window.
+
+use anyhow::{Context, Result};
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::OutputMode;
+use super::injector::PasteShortcut;
+use super::window::FocusedWindow;
+use crate::postprocess::identifier::IdentifierCase;
+use crate::postprocess::{Casing, Step};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputProfile {
+    /// Shown in logs and `config --check`
+    pub name: String,
+    /// Pattern for the window class (Wayland app_id or X11 WM_CLASS)
+    pub class: Option<String>,
+    /// Pattern for the window title
+    pub title: Option<String>,
+    /// "type", "clipboard" or "paste"; an explicit `--clipboard` still copies
+    pub mode: Option<OutputMode>,
+    /// Overrides `output.paste.shortcut`, e.g. "ctrl_shift_v" in terminals
+    pub paste_shortcut: Option<PasteShortcut>,
+    /// Overrides `output.append_space`
+    pub append_space: Option<bool>,
+    /// Post-processing steps used instead of `postprocess.steps`
+    pub steps: Option<Vec<Step>>,
+    /// Case forced after the steps: "sentence", "lower_first" or "lower"
+    pub casing: Option<Casing>,
+    /// Format whole dictations as code identifiers, as `start --code`
+    pub code: Option<IdentifierCase>,
+}
+
+impl OutputProfile {
+    /// Whether both patterns match `window`; fails on an invalid pattern
+    pub fn matches(&self, window: &FocusedWindow) -> Result<bool> {
+        let is_match = |pattern: &Option<String>, value: &str| -> Result<bool> {
+            let Some(pattern) = pattern else {
+                return Ok(true);
+            };
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .with_context(|| {
+                    format!(
+                        "Invalid pattern /{}/ in output profile '{}'",
+                        pattern, self.name
+                    )
+                })?;
+            Ok(regex.is_match(value))
+        };
+        Ok(is_match(&self.class, &window.class)? && is_match(&self.title, &window.title)?)
+    }
+
+    /// Check the patterns, for `config --check`
+    pub fn validate(&self) -> Result<()> {
+        self.matches(&FocusedWindow::default()).map(|_| ())
+    }
+}
+
+/// The first profile matching `window`; profiles with an invalid pattern are skipped
+pub fn select<'a>(
+    profiles: &'a [OutputProfile],
+    window: &FocusedWindow,
+) -> Option<&'a OutputProfile> {
+    profiles
+        .iter()
+        .find(|profile| match profile.matches(window) {
+            Ok(matches) => matches,
+            Err(e) => {
+                warn!("{:#}", e);
+                false
+            },
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(class: &str, title: &str) -> FocusedWindow {
+        FocusedWindow {
+            class: class.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    fn profiles() -> Vec<OutputProfile> {
+        #[derive(Deserialize)]
+        struct Section {
+            profiles: Vec<OutputProfile>,
+        }
+        toml::from_str::<Section>(
+            r#"
+            [[profiles]]
+            name = "broken"
+            class = "("
+
+            [[profiles]]
+            name = "terminal"
+            class = "^(kitty|alacritty|foot)$"
+            mode = "paste"
+            paste_shortcut = "ctrl_shift_v"
+            append_space = false
+            casing = "lower_first"
+            steps = [{ type = "trim" }]
+
+            [[profiles]]
+            name = "slack"
+            title = "slack"
+            mode = "clipboard"
+            casing = "sentence"
+
+            [[profiles]]
+            name = "editor"
+            class = "code"
+            title = "\\.rs\\b"
+            code = "snake"
+            "#,
+        )
+        .unwrap()
+        .profiles
+    }
+
+    #[test]
+    fn test_profiles_from_toml() {
+        let profiles = profiles();
+        assert_eq!(profiles[1].append_space, Some(false));
+        assert_eq!(profiles[1].casing, Some(Casing::LowerFirst));
+        assert_eq!(profiles[1].steps, Some(vec![Step::Trim]));
+        assert_eq!(profiles[1].mode, Some(OutputMode::Paste));
+        assert_eq!(profiles[1].paste_shortcut, Some(PasteShortcut::CtrlShiftV));
+        assert_eq!(profiles[2].mode, Some(OutputMode::Clipboard));
+        assert_eq!(profiles[3].code, Some(IdentifierCase::Snake));
+        assert_eq!(profiles[3].mode, None);
+    }
+
+    #[test]
+    fn test_select() {
+        let profiles = profiles();
+        let name = |class: &str, title: &str| {
+            select(&profiles, &window(class, title)).map(|p| p.name.as_str())
+        };
+        assert_eq!(name("kitty", "~"), Some("terminal"));
+        assert_eq!(name("Alacritty", "vim"), Some("terminal"));
+        assert_eq!(name("kitty-like", "~"), None);
+        assert_eq!(name("firefox", "general | Slack"), Some("slack"));
+        assert_eq!(name("Code", "main.rs - mojovoice"), Some("editor"));
+        assert_eq!(name("Code", "README.md - mojovoice"), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        let profiles = profiles();
+        assert!(profiles[0].validate().is_err());
+        assert!(profiles[1..].iter().all(|p| p.validate().is_ok()));
+
+        let catch_all = OutputProfile {
+            name: "default".to_string(),
+            class: None,
+            title: None,
+            mode: None,
+            paste_shortcut: None,
+            append_space: None,
+            steps: None,
+            casing: None,
+            code: None,
+        };
+        assert!(catch_all.matches(&window("anything", "")).unwrap());
+    }
+}
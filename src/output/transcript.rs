@@ -0,0 +1,335 @@
+//! Transcript rendering for file transcription: plain text, JSON and SRT/WebVTT captions
+
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::transcribe::Segment;
+
+/// Transcript output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Txt,
+    Json,
+    Srt,
+    Vtt,
+}
+
+impl OutputFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Txt => "txt",
+            OutputFormat::Json => "json",
+            OutputFormat::Srt => "srt",
+            OutputFormat::Vtt => "vtt",
+        }
+    }
+
+    /// Infer the format from an output file extension (e.g. `demo.srt`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "txt" => Some(OutputFormat::Txt),
+            "json" => Some(OutputFormat::Json),
+            "srt" => Some(OutputFormat::Srt),
+            "vtt" => Some(OutputFormat::Vtt),
+            _ => None,
+        }
+    }
+
+    /// Whether rendering this format requires segment timing from the daemon
+    pub fn needs_segments(self) -> bool {
+        self != OutputFormat::Txt
+    }
+}
+
+/// Caption layout rules
+#[derive(Debug, Clone, Copy)]
+pub struct CaptionRules {
+    /// Maximum characters per caption line
+    pub max_line_chars: usize,
+    /// Maximum lines per cue
+    pub max_lines: usize,
+    /// Maximum time a single cue stays on screen
+    pub max_cue_secs: f64,
+}
+
+impl Default for CaptionRules {
+    fn default() -> Self {
+        // Common broadcast guidelines: 42 chars x 2 lines, at most ~6 seconds per cue
+        Self {
+            max_line_chars: 42,
+            max_lines: 2,
+            max_cue_secs: 6.0,
+        }
+    }
+}
+
+/// One on-screen caption
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start_secs: f64,
+    pub end_secs: f64,
+    pub lines: Vec<String>,
+}
+
+/// JSON transcript contents
+#[derive(Debug, Serialize)]
+struct TranscriptJson<'a> {
+    source: &'a Path,
+    text: &'a str,
+    model: &'a str,
+    created_at: String,
+    segments: &'a [Segment],
+}
+
+/// Render a transcript in the given format
+pub fn render(
+    format: OutputFormat,
+    text: &str,
+    segments: &[Segment],
+    source: &Path,
+    model: &str,
+) -> Result<String> {
+    let rules = CaptionRules::default();
+    Ok(match format {
+        OutputFormat::Txt => format!("{}\n", text),
+        OutputFormat::Json => {
+            let json = TranscriptJson {
+                source,
+                text,
+                model,
+                created_at: chrono::Local::now().to_rfc3339(),
+                segments,
+            };
+            serde_json::to_string_pretty(&json)? + "\n"
+        },
+        OutputFormat::Srt => render_srt(&build_cues(segments, &rules)),
+        OutputFormat::Vtt => render_vtt(&build_cues(segments, &rules)),
+    })
+}
+
+/// Split segments into cues that respect line length, line count and duration limits.
+/// Time within a segment is shared between its cues in proportion to their text length.
+pub fn build_cues(segments: &[Segment], rules: &CaptionRules) -> Vec<Cue> {
+    let mut cues = Vec::new();
+
+    for segment in segments {
+        let words: Vec<&str> = segment.text.split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+
+        let total_chars = words.iter().map(|w| w.chars().count() + 1).sum::<usize>();
+        let duration = (segment.end_secs - segment.start_secs).max(0.0);
+        let cue_chars = rules.max_line_chars * rules.max_lines;
+
+        let mut count = (total_chars.div_ceil(cue_chars))
+            .max((duration / rules.max_cue_secs).ceil() as usize)
+            .clamp(1, words.len());
+
+        // Grow the cue count until every group wraps within max_lines
+        let groups = loop {
+            let groups = split_evenly(&words, count);
+            let fits = groups
+                .iter()
+                .all(|g| wrap_words(g, rules.max_line_chars).len() <= rules.max_lines);
+            if fits || count >= words.len() {
+                break groups;
+            }
+            count += 1;
+        };
+
+        let mut consumed = 0;
+        for group in groups {
+            let chars = group.iter().map(|w| w.chars().count() + 1).sum::<usize>();
+            let start = segment.start_secs + duration * consumed as f64 / total_chars as f64;
+            consumed += chars;
+            let end = segment.start_secs + duration * consumed as f64 / total_chars as f64;
+            cues.push(Cue {
+                start_secs: start,
+                end_secs: end,
+                lines: wrap_words(&group, rules.max_line_chars),
+            });
+        }
+    }
+
+    cues
+}
+
+/// Split words into `count` consecutive groups of roughly equal character length
+fn split_evenly<'a>(words: &[&'a str], count: usize) -> Vec<Vec<&'a str>> {
+    let total = words.iter().map(|w| w.chars().count() + 1).sum::<usize>() as f64;
+    let mut groups: Vec<Vec<&str>> = vec![Vec::new(); count];
+    let mut cumulative = 0.0;
+
+    for word in words {
+        let len = (word.chars().count() + 1) as f64;
+        // Assign each word by the position of its midpoint
+        let idx = (((cumulative + len / 2.0) / total) * count as f64) as usize;
+        groups[idx.min(count - 1)].push(word);
+        cumulative += len;
+    }
+
+    groups.retain(|g| !g.is_empty());
+    groups
+}
+
+/// Greedy word wrap; a single word longer than the limit gets its own line
+fn wrap_words(words: &[&str], max_chars: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in words {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= max_chars => {
+                line.push(' ');
+                line.push_str(word);
+            },
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+/// Format seconds as `HH:MM:SS<sep>mmm`
+fn format_timestamp(secs: f64, millis_separator: char) -> String {
+    let total_ms = (secs.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        total_ms / 3_600_000,
+        (total_ms / 60_000) % 60,
+        (total_ms / 1000) % 60,
+        millis_separator,
+        total_ms % 1000
+    )
+}
+
+/// Render cues as SubRip (.srt)
+pub fn render_srt(cues: &[Cue]) -> String {
+    let mut out = String::new();
+    for (i, cue) in cues.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_timestamp(cue.start_secs, ','),
+            format_timestamp(cue.end_secs, ','),
+            cue.lines.join("\n")
+        ));
+    }
+    out
+}
+
+/// Render cues as WebVTT (.vtt)
+pub fn render_vtt(cues: &[Cue]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in cues {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(cue.start_secs, '.'),
+            format_timestamp(cue.end_secs, '.'),
+            cue.lines.join("\n")
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start: f64, end: f64, text: &str) -> Segment {
+        Segment {
+            start_secs: start,
+            end_secs: end,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0.0, ','), "00:00:00,000");
+        assert_eq!(format_timestamp(3725.5, ','), "01:02:05,500");
+        assert_eq!(format_timestamp(61.0429, '.'), "00:01:01.043");
+    }
+
+    #[test]
+    fn test_short_segment_is_one_cue() {
+        let cues = build_cues(
+            &[segment(1.0, 3.0, "Hello world")],
+            &CaptionRules::default(),
+        );
+        assert_eq!(
+            cues,
+            vec![Cue {
+                start_secs: 1.0,
+                end_secs: 3.0,
+                lines: vec!["Hello world".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_long_text_wraps_and_splits() {
+        let text = "The quick brown fox jumps over the lazy dog while the \
+                    release pipeline finishes building every artifact for the demo video";
+        let rules = CaptionRules::default();
+        let cues = build_cues(&[segment(0.0, 5.0, text)], &rules);
+
+        assert!(cues.len() >= 2, "{:?}", cues);
+        for cue in &cues {
+            assert!(cue.lines.len() <= rules.max_lines);
+            assert!(
+                cue.lines
+                    .iter()
+                    .all(|l| l.chars().count() <= rules.max_line_chars)
+            );
+        }
+        // Cues tile the segment without gaps
+        assert_eq!(cues[0].start_secs, 0.0);
+        assert!((cues.last().unwrap().end_secs - 5.0).abs() < 1e-9);
+        for pair in cues.windows(2) {
+            assert!((pair[0].end_secs - pair[1].start_secs).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_max_cue_duration_splits_slow_speech() {
+        let cues = build_cues(
+            &[segment(
+                0.0,
+                20.0,
+                "so um let me think about that for a moment",
+            )],
+            &CaptionRules::default(),
+        );
+        assert!(cues.len() >= 4, "{:?}", cues);
+    }
+
+    #[test]
+    fn test_render_srt() {
+        let cues = build_cues(
+            &[segment(0.0, 1.5, "First"), segment(2.0, 3.0, "Second")],
+            &CaptionRules::default(),
+        );
+        assert_eq!(
+            render_srt(&cues),
+            "1\n00:00:00,000 --> 00:00:01,500\nFirst\n\n2\n00:00:02,000 --> 00:00:03,000\nSecond\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_vtt() {
+        let cues = build_cues(&[segment(0.25, 1.0, "Hi")], &CaptionRules::default());
+        assert_eq!(
+            render_vtt(&cues),
+            "WEBVTT\n\n00:00:00.250 --> 00:00:01.000\nHi\n\n"
+        );
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            OutputFormat::from_path(Path::new("demo.SRT")),
+            Some(OutputFormat::Srt)
+        );
+        assert_eq!(OutputFormat::from_path(Path::new("demo.mp4")), None);
+    }
+}
@@ -0,0 +1,232 @@
+//! Focused window lookup for output profiles
+//!
+//! Asks the compositor over its IPC socket (Hyprland, Sway) or the X server through
+//! `xprop`. Other desktops have no focused window and use the default output.
+
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+use tracing::debug;
+
+const IPC_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The window a dictation goes to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FocusedWindow {
+    /// Application class (Wayland app_id or X11 WM_CLASS), e.g. "kitty"
+    pub class: String,
+    pub title: String,
+}
+
+/// The focused window, if the desktop can tell
+pub fn focused_window() -> Option<FocusedWindow> {
+    let window = if let Ok(signature) = std::env::var("HYPRLAND_INSTANCE_SIGNATURE") {
+        hyprland(&signature)
+    } else if let Ok(socket) = std::env::var("SWAYSOCK") {
+        sway(&socket)
+    } else if std::env::var("WAYLAND_DISPLAY").is_err() && std::env::var("DISPLAY").is_ok() {
+        x11()
+    } else {
+        None
+    };
+    debug!("Focused window: {:?}", window);
+    window
+}
+
+fn hyprland(signature: &str) -> Option<FocusedWindow> {
+    let runtime = std::env::var("XDG_RUNTIME_DIR").map(PathBuf::from).ok();
+    // Hyprland moved its sockets from /tmp/hypr to the runtime dir in 0.40
+    let socket = runtime
+        .into_iter()
+        .chain([PathBuf::from("/tmp")])
+        .map(|dir| dir.join("hypr").join(signature).join(".socket.sock"))
+        .find(|path| path.exists())?;
+
+    let mut stream = UnixStream::connect(socket).ok()?;
+    stream.set_read_timeout(Some(IPC_TIMEOUT)).ok()?;
+    stream.write_all(b"j/activewindow").ok()?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).ok()?;
+    parse_hyprland(&reply)
+}
+
+/// `activewindow` reply (`{}` when nothing is focused)
+fn parse_hyprland(reply: &str) -> Option<FocusedWindow> {
+    let window: serde_json::Value = serde_json::from_str(reply).ok()?;
+    Some(FocusedWindow {
+        class: window.get("class")?.as_str()?.to_string(),
+        title: window
+            .get("title")?
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+fn sway(socket: &str) -> Option<FocusedWindow> {
+    const MAGIC: &[u8] = b"i3-ipc";
+    const GET_TREE: u32 = 4;
+
+    let mut stream = UnixStream::connect(socket).ok()?;
+    stream.set_read_timeout(Some(IPC_TIMEOUT)).ok()?;
+    let mut request = MAGIC.to_vec();
+    request.extend_from_slice(&0u32.to_ne_bytes());
+    request.extend_from_slice(&GET_TREE.to_ne_bytes());
+    stream.write_all(&request).ok()?;
+
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header).ok()?;
+    if &header[..6] != MAGIC {
+        return None;
+    }
+    let length = u32::from_ne_bytes(header[6..10].try_into().ok()?) as usize;
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload).ok()?;
+    parse_sway_tree(&String::from_utf8_lossy(&payload))
+}
+
+/// `GET_TREE` reply: the node with `focused: true`
+fn parse_sway_tree(tree: &str) -> Option<FocusedWindow> {
+    fn find(node: &serde_json::Value) -> Option<&serde_json::Value> {
+        if node.get("focused").and_then(|f| f.as_bool()) == Some(true) {
+            return Some(node);
+        }
+        ["nodes", "floating_nodes"]
+            .iter()
+            .filter_map(|key| node.get(key)?.as_array())
+            .flatten()
+            .find_map(find)
+    }
+
+    let tree: serde_json::Value = serde_json::from_str(tree).ok()?;
+    let node = find(&tree)?;
+    // Workspaces and outputs can be focused too; only views have a pid
+    node.get("pid")?;
+    let class = node
+        .get("app_id")
+        .and_then(|id| id.as_str())
+        .or_else(|| node.pointer("/window_properties/class")?.as_str())?;
+    Some(FocusedWindow {
+        class: class.to_string(),
+        title: node
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+fn x11() -> Option<FocusedWindow> {
+    let xprop = |args: &[&str]| -> Option<String> {
+        let output = Command::new("xprop").args(args).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    // "_NET_ACTIVE_WINDOW: window id # 0x3a00007"
+    let active = xprop(&["-root", "_NET_ACTIVE_WINDOW"])?;
+    let id = active.split_whitespace().last()?;
+    if id == "0x0" {
+        return None;
+    }
+    parse_xprop(&xprop(&[
+        "-id",
+        id,
+        "-notype",
+        "WM_CLASS",
+        "_NET_WM_NAME",
+        "WM_NAME",
+    ])?)
+}
+
+/// `xprop -notype WM_CLASS _NET_WM_NAME WM_NAME` output
+fn parse_xprop(output: &str) -> Option<FocusedWindow> {
+    let mut class = None;
+    let mut title = None;
+    for line in output.lines() {
+        let Some((name, value)) = line.split_once(" = ") else {
+            continue;
+        };
+        // Quoted strings, e.g. `"code", "Code"`
+        let strings: Vec<&str> = value.split('"').skip(1).step_by(2).collect();
+        match name.trim() {
+            // Instance name, then class
+            "WM_CLASS" => class = strings.last().map(|s| s.to_string()),
+            "_NET_WM_NAME" => title = strings.first().map(|s| s.to_string()),
+            "WM_NAME" if title.is_none() => title = strings.first().map(|s| s.to_string()),
+            _ => {},
+        }
+    }
+    Some(FocusedWindow {
+        class: class?,
+        title: title.unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hyprland() {
+        let reply = r#"{"address": "0x5a1c", "class": "kitty", "title": "~/src", "pid": 42}"#;
+        assert_eq!(
+            parse_hyprland(reply),
+            Some(FocusedWindow {
+                class: "kitty".to_string(),
+                title: "~/src".to_string(),
+            })
+        );
+        assert_eq!(parse_hyprland("{}"), None);
+        assert_eq!(parse_hyprland("invalid"), None);
+    }
+
+    #[test]
+    fn test_parse_sway_tree() {
+        let tree = r#"{"type": "root", "focused": false, "nodes": [
+            {"type": "output", "focused": false, "nodes": [
+                {"type": "workspace", "focused": false, "nodes": [
+                    {"focused": false, "pid": 1, "app_id": "firefox", "name": "Docs"}
+                ], "floating_nodes": [
+                    {"focused": true, "pid": 2, "app_id": null, "name": "Slack | general",
+                     "window_properties": {"class": "Slack", "instance": "slack"}}
+                ]}
+            ]}
+        ]}"#;
+        assert_eq!(
+            parse_sway_tree(tree),
+            Some(FocusedWindow {
+                class: "Slack".to_string(),
+                title: "Slack | general".to_string(),
+            })
+        );
+
+        // An empty workspace has focus
+        let tree = r#"{"focused": false, "nodes": [{"type": "workspace", "focused": true}]}"#;
+        assert_eq!(parse_sway_tree(tree), None);
+    }
+
+    #[test]
+    fn test_parse_xprop() {
+        let output = "WM_CLASS = \"code\", \"Code\"\n\
+                      _NET_WM_NAME = \"main.rs - mojovoice\"\n\
+                      WM_NAME = \"ignored\"\n";
+        assert_eq!(
+            parse_xprop(output),
+            Some(FocusedWindow {
+                class: "Code".to_string(),
+                title: "main.rs - mojovoice".to_string(),
+            })
+        );
+
+        let output = "WM_CLASS = \"xterm\", \"XTerm\"\n_NET_WM_NAME:  not found.\n\
+                      WM_NAME = \"bash\"\n";
+        assert_eq!(parse_xprop(output).unwrap().title, "bash");
+        assert_eq!(parse_xprop("WM_CLASS:  not found.\n"), None);
+    }
+}
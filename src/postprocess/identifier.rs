@@ -0,0 +1,346 @@
+//! Code identifiers dictated as words
+//!
+//! "camel user account id end" becomes `userAccountId`: a spoken case prefix formats
+//! the words after it up to a spoken "end". Without the "end" (within
+//! `MAX_IDENTIFIER_WORDS` words on the same line) the prefix is ordinary prose, so "the
+//! path to the river" is left alone. The same formatting applies to a whole
+//! transcription when `mojovoice start --code <case>` requests it. Terms from the
+//! vocabulary store win over the generated spelling when it fits the case, so
+//! "pascal http client end" can come out as a known `HTTPClient`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{bare, is_trailing_punct};
+
+/// Most words a spoken prefix formats before its "end"
+const MAX_IDENTIFIER_WORDS: usize = 8;
+
+/// Spoken word that closes an identifier
+const END_WORD: &str = "end";
+
+/// How the words of an identifier are joined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentifierCase {
+    /// `userAccountId`
+    Camel,
+    /// `UserAccountId`
+    Pascal,
+    /// `user_account_id`
+    Snake,
+    /// `USER_ACCOUNT_ID`
+    ScreamingSnake,
+    /// `user-account-id`
+    Kebab,
+    /// `src/main.rs` ("path src main dot rs")
+    Path,
+}
+
+impl IdentifierCase {
+    /// The case named by a spoken prefix word ("screaming" needs a following "snake")
+    fn from_prefix(word: &str) -> Option<Self> {
+        match word {
+            "camel" => Some(IdentifierCase::Camel),
+            "pascal" => Some(IdentifierCase::Pascal),
+            "snake" => Some(IdentifierCase::Snake),
+            "kebab" => Some(IdentifierCase::Kebab),
+            "path" => Some(IdentifierCase::Path),
+            _ => None,
+        }
+    }
+
+    /// Whether `term` is spelled in this case, so it can stand in for joined words
+    fn fits(self, term: &str) -> bool {
+        let only = |extra: &[char]| {
+            term.chars()
+                .all(|c| c.is_alphanumeric() || extra.contains(&c))
+        };
+        let first_upper = term.chars().next().is_some_and(char::is_uppercase);
+        match self {
+            IdentifierCase::Camel => only(&[]) && !first_upper,
+            IdentifierCase::Pascal => only(&[]) && first_upper,
+            IdentifierCase::Snake => only(&['_']) && !term.chars().any(char::is_uppercase),
+            IdentifierCase::ScreamingSnake => only(&['_']) && !term.chars().any(char::is_lowercase),
+            IdentifierCase::Kebab => only(&['-']) && !term.chars().any(char::is_uppercase),
+            IdentifierCase::Path => only(&['/', '.', '_', '-']),
+        }
+    }
+
+    /// Join lowercase words
+    fn join(self, words: &[String]) -> String {
+        match self {
+            IdentifierCase::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) })
+                .collect(),
+            IdentifierCase::Pascal => words.iter().map(|w| capitalize(w)).collect(),
+            IdentifierCase::Snake => words.join("_"),
+            IdentifierCase::ScreamingSnake => words.join("_").to_uppercase(),
+            IdentifierCase::Kebab => words.join("-"),
+            IdentifierCase::Path => {
+                let mut out = String::new();
+                let mut glued = true;
+                for (i, word) in words.iter().enumerate() {
+                    if word == "dot" && i > 0 && i + 1 < words.len() {
+                        out.push('.');
+                        glued = true;
+                        continue;
+                    }
+                    if !glued {
+                        out.push('/');
+                    }
+                    out.push_str(word);
+                    glued = false;
+                }
+                out
+            },
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Lowercase alphanumeric words of `text`; apostrophes don't split a word
+fn words_of(text: &str) -> Vec<String> {
+    text.replace(['\'', '’'], "")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Matching key for an identifier: its letters and digits, lowercased
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Identifier formatting with a set of known spellings
+#[derive(Debug, Clone, Default)]
+pub struct Identifiers {
+    /// Normalized key -> terms as stored, in preference order
+    known: HashMap<String, Vec<String>>,
+}
+
+impl Identifiers {
+    /// Prefer `terms` (e.g. the vocabulary store, most used first) over generated
+    /// spellings in the case they are written in. When two terms of the same case
+    /// normalize the same, the first wins.
+    pub fn new(terms: &[String]) -> Self {
+        let mut known: HashMap<String, Vec<String>> = HashMap::new();
+        for term in terms {
+            let key = normalize(term);
+            if !key.is_empty() {
+                known.entry(key).or_default().push(term.clone());
+            }
+        }
+        Self { known }
+    }
+
+    fn format_words(&self, words: &[String], case: IdentifierCase) -> String {
+        // A spoken "dot" in a path is punctuation, which stored terms are keyed without
+        let key: String = words
+            .iter()
+            .enumerate()
+            .filter(|(i, word)| {
+                !(case == IdentifierCase::Path && *word == "dot" && *i > 0 && i + 1 < words.len())
+            })
+            .map(|(_, word)| word.as_str())
+            .collect();
+        self.known
+            .get(&key)
+            .and_then(|terms| terms.iter().find(|term| case.fits(term)))
+            .cloned()
+            .unwrap_or_else(|| case.join(words))
+    }
+
+    /// Format a whole transcription as one identifier, dropping its punctuation
+    pub fn format(&self, text: &str, case: IdentifierCase) -> String {
+        self.format_words(&words_of(text), case)
+    }
+
+    /// Replace spoken identifiers ("snake user id end") line by line. An optional
+    /// "case" after the prefix is skipped; a prefix without words and an "end" after
+    /// it stays text.
+    pub fn apply(&self, text: &str) -> String {
+        text.split('\n')
+            .map(|line| self.apply_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn apply_line(&self, line: &str) -> String {
+        let tokens: Vec<&str> = line.split(' ').collect();
+        let mut out: Vec<String> = Vec::new();
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let Some((case, prefix_len)) = prefix_at(&tokens[i..]) else {
+                out.push(tokens[i].to_string());
+                i += 1;
+                continue;
+            };
+
+            let Some((words, trailing, end)) = identifier_words(&tokens[i + prefix_len..]) else {
+                out.push(tokens[i].to_string());
+                i += 1;
+                continue;
+            };
+            out.push(format!("{}{}", self.format_words(&words, case), trailing));
+            i += prefix_len + end;
+        }
+        out.join(" ")
+    }
+}
+
+/// The words of a spoken identifier at the start of `tokens`, closed by "end" within
+/// `MAX_IDENTIFIER_WORDS` words: (words, punctuation after the "end", tokens used).
+/// Punctuation before the "end" means this is prose.
+fn identifier_words<'a>(tokens: &[&'a str]) -> Option<(Vec<String>, &'a str, usize)> {
+    let mut words = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let stem = token.trim_end_matches(is_trailing_punct);
+        if bare(stem) == END_WORD {
+            return (!words.is_empty()).then(|| (words, &token[stem.len()..], i + 1));
+        }
+        if stem.len() < token.len() {
+            return None;
+        }
+        words.extend(words_of(stem));
+        if words.len() > MAX_IDENTIFIER_WORDS {
+            return None;
+        }
+    }
+    None
+}
+
+/// A case prefix at the start of `tokens`, with the number of tokens it spans
+fn prefix_at(tokens: &[&str]) -> Option<(IdentifierCase, usize)> {
+    // "snake." ends a sentence; a prefix can't carry punctuation
+    let plain = |i: usize| {
+        tokens
+            .get(i)
+            .filter(|t| !t.ends_with(is_trailing_punct))
+            .map(|t| bare(t))
+    };
+
+    let first = plain(0)?;
+    let (case, len) = if first == "screaming" && plain(1).as_deref() == Some("snake") {
+        (IdentifierCase::ScreamingSnake, 2)
+    } else {
+        (IdentifierCase::from_prefix(&first)?, 1)
+    };
+
+    if plain(len).as_deref() == Some("case") {
+        Some((case, len + 1))
+    } else {
+        Some((case, len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cases() {
+        let identifiers = Identifiers::default();
+        let cases = [
+            (IdentifierCase::Camel, "userAccountId"),
+            (IdentifierCase::Pascal, "UserAccountId"),
+            (IdentifierCase::Snake, "user_account_id"),
+            (IdentifierCase::ScreamingSnake, "USER_ACCOUNT_ID"),
+            (IdentifierCase::Kebab, "user-account-id"),
+            (IdentifierCase::Path, "user/account/id"),
+        ];
+        for (case, expected) in cases {
+            assert_eq!(identifiers.format("User account ID.", case), expected);
+        }
+        assert_eq!(
+            identifiers.format("source main dot rs", IdentifierCase::Path),
+            "source/main.rs"
+        );
+        assert_eq!(identifiers.format("", IdentifierCase::Camel), "");
+    }
+
+    #[test]
+    fn test_spoken_prefixes() {
+        let identifiers = Identifiers::default();
+        let cases = [
+            ("camel user account id end", "userAccountId"),
+            (
+                "set snake max retries end, then go",
+                "set max_retries, then go",
+            ),
+            ("Screaming snake case api key end.", "API_KEY."),
+            (
+                "kebab case main menu end\npath src lib dot rs end",
+                "main-menu\nsrc/lib.rs",
+            ),
+            // Not prefixes
+            ("a snake. it bit", "a snake. it bit"),
+            ("the screaming kids", "the screaming kids"),
+            ("just camel", "just camel"),
+            ("camel end", "camel end"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(identifiers.apply(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_prose_with_prefix_words_is_untouched() {
+        let identifiers = Identifiers::default();
+        let prose = [
+            "we walked down the path to the river",
+            "we walked down the path to the river.",
+            "the snake slid under the rock and we ran",
+            // An "end" after punctuation or too many words later is not a terminator
+            "take the path, then turn left at the end",
+            "a camel can walk for days across the desert without water until the end",
+            "kebab shops stay open late\nend of story",
+        ];
+        for text in prose {
+            assert_eq!(identifiers.apply(text), text, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_known_terms_are_preferred() {
+        let identifiers = Identifiers::new(&[
+            "HTTPClient".to_string(),
+            "http_client".to_string(),
+            "mojovoice".to_string(),
+        ]);
+        assert_eq!(identifiers.apply("pascal http client end"), "HTTPClient");
+        assert_eq!(identifiers.apply("snake http client end"), "http_client");
+        // Known spellings that don't fit the case are not used
+        assert_eq!(identifiers.apply("camel http client end"), "httpClient");
+        assert_eq!(
+            identifiers.format("Mojo voice", IdentifierCase::Pascal),
+            "MojoVoice"
+        );
+        assert_eq!(
+            identifiers.format("Mojo voice", IdentifierCase::Snake),
+            "mojovoice"
+        );
+        assert_eq!(identifiers.apply("snake http server end"), "http_server");
+    }
+
+    #[test]
+    fn test_known_paths_match_without_dots() {
+        let identifiers = Identifiers::new(&["src/main.rs".to_string()]);
+        assert_eq!(identifiers.apply("path src main dot rs end"), "src/main.rs");
+        assert_eq!(identifiers.apply("path src lib dot rs end"), "src/lib.rs");
+    }
+}
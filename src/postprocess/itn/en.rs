@@ -0,0 +1,158 @@
+//! English lexicon
+
+use super::Lexicon;
+
+pub(super) const ENGLISH: Lexicon = Lexicon {
+    cardinals: &[
+        ("zero", 0),
+        ("one", 1),
+        ("two", 2),
+        ("three", 3),
+        ("four", 4),
+        ("five", 5),
+        ("six", 6),
+        ("seven", 7),
+        ("eight", 8),
+        ("nine", 9),
+        ("ten", 10),
+        ("eleven", 11),
+        ("twelve", 12),
+        ("thirteen", 13),
+        ("fourteen", 14),
+        ("fifteen", 15),
+        ("sixteen", 16),
+        ("seventeen", 17),
+        ("eighteen", 18),
+        ("nineteen", 19),
+        ("twenty", 20),
+        ("thirty", 30),
+        ("forty", 40),
+        ("fifty", 50),
+        ("sixty", 60),
+        ("seventy", 70),
+        ("eighty", 80),
+        ("ninety", 90),
+        ("hundred", 100),
+        ("thousand", 1_000),
+        ("million", 1_000_000),
+        ("billion", 1_000_000_000),
+        ("trillion", 1_000_000_000_000),
+    ],
+    ordinals: &[
+        ("first", 1),
+        ("second", 2),
+        ("third", 3),
+        ("fourth", 4),
+        ("fifth", 5),
+        ("sixth", 6),
+        ("seventh", 7),
+        ("eighth", 8),
+        ("ninth", 9),
+        ("tenth", 10),
+        ("eleventh", 11),
+        ("twelfth", 12),
+        ("thirteenth", 13),
+        ("fourteenth", 14),
+        ("fifteenth", 15),
+        ("sixteenth", 16),
+        ("seventeenth", 17),
+        ("eighteenth", 18),
+        ("nineteenth", 19),
+        ("twentieth", 20),
+        ("thirtieth", 30),
+        ("fortieth", 40),
+        ("fiftieth", 50),
+        ("sixtieth", 60),
+        ("seventieth", 70),
+        ("eightieth", 80),
+        ("ninetieth", 90),
+        ("hundredth", 100),
+        ("thousandth", 1_000),
+        ("millionth", 1_000_000),
+        ("billionth", 1_000_000_000),
+    ],
+    ambiguous_ordinals: &["second"],
+    and: "and",
+    point: "point",
+    oh: "oh",
+    minus: &["minus", "negative"],
+    plural: "s",
+    percent: &[&["percent"], &["per", "cent"]],
+    currencies: &[
+        (&["dollar"], "$"),
+        (&["euro"], "€"),
+        (&["yen"], "¥"),
+        (&["rupee"], "₹"),
+    ],
+    cent: "cent",
+    units: &[
+        (&["nanosecond"], "ns"),
+        (&["microsecond"], "µs"),
+        (&["millisecond"], "ms"),
+        (&["hertz"], "Hz"),
+        (&["kilohertz"], "kHz"),
+        (&["megahertz"], "MHz"),
+        (&["gigahertz"], "GHz"),
+        (&["kilobyte"], "KB"),
+        (&["megabyte"], "MB"),
+        (&["gigabyte"], "GB"),
+        (&["terabyte"], "TB"),
+        (&["millimeter"], "mm"),
+        (&["millimetre"], "mm"),
+        (&["centimeter"], "cm"),
+        (&["centimetre"], "cm"),
+        (&["meter"], "m"),
+        (&["metre"], "m"),
+        (&["kilometer"], "km"),
+        (&["kilometre"], "km"),
+        (&["milligram"], "mg"),
+        (&["gram"], "g"),
+        (&["kilogram"], "kg"),
+        (&["milliliter"], "mL"),
+        (&["millilitre"], "mL"),
+        (&["liter"], "L"),
+        (&["litre"], "L"),
+        (&["volt"], "V"),
+        (&["watt"], "W"),
+        (&["kilowatt"], "kW"),
+        (&["pixel"], "px"),
+        (&["frame", "per", "second"], "fps"),
+        (&["mile", "per", "hour"], "mph"),
+        (&["kilometer", "per", "hour"], "km/h"),
+        (&["kilometre", "per", "hour"], "km/h"),
+        (&["degree"], "°"),
+        (&["degree", "celsius"], "°C"),
+        (&["degree", "fahrenheit"], "°F"),
+    ],
+    months: [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ],
+    ambiguous_months: &["march", "may"],
+    meridiem: &["am", "a.m", "pm", "p.m"],
+    oclock: &["o'clock", "o’clock"],
+    time_prepositions: &["at", "by", "until", "till", "from"],
+    version: &["version"],
+    paired_years: Some((13, 20)),
+    ordinal_suffix,
+};
+
+fn ordinal_suffix(n: u64) -> &'static str {
+    match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
@@ -0,0 +1,824 @@
+//! Inverse text normalization: spoken numbers, dates and units in written form
+//!
+//! "version three point two" becomes "version 3.2" and "twenty five milliseconds"
+//! becomes "25 ms". The rules are shared; the words they match come from a
+//! per-language [`Lexicon`]. Numbers below ten stay spelled out ("three options")
+//! unless a unit, currency, sign, decimal or version needs digits ("three percent"
+//! is "3%").
+
+use super::is_trailing_punct;
+
+mod en;
+
+/// Words and conventions of one language
+#[derive(Debug)]
+pub(super) struct Lexicon {
+    /// Cardinal number words ("twenty" = 20, "hundred" = 100, "million", ...)
+    cardinals: &'static [(&'static str, u64)],
+    ordinals: &'static [(&'static str, u64)],
+    /// Ordinals that are also common words: "thirty second" is read as a duration
+    /// unless it is a date
+    ambiguous_ordinals: &'static [&'static str],
+    /// Joins the parts of a number ("one hundred and five")
+    and: &'static str,
+    /// Decimal or version separator
+    point: &'static str,
+    /// Zero digit after a point, in minutes and years ("three oh five")
+    oh: &'static str,
+    /// Sign words
+    minus: &'static [&'static str],
+    /// Plural ending; units and currencies are listed in the singular
+    plural: &'static str,
+    percent: &'static [&'static [&'static str]],
+    /// Currency words and the symbol written before the amount
+    currencies: &'static [(&'static [&'static str], &'static str)],
+    /// Subunit of a currency ("five dollars and fifty cents")
+    cent: &'static str,
+    /// Units and their symbols; symbols starting with `°` attach to the number
+    units: &'static [(&'static [&'static str], &'static str)],
+    months: [&'static str; 12],
+    /// Months that are also common words; they need an ordinal day ("may third")
+    ambiguous_months: &'static [&'static str],
+    /// "am"/"pm" as transcribed; a time keeps the spelling
+    meridiem: &'static [&'static str],
+    oclock: &'static [&'static str],
+    /// Words that make "three thirty" a time without am/pm ("at three thirty")
+    time_prepositions: &'static [&'static str],
+    /// Words after which a small number is written in digits ("version three")
+    version: &'static [&'static str],
+    /// Range of the first pair of a year read in pairs ("nineteen ninety nine")
+    paired_years: Option<(u64, u64)>,
+    ordinal_suffix: fn(u64) -> &'static str,
+}
+
+/// The lexicon for a language code. Only English exists so far; it also serves
+/// languages without their own, whose words it doesn't match.
+pub(super) fn lexicon(_language: &str) -> &'static Lexicon {
+    &en::ENGLISH
+}
+
+/// Rewrite spoken numbers line by line
+pub(super) fn normalize(text: &str, lexicon: &Lexicon) -> String {
+    text.split('\n')
+        .map(|line| normalize_line(line, lexicon))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn normalize_line(line: &str, lex: &Lexicon) -> String {
+    let tokens = tokenize(line, lex);
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let after_number = out.ends_with(|c: char| c.is_ascii_digit());
+        let written = time(lex, &tokens, i)
+            .or_else(|| date(lex, &tokens, i))
+            .or_else(|| paired_year(lex, &tokens, i).map(|(y, end)| (y.to_string(), end)))
+            .or_else(|| expression(lex, &tokens, i, after_number));
+
+        out.push_str(token.sep);
+        match written {
+            Some((text, end)) => {
+                out.push_str(&text);
+                out.push_str(tokens[end - 1].punct);
+                i = end;
+            },
+            None => {
+                out.push_str(token.text);
+                out.push_str(token.punct);
+                i += 1;
+            },
+        }
+    }
+    out
+}
+
+/// One word of a line
+#[derive(Debug)]
+struct Token<'a> {
+    /// What precedes it in the line: "", " ", or "-" inside "twenty-five"
+    sep: &'a str,
+    /// As transcribed, without trailing punctuation
+    text: &'a str,
+    /// Lowercased `text`, for matching
+    word: String,
+    /// Trailing punctuation
+    punct: &'a str,
+}
+
+impl Token<'_> {
+    /// Whether an expression may continue past this token
+    fn open(&self) -> bool {
+        self.punct.is_empty()
+    }
+}
+
+/// Split at spaces, and at hyphens between number words
+fn tokenize<'a>(line: &'a str, lex: &Lexicon) -> Vec<Token<'a>> {
+    let mut tokens = Vec::new();
+    for (n, raw) in line.split(' ').enumerate() {
+        let sep = if n == 0 { "" } else { " " };
+        let stem = raw.trim_end_matches(is_trailing_punct);
+        let punct = &raw[stem.len()..];
+
+        let parts: Vec<&str> = stem.split('-').collect();
+        let numeric = parts.len() > 1
+            && parts
+                .iter()
+                .all(|part| lex.number_word(&part.to_lowercase()).is_some());
+        if !numeric {
+            let word = stem.to_lowercase();
+            tokens.push(Token {
+                sep,
+                text: stem,
+                word,
+                punct,
+            });
+            continue;
+        }
+
+        let last = parts.len() - 1;
+        for (k, part) in parts.into_iter().enumerate() {
+            tokens.push(Token {
+                sep: if k == 0 { sep } else { "-" },
+                text: part,
+                word: part.to_lowercase(),
+                punct: if k == last { punct } else { "" },
+            });
+        }
+    }
+    tokens
+}
+
+/// The token at `at` when the expression before it may continue
+fn next<'t, 'a>(tokens: &'t [Token<'a>], at: usize) -> Option<&'t Token<'a>> {
+    if at == 0 || !tokens.get(at - 1)?.open() {
+        return None;
+    }
+    tokens.get(at)
+}
+
+/// Where a number word may appear in a number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    /// 0-9
+    Unit,
+    /// 10-19
+    Teen,
+    /// 20, 30, ... 90
+    Tens,
+    Hundred,
+    /// Thousand and up
+    Scale,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct NumberWord {
+    value: u64,
+    class: Class,
+    ordinal: bool,
+}
+
+fn lookup(table: &[(&str, u64)], word: &str) -> Option<u64> {
+    table.iter().find(|(w, _)| *w == word).map(|(_, v)| *v)
+}
+
+impl Lexicon {
+    fn number_word(&self, word: &str) -> Option<NumberWord> {
+        let (value, ordinal) = lookup(self.cardinals, word)
+            .map(|v| (v, false))
+            .or_else(|| lookup(self.ordinals, word).map(|v| (v, true)))?;
+        let class = match value {
+            0..=9 => Class::Unit,
+            10..=19 => Class::Teen,
+            20..=99 => Class::Tens,
+            100 => Class::Hundred,
+            _ => Class::Scale,
+        };
+        Some(NumberWord {
+            value,
+            class,
+            ordinal,
+        })
+    }
+
+    fn cardinal(&self, word: &str) -> Option<NumberWord> {
+        self.number_word(word).filter(|w| !w.ordinal)
+    }
+
+    /// A single digit, including "oh"
+    fn digit(&self, word: &str) -> Option<u64> {
+        if word == self.oh {
+            return Some(0);
+        }
+        self.cardinal(word)
+            .filter(|w| w.class == Class::Unit)
+            .map(|w| w.value)
+    }
+
+    /// `word` is `singular` or its plural
+    fn names(&self, word: &str, singular: &str) -> bool {
+        word == singular || word.strip_suffix(self.plural) == Some(singular)
+    }
+}
+
+/// The end of `words` starting at `at`, if they continue the expression
+fn phrase_end(lex: &Lexicon, tokens: &[Token], at: usize, words: &[&str]) -> Option<usize> {
+    for (k, word) in words.iter().enumerate() {
+        let token = next(tokens, at + k)?;
+        if !lex.names(&token.word, word) {
+            return None;
+        }
+    }
+    Some(at + words.len())
+}
+
+/// A spoken number
+#[derive(Debug)]
+struct Number {
+    value: u64,
+    ordinal: bool,
+    end: usize,
+    /// For a number that is a multiple of one scale of a million or more, the scale
+    /// and its word, so it's written "5 million"
+    scale: Option<(u64, String)>,
+}
+
+/// The longest number spoken from `start` ("two thousand and twenty four")
+fn parse_number(lex: &Lexicon, tokens: &[Token], start: usize) -> Option<Number> {
+    let mut total = 0;
+    let mut current = 0;
+    let mut last: Option<Class> = None;
+    let mut big = u64::MAX;
+    let mut scales = 0;
+    let mut ordinal = false;
+    let mut at = start;
+    let mut end = start;
+
+    loop {
+        let token = if at == start {
+            tokens.get(at)
+        } else {
+            next(tokens, at)
+        };
+        let Some(token) = token else { break };
+
+        if token.word == lex.and && matches!(last, Some(Class::Hundred | Class::Scale)) {
+            let joins = next(tokens, at + 1)
+                .and_then(|t| lex.number_word(&t.word))
+                .is_some_and(|w| {
+                    matches!(w.class, Class::Unit | Class::Teen | Class::Tens) && w.value > 0
+                });
+            if !joins {
+                break;
+            }
+            at += 1;
+            continue;
+        }
+
+        let Some(word) = lex.number_word(&token.word) else {
+            break;
+        };
+        let fits = match word.class {
+            Class::Unit if word.value == 0 => last.is_none(),
+            Class::Unit => matches!(
+                last,
+                None | Some(Class::Tens | Class::Hundred | Class::Scale)
+            ),
+            Class::Teen | Class::Tens => matches!(last, None | Some(Class::Hundred | Class::Scale)),
+            Class::Hundred => (1..100).contains(&current),
+            Class::Scale => current > 0 && word.value < big,
+        };
+        if !fits {
+            break;
+        }
+
+        match word.class {
+            Class::Hundred => current *= 100,
+            Class::Scale => {
+                total += current * word.value;
+                current = 0;
+                big = word.value;
+                scales += 1;
+            },
+            _ => current += word.value,
+        }
+        last = Some(word.class);
+        at += 1;
+        end = at;
+        if word.ordinal {
+            ordinal = true;
+            break;
+        }
+    }
+
+    last?;
+    let scale = (last == Some(Class::Scale) && scales == 1 && big >= 1_000_000 && !ordinal)
+        .then(|| (big, tokens[end - 1].text.to_lowercase()));
+    Some(Number {
+        value: total + current,
+        ordinal,
+        end,
+        scale,
+    })
+}
+
+/// Digits with thousands separators from 10,000 up
+fn group(n: u64) -> String {
+    let digits = n.to_string();
+    if n < 10_000 {
+        return digits;
+    }
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (k, c) in digits.chars().enumerate() {
+        if k > 0 && (digits.len() - k) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Already written as digits ("25", "1,000", "3.5")
+fn is_numeral(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_digit())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == ',' || c == '.')
+}
+
+/// A number in written form
+#[derive(Debug)]
+struct Amount {
+    text: String,
+    end: usize,
+    ordinal: bool,
+    /// Spelled out and below ten, with no decimals
+    small: bool,
+    /// A whole number without decimals or scale word
+    plain: bool,
+    /// Spelled out rather than already in digits
+    spoken: bool,
+}
+
+fn amount(lex: &Lexicon, tokens: &[Token], start: usize) -> Option<Amount> {
+    let token = tokens.get(start)?;
+    if is_numeral(&token.word) {
+        return Some(Amount {
+            text: token.text.to_string(),
+            end: start + 1,
+            ordinal: false,
+            small: false,
+            plain: !token.text.contains('.'),
+            spoken: false,
+        });
+    }
+
+    let mut number = parse_number(lex, tokens, start)?;
+    if number.ordinal && number.end - start > 1 {
+        let last = tokens[number.end - 1].word.as_str();
+        if lex.ambiguous_ordinals.contains(&last) {
+            number = parse_number(lex, &tokens[..number.end - 1], start)?;
+        }
+    }
+    if number.ordinal {
+        return Some(Amount {
+            text: format!(
+                "{}{}",
+                group(number.value),
+                (lex.ordinal_suffix)(number.value)
+            ),
+            end: number.end,
+            ordinal: true,
+            small: number.value < 10,
+            plain: true,
+            spoken: true,
+        });
+    }
+
+    let mut text = match &number.scale {
+        Some((scale, word)) => format!("{} {}", group(number.value / scale), word),
+        None => group(number.value),
+    };
+    let mut end = number.end;
+    let mut decimal = false;
+    if number.scale.is_none() {
+        while let Some((part, after)) = fraction(lex, tokens, end) {
+            text.push('.');
+            text.push_str(&part);
+            end = after;
+            decimal = true;
+        }
+        // "three point five million"
+        let scale = next(tokens, end).filter(|t| {
+            decimal
+                && lex
+                    .cardinal(&t.word)
+                    .is_some_and(|w| w.class == Class::Scale)
+        });
+        if let Some(scale) = scale {
+            text.push(' ');
+            text.push_str(&scale.word);
+            end += 1;
+        }
+    }
+
+    Some(Amount {
+        text,
+        end,
+        ordinal: false,
+        small: number.value < 10 && !decimal,
+        plain: !decimal && number.scale.is_none(),
+        spoken: true,
+    })
+}
+
+/// The part after a "point" at `at`: a digit run ("one four") or, for versions, a
+/// number ("one point twelve")
+fn fraction(lex: &Lexicon, tokens: &[Token], at: usize) -> Option<(String, usize)> {
+    if next(tokens, at)?.word != lex.point {
+        return None;
+    }
+
+    let mut digits = String::new();
+    let mut end = at + 1;
+    while let Some(digit) = next(tokens, end).and_then(|t| lex.digit(&t.word)) {
+        digits.push_str(&digit.to_string());
+        end += 1;
+    }
+    if !digits.is_empty() {
+        return Some((digits, end));
+    }
+
+    next(tokens, at + 1)?;
+    let number = parse_number(lex, tokens, at + 1).filter(|n| !n.ordinal && n.scale.is_none())?;
+    Some((number.value.to_string(), number.end))
+}
+
+/// A number with its sign, percent, currency or unit
+fn expression(
+    lex: &Lexicon,
+    tokens: &[Token],
+    i: usize,
+    after_number: bool,
+) -> Option<(String, usize)> {
+    let negative = !after_number
+        && lex.minus.contains(&tokens[i].word.as_str())
+        && next(tokens, i + 1).is_some();
+    let (sign, start) = if negative { ("-", i + 1) } else { ("", i) };
+
+    let amount = amount(lex, tokens, start)?;
+    if amount.ordinal {
+        return (!amount.small && !negative).then_some((amount.text, amount.end));
+    }
+
+    if let Some(end) = lex
+        .percent
+        .iter()
+        .find_map(|words| phrase_end(lex, tokens, amount.end, words))
+    {
+        return Some((format!("{}{}%", sign, amount.text), end));
+    }
+
+    if let Some((end, symbol)) = lex
+        .currencies
+        .iter()
+        .find_map(|(words, symbol)| Some((phrase_end(lex, tokens, amount.end, words)?, symbol)))
+    {
+        let mut text = format!("{}{}{}", sign, symbol, amount.text);
+        let mut end = end;
+        if let Some((cents, after)) = cents(lex, tokens, end).filter(|_| amount.plain) {
+            text.push_str(&format!(".{:02}", cents));
+            end = after;
+        }
+        return Some((text, end));
+    }
+
+    if let Some((end, symbol)) = lex
+        .units
+        .iter()
+        .filter_map(|(words, symbol)| Some((phrase_end(lex, tokens, amount.end, words)?, symbol)))
+        .max_by_key(|(end, _)| *end)
+    {
+        let space = if symbol.starts_with('°') { "" } else { " " };
+        return Some((format!("{}{}{}{}", sign, amount.text, space, symbol), end));
+    }
+
+    let version =
+        i > 0 && tokens[i - 1].open() && lex.version.contains(&tokens[i - 1].word.as_str());
+    if !amount.spoken || (amount.small && !negative && !version) {
+        return None;
+    }
+    Some((format!("{}{}", sign, amount.text), amount.end))
+}
+
+/// "and fifty cents" after a currency amount
+fn cents(lex: &Lexicon, tokens: &[Token], at: usize) -> Option<(u64, usize)> {
+    if next(tokens, at)?.word != lex.and {
+        return None;
+    }
+    next(tokens, at + 1)?;
+    let number =
+        parse_number(lex, tokens, at + 1).filter(|n| !n.ordinal && (1..100).contains(&n.value))?;
+    let end = phrase_end(lex, tokens, number.end, &[lex.cent])?;
+    Some((number.value, end))
+}
+
+/// "three thirty pm", "ten oh five a.m.", "seven o'clock"
+fn time(lex: &Lexicon, tokens: &[Token], i: usize) -> Option<(String, usize)> {
+    let hour = lex
+        .cardinal(&tokens[i].word)
+        .filter(|w| (1..=12).contains(&w.value))?;
+
+    let mut at = i + 1;
+    let mut minutes = None;
+    if let Some(token) = next(tokens, at) {
+        if token.word == lex.oh {
+            minutes = next(tokens, at + 1)
+                .and_then(|t| lex.digit(&t.word))
+                .filter(|d| *d > 0);
+            at += 2;
+        } else if let Some(word) = lex.cardinal(&token.word) {
+            match word.class {
+                Class::Teen => {
+                    minutes = Some(word.value);
+                    at += 1;
+                },
+                Class::Tens if word.value < 60 => {
+                    let unit = next(tokens, at + 1)
+                        .and_then(|t| lex.cardinal(&t.word))
+                        .filter(|w| w.class == Class::Unit && w.value > 0);
+                    minutes = Some(word.value + unit.map_or(0, |w| w.value));
+                    at += if unit.is_some() { 2 } else { 1 };
+                },
+                _ => {},
+            }
+        }
+        if minutes.is_none() {
+            at = i + 1;
+        }
+    }
+
+    let clock = match minutes {
+        Some(m) => format!("{}:{:02}", hour.value, m),
+        None => hour.value.to_string(),
+    };
+    match next(tokens, at) {
+        Some(last) if lex.meridiem.contains(&last.word.as_str()) => {
+            Some((format!("{} {}", clock, last.text), at + 1))
+        },
+        Some(last) if minutes.is_none() && lex.oclock.contains(&last.word.as_str()) => {
+            Some((format!("{}:00", hour.value), at + 1))
+        },
+        // "at three thirty"
+        _ if minutes.is_some() && i > 0 && tokens[i - 1].open() => lex
+            .time_prepositions
+            .contains(&tokens[i - 1].word.as_str())
+            .then_some((clock, at)),
+        _ => None,
+    }
+}
+
+/// "march third", "july fourth twenty twenty six"
+fn date(lex: &Lexicon, tokens: &[Token], i: usize) -> Option<(String, usize)> {
+    let month = &tokens[i];
+    if !lex.months.contains(&month.word.as_str()) {
+        return None;
+    }
+    next(tokens, i + 1)?;
+    let day = parse_number(lex, tokens, i + 1)
+        .filter(|n| (1..=31).contains(&n.value) && n.scale.is_none())?;
+    if !day.ordinal && lex.ambiguous_months.contains(&month.word.as_str()) {
+        return None;
+    }
+
+    let mut chars = month.text.chars();
+    let name: String = chars
+        .next()
+        .into_iter()
+        .flat_map(char::to_uppercase)
+        .chain(chars)
+        .collect();
+    let mut text = format!("{} {}", name, day.value);
+    let mut end = day.end;
+
+    let year = next(tokens, end).and_then(|_| {
+        paired_year(lex, tokens, end).or_else(|| {
+            parse_number(lex, tokens, end)
+                .filter(|n| !n.ordinal && (1000..3000).contains(&n.value))
+                .map(|n| (n.value, n.end))
+        })
+    });
+    if let Some((year, after)) = year {
+        text.push_str(&format!(", {}", year));
+        end = after;
+    }
+    Some((text, end))
+}
+
+/// A year read in pairs: "nineteen ninety nine", "twenty oh five"
+fn paired_year(lex: &Lexicon, tokens: &[Token], at: usize) -> Option<(u64, usize)> {
+    let (low, high) = lex.paired_years?;
+    let (first, at) = pair(lex, tokens, at)?;
+    if !(low..=high).contains(&first) {
+        return None;
+    }
+
+    let token = next(tokens, at)?;
+    if token.word == lex.oh {
+        let digit = next(tokens, at + 1)
+            .and_then(|t| lex.digit(&t.word))
+            .filter(|d| *d > 0)?;
+        return Some((first * 100 + digit, at + 2));
+    }
+    let (second, end) = pair(lex, tokens, at)?;
+    Some((first * 100 + second, end))
+}
+
+/// Two digits as spoken: a teen, or tens with an optional unit
+fn pair(lex: &Lexicon, tokens: &[Token], at: usize) -> Option<(u64, usize)> {
+    let word = lex.cardinal(&tokens.get(at)?.word)?;
+    match word.class {
+        Class::Teen => Some((word.value, at + 1)),
+        Class::Tens => {
+            let unit = next(tokens, at + 1)
+                .and_then(|t| lex.cardinal(&t.word))
+                .filter(|w| w.class == Class::Unit && w.value > 0);
+            match unit {
+                Some(unit) => Some((word.value + unit.value, at + 2)),
+                None => Some((word.value, at + 1)),
+            }
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn itn(text: &str) -> String {
+        normalize(text, lexicon("en"))
+    }
+
+    fn check(cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            assert_eq!(itn(input), *expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_cardinals() {
+        check(&[
+            ("ten", "10"),
+            ("twenty five people", "25 people"),
+            ("twenty-five people", "25 people"),
+            ("one hundred and five", "105"),
+            ("nineteen hundred", "1900"),
+            ("two thousand twenty four", "2024"),
+            ("twelve thousand three hundred forty", "12,340"),
+            ("one hundred thousand", "100,000"),
+            ("two million three hundred thousand", "2,300,000"),
+            ("five million users", "5 million users"),
+            ("Forty two.", "42."),
+            ("ninety nine, a hundred", "99, a hundred"),
+        ]);
+    }
+
+    #[test]
+    fn test_small_numbers_stay_words() {
+        check(&[
+            ("one of three options", "one of three options"),
+            ("zero", "zero"),
+            ("one two three", "one two three"),
+            ("the first and second", "the first and second"),
+            ("thousand", "thousand"),
+            ("hundreds of them", "hundreds of them"),
+        ]);
+    }
+
+    #[test]
+    fn test_ordinals() {
+        check(&[
+            ("the twenty first century", "the 21st century"),
+            ("eleventh", "11th"),
+            ("twelfth", "12th"),
+            ("one hundred and third", "103rd"),
+            ("a thirty second clip", "a 30 second clip"),
+            ("ninety second", "90 second"),
+            ("third place", "third place"),
+            ("forty fourth", "44th"),
+            ("one thousandth", "1000th"),
+        ]);
+    }
+
+    #[test]
+    fn test_decimals_and_versions() {
+        check(&[
+            ("version three point two", "version 3.2"),
+            ("version three", "version 3"),
+            ("version three point two point one", "version 3.2.1"),
+            ("version ten point twelve point one", "version 10.12.1"),
+            ("pi is three point one four", "pi is 3.14"),
+            ("zero point oh five", "0.05"),
+            ("three point five million", "3.5 million"),
+            ("a point of view", "a point of view"),
+            ("five point", "five point"),
+        ]);
+    }
+
+    #[test]
+    fn test_percent_and_sign() {
+        check(&[
+            ("five percent", "5%"),
+            ("twelve point five percent", "12.5%"),
+            ("ten per cent", "10%"),
+            ("5 percent", "5%"),
+            ("minus five", "-5"),
+            ("negative two percent", "-2%"),
+            ("ten minus three", "10 minus three"),
+            ("minus", "minus"),
+        ]);
+    }
+
+    #[test]
+    fn test_currency() {
+        check(&[
+            ("five dollars", "$5"),
+            ("one dollar", "$1"),
+            ("twenty five euros", "€25"),
+            ("five dollars and fifty cents", "$5.50"),
+            ("three thousand yen", "¥3000"),
+            ("two million dollars", "$2 million"),
+            ("one point five million dollars", "$1.5 million"),
+            ("twenty dollars and some change", "$20 and some change"),
+            ("fifty cents", "50 cents"),
+            ("30 dollars", "$30"),
+        ]);
+    }
+
+    #[test]
+    fn test_units() {
+        check(&[
+            ("twenty five milliseconds", "25 ms"),
+            ("one millisecond", "1 ms"),
+            ("five megabytes", "5 MB"),
+            ("sixteen gigabytes of RAM", "16 GB of RAM"),
+            ("two point four gigahertz", "2.4 GHz"),
+            ("sixty frames per second", "60 fps"),
+            ("one hundred kilometers per hour", "100 km/h"),
+            ("five kilometers", "5 km"),
+            ("twenty degrees celsius", "20°C"),
+            ("ninety degrees", "90°"),
+            ("minus ten degrees fahrenheit", "-10°F"),
+            ("250 milliseconds", "250 ms"),
+            ("twenty five minutes", "25 minutes"),
+            ("five minutes", "five minutes"),
+        ]);
+    }
+
+    #[test]
+    fn test_times() {
+        check(&[
+            ("at three thirty pm", "at 3:30 pm"),
+            ("at three pm", "at 3 pm"),
+            ("ten oh five a.m.", "10:05 a.m."),
+            ("at ten fifteen AM today", "at 10:15 AM today"),
+            ("twelve forty five p.m.", "12:45 p.m."),
+            ("seven o'clock", "7:00"),
+            ("see you at three thirty", "see you at 3:30"),
+            ("by eleven forty five, done", "by 11:45, done"),
+            ("at three, pm", "at three, pm"),
+        ]);
+    }
+
+    #[test]
+    fn test_dates_and_years() {
+        check(&[
+            ("on march third", "on March 3"),
+            ("july fourth twenty twenty six", "July 4, 2026"),
+            ("june fifth, two thousand nine", "June 5, 2009"),
+            ("march twenty second", "March 22"),
+            ("june fifth two thousand nine", "June 5, 2009"),
+            ("december thirty one", "December 31"),
+            ("may third", "May 3"),
+            ("you may one day", "you may one day"),
+            ("we march ten miles", "we march 10 miles"),
+            ("in nineteen ninety nine", "in 1999"),
+            ("twenty oh five", "2005"),
+            ("twenty twenty four", "2024"),
+        ]);
+    }
+
+    #[test]
+    fn test_lines_and_spacing_are_kept() {
+        check(&[
+            ("", ""),
+            ("twenty\nthirty five", "20\n35"),
+            ("  twenty five ", "  25 "),
+            ("nothing to do here", "nothing to do here"),
+        ]);
+    }
+}
@@ -0,0 +1,580 @@
+//! Text post-processing between transcription and output
+//!
+//! Every dictation runs through an ordered list of steps from `[postprocess]` (see
+//! [`dictation`]) before it is saved to history or injected; file transcriptions do
+//! when the request asks for it. Steps are applied in config order, so e.g.
+//! `spoken_punctuation` should run before `capitalize` to capitalize the start of
+//! dictated sentences, and `identifiers` after `spoken_punctuation` so a dictated
+//! "comma" ends an identifier.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::Config;
+use crate::output::profile::OutputProfile;
+
+pub mod identifier;
+mod itn;
+pub mod llm;
+pub mod spoken;
+
+pub use llm::{LlmConfig, LlmMode};
+pub use spoken::SpokenConfig;
+
+/// One transform, as configured
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Step {
+    /// Strip surrounding whitespace and collapse runs of spaces
+    Trim,
+    /// Drop hesitation words ("um", "uh", ...)
+    Fillers {
+        #[serde(default = "default_fillers")]
+        words: Vec<String>,
+    },
+    /// Uppercase the first letter of each sentence and the pronoun "I"
+    Capitalize,
+    /// Regex substitution; `replacement` may use `$1`-style groups
+    Replace {
+        pattern: String,
+        replacement: String,
+    },
+    /// Turn dictated "comma", "new line", "all caps", ... into symbols and formatting,
+    /// using the `[postprocess.spoken]` vocabulary
+    SpokenPunctuation,
+    /// Format words between "camel", "snake", "screaming snake", "kebab", "pascal" or
+    /// "path" and a spoken "end" as a code identifier
+    Identifiers,
+    /// Inverse text normalization: "twenty five milliseconds" to "25 ms", with
+    /// ordinals, decimals, versions, percentages, currencies, times and dates
+    Itn,
+    /// Rewrite by a local language model (`[postprocess.llm]`); `mode` defaults to
+    /// `postprocess.llm.mode`
+    Llm {
+        #[serde(default)]
+        mode: Option<LlmMode>,
+    },
+}
+
+fn default_fillers() -> Vec<String> {
+    ["um", "umm", "uh", "uhh", "er", "erm", "ah", "hmm", "mm"]
+        .map(String::from)
+        .to_vec()
+}
+
+/// Letter case forced on a dictation after its steps, e.g. by an output profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Casing {
+    /// Uppercase sentence starts and "I", as the `capitalize` step
+    Sentence,
+    /// Lowercase the first letter, unless it starts an acronym ("ls -la", "API key")
+    LowerFirst,
+    /// Lowercase everything
+    Lower,
+}
+
+impl Casing {
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Casing::Sentence => capitalize(text),
+            Casing::LowerFirst => {
+                let first_word = text.split_whitespace().next().unwrap_or_default();
+                let acronym = first_word.chars().filter(|c| c.is_alphabetic()).count() > 1
+                    && !first_word.chars().any(|c| c.is_lowercase());
+                let mut chars = text.chars();
+                match chars.next() {
+                    Some(first) if !acronym => first.to_lowercase().chain(chars).collect(),
+                    _ => text.to_string(),
+                }
+            },
+            Casing::Lower => text.to_lowercase(),
+        }
+    }
+}
+
+impl Step {
+    /// Short label for `postprocess --dry-run`
+    pub fn label(&self) -> String {
+        match self {
+            Step::Trim => "trim".to_string(),
+            Step::Fillers { .. } => "fillers".to_string(),
+            Step::Capitalize => "capitalize".to_string(),
+            Step::Replace { pattern, .. } => format!("replace /{}/", pattern),
+            Step::SpokenPunctuation => "spoken_punctuation".to_string(),
+            Step::Identifiers => "identifiers".to_string(),
+            Step::Itn => "itn".to_string(),
+            Step::Llm { mode: None } => "llm".to_string(),
+            Step::Llm { mode: Some(mode) } => format!("llm ({:?})", mode).to_lowercase(),
+        }
+    }
+}
+
+/// A step ready to run (regexes compiled)
+#[derive(Debug)]
+enum Stage {
+    Trim,
+    Fillers(Vec<String>),
+    Capitalize,
+    Replace(Regex, String),
+    SpokenPunctuation(spoken::Grammar),
+    Identifiers(identifier::Identifiers),
+    Itn(&'static itn::Lexicon),
+    Llm(Option<LlmMode>, LlmConfig),
+}
+
+impl Stage {
+    fn apply(&self, text: &str) -> String {
+        match self {
+            Stage::Trim => trim(text),
+            Stage::Fillers(words) => remove_fillers(text, words),
+            Stage::Capitalize => capitalize(text),
+            Stage::Replace(regex, replacement) => {
+                regex.replace_all(text, replacement.as_str()).into_owned()
+            },
+            Stage::SpokenPunctuation(grammar) => grammar.apply(text),
+            Stage::Identifiers(identifiers) => identifiers.apply(text),
+            Stage::Itn(lexicon) => itn::normalize(text, lexicon),
+            Stage::Llm(mode, config) => config.cleanup(text, mode.unwrap_or(config.mode)),
+        }
+    }
+}
+
+/// Compiled post-processing steps. The default pipeline leaves text unchanged.
+#[derive(Debug, Default)]
+pub struct Pipeline {
+    stages: Vec<(String, Stage)>,
+}
+
+impl Pipeline {
+    /// Compile configured steps, failing on an invalid regex. Spoken commands use the
+    /// vocabulary for `spoken.language`, or `language` when that isn't set.
+    pub fn new(steps: &[Step], spoken: &SpokenConfig, language: &str) -> Result<Self> {
+        let stages = steps
+            .iter()
+            .map(|step| {
+                let stage = match step {
+                    Step::Trim => Stage::Trim,
+                    Step::Fillers { words } => {
+                        Stage::Fillers(words.iter().map(|w| w.to_lowercase()).collect())
+                    },
+                    Step::Capitalize => Stage::Capitalize,
+                    Step::Replace {
+                        pattern,
+                        replacement,
+                    } => Stage::Replace(
+                        Regex::new(pattern)
+                            .with_context(|| format!("Invalid replace pattern /{}/", pattern))?,
+                        replacement.clone(),
+                    ),
+                    Step::SpokenPunctuation => {
+                        Stage::SpokenPunctuation(spoken::Grammar::new(spoken, language))
+                    },
+                    Step::Identifiers => Stage::Identifiers(Default::default()),
+                    Step::Itn => Stage::Itn(itn::lexicon(language)),
+                    Step::Llm { mode } => Stage::Llm(*mode, LlmConfig::default()),
+                };
+                Ok((step.label(), stage))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { stages })
+    }
+
+    /// Spell dictated identifiers that match `terms` as stored (see
+    /// [`identifier::Identifiers::new`])
+    pub fn with_vocabulary(mut self, terms: &[String]) -> Self {
+        for (_, stage) in &mut self.stages {
+            if let Stage::Identifiers(identifiers) = stage {
+                *identifiers = identifier::Identifiers::new(terms);
+            }
+        }
+        self
+    }
+
+    /// Send `llm` steps to the endpoint in `config`. A `mode` overrides theirs, and
+    /// adds a final `llm` step when none is configured.
+    pub fn with_llm(mut self, config: &LlmConfig, mode: Option<LlmMode>) -> Self {
+        let mut found = false;
+        for (label, stage) in &mut self.stages {
+            if let Stage::Llm(stage_mode, stage_config) = stage {
+                *stage_config = config.clone();
+                if mode.is_some() {
+                    *stage_mode = mode;
+                    *label = Step::Llm { mode }.label();
+                }
+                found = true;
+            }
+        }
+        if !found && mode.is_some() {
+            let step = Step::Llm { mode };
+            self.stages
+                .push((step.label(), Stage::Llm(mode, config.clone())));
+        }
+        self
+    }
+
+    /// Drop `llm` steps, for text that isn't a whole dictation (files, timed segments)
+    pub fn without_llm(mut self) -> Self {
+        self.stages
+            .retain(|(_, stage)| !matches!(stage, Stage::Llm(..)));
+        self
+    }
+
+    /// Run every step in order
+    pub fn apply(&self, text: &str) -> String {
+        self.stages
+            .iter()
+            .fold(text.to_string(), |text, (_, stage)| stage.apply(&text))
+    }
+
+    /// Output after each step, labeled, for inspecting a configuration
+    pub fn trace(&self, text: &str) -> Vec<(String, String)> {
+        let mut text = text.to_string();
+        self.stages
+            .iter()
+            .map(|(label, stage)| {
+                text = stage.apply(&text);
+                (label.clone(), text.clone())
+            })
+            .collect()
+    }
+}
+
+/// Post-process a finished dictation: the `[postprocess]` steps (or the output
+/// `profile`'s), then the profile's casing and identifier case. `llm` and `code`, from
+/// the command line, override the profile. Steps that don't compile are skipped.
+pub fn dictation(
+    text: &str,
+    config: &Config,
+    profile: Option<&OutputProfile>,
+    llm: Option<LlmMode>,
+    code: Option<identifier::IdentifierCase>,
+) -> String {
+    let postprocess = &config.postprocess;
+    let terms = vocabulary_terms();
+    let pipeline = match profile.and_then(|p| p.steps.as_deref()) {
+        Some(steps) => Pipeline::new(steps, &postprocess.spoken, &config.model.language),
+        None => postprocess.pipeline(&config.model.language),
+    };
+    let mut text = match pipeline {
+        Ok(pipeline) => pipeline
+            .with_llm(&postprocess.llm, llm)
+            .with_vocabulary(&terms)
+            .apply(text),
+        Err(e) => {
+            warn!("Text post-processing disabled: {:#}", e);
+            text.to_string()
+        },
+    };
+    if let Some(casing) = profile.and_then(|p| p.casing) {
+        text = casing.apply(&text);
+    }
+    if let Some(case) = code.or(profile.and_then(|p| p.code)) {
+        text = identifier::Identifiers::new(&terms).format(&text, case);
+    }
+    text
+}
+
+/// Vocabulary store terms, most used first; empty if the store can't be read
+pub fn vocabulary_terms() -> Vec<String> {
+    crate::vocab::VocabStore::open()
+        .and_then(|store| store.terms())
+        .unwrap_or_else(|e| {
+            warn!("Vocabulary unavailable for identifiers: {:#}", e);
+            Vec::new()
+        })
+}
+
+/// Collapse runs of spaces and tabs within lines and trim the whole text
+fn trim(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Punctuation that may trail a word
+fn is_trailing_punct(c: char) -> bool {
+    matches!(c, '.' | ',' | '!' | '?' | ';' | ':')
+}
+
+/// A word lowercased without trailing punctuation, for matching
+fn bare(word: &str) -> String {
+    word.trim_end_matches(is_trailing_punct).to_lowercase()
+}
+
+/// Drop filler words. A sentence end carried by a filler ("so, um.") moves to the
+/// previous word; a comma after it is dropped with it.
+fn remove_fillers(text: &str, fillers: &[String]) -> String {
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            let mut out: Vec<String> = Vec::new();
+            for word in line.split(' ') {
+                let key = bare(word);
+                if key.is_empty() || !fillers.contains(&key) {
+                    out.push(word.to_string());
+                    continue;
+                }
+                let end = word.trim_start_matches(|c| !is_trailing_punct(c));
+                if !end.starts_with(['.', '!', '?']) {
+                    continue;
+                }
+                if let Some(previous) = out.iter_mut().rev().find(|w| !w.is_empty()) {
+                    let stem = previous.trim_end_matches(is_trailing_punct).len();
+                    previous.truncate(stem);
+                    previous.push_str(end);
+                }
+            }
+            out.retain(|w| !w.is_empty());
+            out.join(" ")
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// Uppercase sentence starts (text start, after `.`/`!`/`?` followed by a space, and
+/// after line breaks) and the standalone pronoun "I"
+fn capitalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut sentence_start = true;
+    let mut previous = ' ';
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let word_start = !previous.is_alphanumeric() && previous != '\'';
+        let pronoun = word_start
+            && c == 'i'
+            && chars
+                .peek()
+                .is_none_or(|next| !next.is_alphanumeric() && *next != '-');
+
+        if c.is_alphanumeric() {
+            if sentence_start || pronoun {
+                out.extend(c.to_uppercase());
+            } else {
+                out.push(c);
+            }
+            sentence_start = false;
+        } else {
+            out.push(c);
+            let ends_sentence = matches!(c, '.' | '!' | '?')
+                && chars.peek().is_none_or(|next| next.is_whitespace());
+            if ends_sentence || c == '\n' {
+                sentence_start = true;
+            }
+        }
+        previous = c;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(steps: &[Step], text: &str) -> String {
+        Pipeline::new(steps, &SpokenConfig::default(), "en")
+            .unwrap()
+            .apply(text)
+    }
+
+    #[test]
+    fn test_default_pipeline_is_identity() {
+        assert_eq!(Pipeline::default().apply(" as is "), " as is ");
+    }
+
+    #[test]
+    fn test_trim() {
+        assert_eq!(
+            run(&[Step::Trim], "  hello   there \n  next\tline "),
+            "hello there\nnext line"
+        );
+    }
+
+    #[test]
+    fn test_fillers() {
+        let fillers = [Step::Fillers {
+            words: default_fillers(),
+        }];
+        assert_eq!(run(&fillers, "Um, so we, uh, ship it"), "so we, ship it");
+        assert_eq!(run(&fillers, "Ship it, um."), "Ship it.");
+        // Only whole words
+        assert_eq!(run(&fillers, "the umbrella hummed"), "the umbrella hummed");
+    }
+
+    #[test]
+    fn test_capitalize() {
+        let cases = [
+            ("hello. is it me? yes! ok", "Hello. Is it me? Yes! Ok"),
+            (
+                "i think i'm right, and i-beam stays",
+                "I think I'm right, and i-beam stays",
+            ),
+            ("first line\nsecond line", "First line\nSecond line"),
+            ("version 1.2 is out", "Version 1.2 is out"),
+            ("the wifi is down", "The wifi is down"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(run(&[Step::Capitalize], input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_casing() {
+        assert_eq!(Casing::Sentence.apply("hi. i'm here"), "Hi. I'm here");
+        assert_eq!(Casing::LowerFirst.apply("Git status."), "git status.");
+        assert_eq!(Casing::LowerFirst.apply("API key"), "API key");
+        assert_eq!(Casing::LowerFirst.apply("I am"), "i am");
+        assert_eq!(Casing::LowerFirst.apply(""), "");
+        assert_eq!(Casing::Lower.apply("Hello World"), "hello world");
+    }
+
+    #[test]
+    fn test_replace_with_groups() {
+        let step = Step::Replace {
+            pattern: r"(?i)\bmojo voice\b".to_string(),
+            replacement: "mojovoice".to_string(),
+        };
+        assert_eq!(run(&[step], "Start Mojo Voice now"), "Start mojovoice now");
+
+        let step = Step::Replace {
+            pattern: r"(\w+)@(\w+)".to_string(),
+            replacement: "$2 at $1".to_string(),
+        };
+        assert_eq!(run(&[step], "ping ops@home"), "ping home at ops");
+    }
+
+    #[test]
+    fn test_invalid_regex_is_an_error() {
+        let step = Step::Replace {
+            pattern: "(".to_string(),
+            replacement: String::new(),
+        };
+        assert!(Pipeline::new(&[step], &SpokenConfig::default(), "en").is_err());
+    }
+
+    #[test]
+    fn test_steps_run_in_order_and_trace() {
+        let steps = [
+            Step::Fillers {
+                words: default_fillers(),
+            },
+            Step::SpokenPunctuation,
+            Step::Capitalize,
+            Step::Trim,
+        ];
+        let pipeline = Pipeline::new(&steps, &SpokenConfig::default(), "en").unwrap();
+        let trace = pipeline.trace("uh hello comma world period next one ");
+
+        let labels: Vec<&str> = trace.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(
+            labels,
+            ["fillers", "spoken_punctuation", "capitalize", "trim"]
+        );
+        assert_eq!(trace[0].1, "hello comma world period next one");
+        assert_eq!(trace[3].1, "Hello, world. Next one");
+        assert_eq!(
+            pipeline.apply("uh hello comma world period next one "),
+            trace[3].1
+        );
+    }
+
+    #[test]
+    fn test_identifiers_after_spoken_punctuation() {
+        let steps = [Step::SpokenPunctuation, Step::Identifiers];
+        let pipeline = Pipeline::new(&steps, &SpokenConfig::default(), "en")
+            .unwrap()
+            .with_vocabulary(&["userID".to_string()]);
+        assert_eq!(
+            pipeline.apply("call camel user id end comma snake retry count end"),
+            "call userID, retry_count"
+        );
+    }
+
+    #[test]
+    fn test_llm_mode_override_adds_step() {
+        // Nothing listens on a port that was just freed, so the step falls back to
+        // its input
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!(
+            "http://{}/v1/chat/completions",
+            listener.local_addr().unwrap()
+        );
+        drop(listener);
+        let llm = LlmConfig {
+            endpoint,
+            ..LlmConfig::default()
+        };
+        let labels = |pipeline: &Pipeline| -> Vec<String> {
+            pipeline
+                .trace("x")
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect()
+        };
+
+        let pipeline = Pipeline::new(&[Step::Trim], &SpokenConfig::default(), "en").unwrap();
+        assert_eq!(labels(&pipeline.with_llm(&llm, None)), ["trim"]);
+
+        let pipeline = Pipeline::new(&[Step::Trim], &SpokenConfig::default(), "en")
+            .unwrap()
+            .with_llm(&llm, Some(LlmMode::Bullets));
+        assert_eq!(labels(&pipeline), ["trim", "llm (bullets)"]);
+        assert_eq!(pipeline.apply(" a  b "), "a b");
+
+        let steps = [Step::Llm { mode: None }, Step::Trim];
+        let pipeline = Pipeline::new(&steps, &SpokenConfig::default(), "en")
+            .unwrap()
+            .with_llm(&llm, Some(LlmMode::Commit));
+        assert_eq!(labels(&pipeline), ["llm (commit)", "trim"]);
+        assert_eq!(labels(&pipeline.without_llm()), ["trim"]);
+    }
+
+    #[test]
+    fn test_steps_from_toml() {
+        #[derive(Deserialize)]
+        struct Section {
+            steps: Vec<Step>,
+        }
+        let section: Section = toml::from_str(
+            r#"steps = [
+                { type = "trim" },
+                { type = "fillers" },
+                { type = "fillers", words = ["like"] },
+                { type = "replace", pattern = "a", replacement = "b" },
+                { type = "spoken_punctuation" },
+                { type = "identifiers" },
+                { type = "itn" },
+                { type = "llm", mode = "email" },
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(section.steps[0], Step::Trim);
+        assert_eq!(
+            section.steps[1],
+            Step::Fillers {
+                words: default_fillers()
+            }
+        );
+        assert_eq!(
+            section.steps[2],
+            Step::Fillers {
+                words: vec!["like".to_string()]
+            }
+        );
+        assert_eq!(section.steps[4], Step::SpokenPunctuation);
+        assert_eq!(section.steps[5], Step::Identifiers);
+        assert_eq!(section.steps[6], Step::Itn);
+        assert_eq!(
+            section.steps[7],
+            Step::Llm {
+                mode: Some(LlmMode::Email)
+            }
+        );
+    }
+}
@@ -0,0 +1,525 @@
+//! Spoken commands: punctuation, line breaks and formatting dictated as words
+//!
+//! "hello comma world new line" becomes "hello, world\n". Phrases come from a
+//! built-in vocabulary for the transcription language, extended or overridden per
+//! language in `[postprocess.spoken.vocab.<lang>]`. Saying the escape word
+//! ("literal" in English) before a phrase keeps it as text.
+//!
+//! Whisper often punctuates around a dictated symbol ("hello, comma, world"), so the
+//! punctuation it added next to a spoken symbol is replaced rather than kept.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Spoken command settings (`[postprocess.spoken]`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpokenConfig {
+    /// Vocabulary language (None = `model.language`)
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Word that makes the following phrase literal text (None = language default)
+    #[serde(default)]
+    pub escape_word: Option<String>,
+    /// Extra phrases per language code. An empty string removes a built-in phrase.
+    #[serde(default)]
+    pub vocab: HashMap<String, BTreeMap<String, VocabEntry>>,
+}
+
+/// What a configured phrase does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VocabEntry {
+    /// Text to insert, spaced by what it looks like: closing punctuation attaches to
+    /// the previous word, opening brackets to the next, line breaks start a new line
+    Text(String),
+    /// Text with explicit spacing
+    Symbol { text: String, glue: Glue },
+    /// A formatting command
+    Action { action: Action },
+}
+
+/// Which neighbours an inserted symbol joins without a space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Glue {
+    None,
+    Left,
+    Right,
+    Both,
+}
+
+/// Commands that change the text around them instead of inserting a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Uppercase the next word
+    AllCaps,
+    /// Lowercase the next word
+    NoCaps,
+    /// Join the next word to the previous one
+    NoSpace,
+    /// Delete back to the end of the previous sentence or line
+    ScratchThat,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Insert(String, Glue),
+    Break(String),
+    Action(Action),
+}
+
+impl From<&VocabEntry> for Command {
+    fn from(entry: &VocabEntry) -> Self {
+        match entry {
+            VocabEntry::Text(text) => infer(text),
+            VocabEntry::Symbol { text, glue } => Command::Insert(text.clone(), *glue),
+            VocabEntry::Action { action } => Command::Action(*action),
+        }
+    }
+}
+
+/// Spacing for a plain text entry
+fn infer(text: &str) -> Command {
+    if text.contains('\n') && text.trim().is_empty() {
+        return Command::Break(text.to_string());
+    }
+    let glue = if text.starts_with([',', '.', ';', ':', '?', '!', ')', ']', '}', '%']) {
+        Glue::Left
+    } else if text.ends_with(['(', '[', '{']) {
+        Glue::Right
+    } else {
+        Glue::None
+    };
+    Command::Insert(text.to_string(), glue)
+}
+
+/// Built-in phrase: plain text (spacing inferred), glued text or an action
+enum Builtin {
+    Text(&'static str),
+    Glued(&'static str, Glue),
+    Act(Action),
+}
+
+use Builtin::{Act, Glued, Text};
+
+const EN: &[(&str, Builtin)] = &[
+    ("comma", Text(",")),
+    ("period", Text(".")),
+    ("full stop", Text(".")),
+    ("question mark", Text("?")),
+    ("exclamation mark", Text("!")),
+    ("exclamation point", Text("!")),
+    ("colon", Text(":")),
+    ("semicolon", Text(";")),
+    ("ellipsis", Text("...")),
+    ("new line", Text("\n")),
+    ("newline", Text("\n")),
+    ("new paragraph", Text("\n\n")),
+    ("open paren", Text("(")),
+    ("close paren", Text(")")),
+    ("open parenthesis", Text("(")),
+    ("close parenthesis", Text(")")),
+    ("open bracket", Text("[")),
+    ("close bracket", Text("]")),
+    ("open brace", Text("{")),
+    ("close brace", Text("}")),
+    ("open quote", Glued("\"", Glue::Right)),
+    ("close quote", Glued("\"", Glue::Left)),
+    ("apostrophe", Glued("'", Glue::Both)),
+    ("hyphen", Glued("-", Glue::Both)),
+    ("dash", Text("-")),
+    ("slash", Glued("/", Glue::Both)),
+    ("underscore", Glued("_", Glue::Both)),
+    ("dot", Glued(".", Glue::Both)),
+    ("at sign", Glued("@", Glue::Both)),
+    ("hash sign", Glued("#", Glue::Right)),
+    ("percent sign", Text("%")),
+    ("ampersand", Text("&")),
+    ("asterisk", Text("*")),
+    ("equals sign", Text("=")),
+    ("plus sign", Text("+")),
+    ("backtick", Glued("`", Glue::Both)),
+    ("all caps", Act(Action::AllCaps)),
+    ("no caps", Act(Action::NoCaps)),
+    ("no space", Act(Action::NoSpace)),
+    ("scratch that", Act(Action::ScratchThat)),
+];
+
+const DE: &[(&str, Builtin)] = &[
+    ("komma", Text(",")),
+    ("punkt", Text(".")),
+    ("fragezeichen", Text("?")),
+    ("ausrufezeichen", Text("!")),
+    ("doppelpunkt", Text(":")),
+    ("semikolon", Text(";")),
+    ("neue zeile", Text("\n")),
+    ("neuer absatz", Text("\n\n")),
+    ("klammer auf", Text("(")),
+    ("klammer zu", Text(")")),
+    ("anführungszeichen oben", Glued("\"", Glue::Right)),
+    ("anführungszeichen unten", Glued("\"", Glue::Left)),
+    ("bindestrich", Glued("-", Glue::Both)),
+    ("schrägstrich", Glued("/", Glue::Both)),
+    ("unterstrich", Glued("_", Glue::Both)),
+    ("alles groß", Act(Action::AllCaps)),
+    ("alles klein", Act(Action::NoCaps)),
+    ("kein leerzeichen", Act(Action::NoSpace)),
+    ("lösch das", Act(Action::ScratchThat)),
+];
+
+const ES: &[(&str, Builtin)] = &[
+    ("coma", Text(",")),
+    ("punto", Text(".")),
+    ("punto y coma", Text(";")),
+    ("dos puntos", Text(":")),
+    ("signo de interrogación", Text("?")),
+    ("signo de exclamación", Text("!")),
+    ("nueva línea", Text("\n")),
+    ("nuevo párrafo", Text("\n\n")),
+    ("abrir paréntesis", Text("(")),
+    ("cerrar paréntesis", Text(")")),
+    ("abrir comillas", Glued("\"", Glue::Right)),
+    ("cerrar comillas", Glued("\"", Glue::Left)),
+    ("guion", Glued("-", Glue::Both)),
+    ("barra", Glued("/", Glue::Both)),
+    ("guion bajo", Glued("_", Glue::Both)),
+    ("todo mayúsculas", Act(Action::AllCaps)),
+    ("todo minúsculas", Act(Action::NoCaps)),
+    ("sin espacio", Act(Action::NoSpace)),
+    ("borra eso", Act(Action::ScratchThat)),
+];
+
+const FR: &[(&str, Builtin)] = &[
+    ("virgule", Text(",")),
+    ("point", Text(".")),
+    ("point-virgule", Text(";")),
+    ("deux points", Text(":")),
+    ("point d'interrogation", Text("?")),
+    ("point d'exclamation", Text("!")),
+    ("nouvelle ligne", Text("\n")),
+    ("à la ligne", Text("\n")),
+    ("nouveau paragraphe", Text("\n\n")),
+    ("ouvrir la parenthèse", Text("(")),
+    ("fermer la parenthèse", Text(")")),
+    ("ouvrir les guillemets", Glued("\"", Glue::Right)),
+    ("fermer les guillemets", Glued("\"", Glue::Left)),
+    ("trait d'union", Glued("-", Glue::Both)),
+    ("barre oblique", Glued("/", Glue::Both)),
+    ("tiret bas", Glued("_", Glue::Both)),
+    ("tout en majuscules", Act(Action::AllCaps)),
+    ("tout en minuscules", Act(Action::NoCaps)),
+    ("sans espace", Act(Action::NoSpace)),
+    ("efface ça", Act(Action::ScratchThat)),
+];
+
+/// Built-in vocabulary and escape word for a language; English for unknown codes
+fn builtin(language: &str) -> (&'static str, &'static [(&'static str, Builtin)]) {
+    match language {
+        "de" => ("wörtlich", DE),
+        "es" => ("literal", ES),
+        "fr" => ("littéralement", FR),
+        _ => ("literal", EN),
+    }
+}
+
+/// Phrase words as matched: lowercase, without trailing punctuation
+fn phrase_key(phrase: &str) -> Vec<String> {
+    phrase.split_whitespace().map(super::bare).collect()
+}
+
+/// The vocabulary for one language, ready to match
+#[derive(Debug)]
+pub(super) struct Grammar {
+    /// Longest phrases first, so "punto y coma" wins over "punto"
+    phrases: Vec<(Vec<String>, Command)>,
+    escape: String,
+}
+
+impl Grammar {
+    pub(super) fn new(config: &SpokenConfig, model_language: &str) -> Self {
+        let language = config.language.as_deref().unwrap_or(model_language);
+        let (escape, table) = builtin(language);
+
+        let mut phrases: BTreeMap<Vec<String>, Command> = table
+            .iter()
+            .map(|(phrase, builtin)| {
+                let command = match builtin {
+                    Text(text) => infer(text),
+                    Glued(text, glue) => Command::Insert(text.to_string(), *glue),
+                    Act(action) => Command::Action(*action),
+                };
+                (phrase_key(phrase), command)
+            })
+            .collect();
+        for (phrase, entry) in config.vocab.get(language).into_iter().flatten() {
+            let key = phrase_key(phrase);
+            if key.is_empty() {
+                continue;
+            }
+            match entry {
+                VocabEntry::Text(text) if text.is_empty() => phrases.remove(&key),
+                entry => phrases.insert(key, entry.into()),
+            };
+        }
+
+        let mut phrases: Vec<_> = phrases.into_iter().collect();
+        phrases.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+        Self {
+            phrases,
+            escape: config
+                .escape_word
+                .as_deref()
+                .unwrap_or(escape)
+                .to_lowercase(),
+        }
+    }
+
+    /// The phrase starting at word `i`, if any
+    fn matching(&self, keys: &[String], i: usize) -> Option<&(Vec<String>, Command)> {
+        self.phrases
+            .iter()
+            .find(|(phrase, _)| keys.get(i..i + phrase.len()) == Some(phrase.as_slice()))
+    }
+
+    /// Apply spoken commands in `text`, line by line
+    pub(super) fn apply(&self, text: &str) -> String {
+        let mut writer = Writer::default();
+        for (n, line) in text.split('\n').enumerate() {
+            if n > 0 {
+                writer.line_break("\n");
+            }
+            self.apply_line(line, &mut writer);
+        }
+        writer.out
+    }
+
+    fn apply_line(&self, line: &str, writer: &mut Writer) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let keys: Vec<String> = words.iter().map(|w| super::bare(w)).collect();
+
+        let mut i = 0;
+        while i < words.len() {
+            if keys[i] == self.escape {
+                if let Some((phrase, _)) = self.matching(&keys, i + 1) {
+                    for word in &words[i + 1..i + 1 + phrase.len()] {
+                        writer.word(word);
+                    }
+                    i += 1 + phrase.len();
+                    continue;
+                }
+            }
+
+            match self.matching(&keys, i) {
+                Some((phrase, command)) => {
+                    writer.command(command);
+                    i += phrase.len();
+                },
+                None => {
+                    writer.word(words[i]);
+                    i += 1;
+                },
+            }
+        }
+    }
+}
+
+/// Case change waiting for the next word
+#[derive(Debug, Clone, Copy)]
+enum Case {
+    Upper,
+    Lower,
+}
+
+/// Output being assembled, with spacing state between words and commands
+#[derive(Debug, Default)]
+struct Writer {
+    out: String,
+    /// The next word joins the previous output without a space
+    glue_next: bool,
+    case_next: Option<Case>,
+}
+
+impl Writer {
+    fn separate(&mut self) {
+        if !self.glue_next && !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push(' ');
+        }
+        self.glue_next = false;
+    }
+
+    fn word(&mut self, word: &str) {
+        self.separate();
+        match self.case_next.take() {
+            Some(Case::Upper) => self.out.push_str(&word.to_uppercase()),
+            Some(Case::Lower) => self.out.push_str(&word.to_lowercase()),
+            None => self.out.push_str(word),
+        }
+    }
+
+    /// Drop trailing spaces, and Whisper's own punctuation when `punct` is set
+    fn trim_end(&mut self, punct: bool) {
+        let kept = self
+            .out
+            .trim_end_matches(|c| c == ' ' || (punct && super::is_trailing_punct(c)))
+            .len();
+        self.out.truncate(kept);
+    }
+
+    fn line_break(&mut self, text: &str) {
+        self.trim_end(false);
+        self.out.push_str(text);
+        self.glue_next = false;
+    }
+
+    fn command(&mut self, command: &Command) {
+        match command {
+            Command::Insert(text, glue) => {
+                if matches!(glue, Glue::Left | Glue::Both) {
+                    self.trim_end(text.starts_with(super::is_trailing_punct));
+                    self.out.push_str(text);
+                } else {
+                    self.separate();
+                    self.out.push_str(text);
+                }
+                self.glue_next = matches!(glue, Glue::Right | Glue::Both);
+            },
+            Command::Break(text) => self.line_break(text),
+            Command::Action(Action::AllCaps) => self.case_next = Some(Case::Upper),
+            Command::Action(Action::NoCaps) => self.case_next = Some(Case::Lower),
+            Command::Action(Action::NoSpace) => self.glue_next = true,
+            Command::Action(Action::ScratchThat) => {
+                self.trim_end(true);
+                self.out.truncate(sentence_start(&self.out));
+                self.glue_next = false;
+            },
+        }
+    }
+}
+
+/// Offset just past the last sentence end in `text`: '!', '?', a line break, or a '.'
+/// followed by whitespace or the end (so the point in "1.2" is not one)
+fn sentence_start(text: &str) -> usize {
+    text.char_indices()
+        .rev()
+        .find(|&(i, c)| match c {
+            '!' | '?' | '\n' => true,
+            '.' => text[i + 1..].chars().next().is_none_or(char::is_whitespace),
+            _ => false,
+        })
+        .map_or(0, |(i, _)| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english(text: &str) -> String {
+        Grammar::new(&SpokenConfig::default(), "en").apply(text)
+    }
+
+    #[test]
+    fn test_punctuation_and_breaks() {
+        let cases = [
+            ("hello comma world period", "hello, world."),
+            ("Hello, comma, world. Period.", "Hello, world."),
+            ("is it done question mark", "is it done?"),
+            ("wow exclamation point", "wow!"),
+            ("dear team colon new line thanks", "dear team:\nthanks"),
+            ("first new paragraph second", "first\n\nsecond"),
+            ("a full stop here", "a. here"),
+            ("kept\nline breaks comma too", "kept\nline breaks, too"),
+            // Words that only contain a phrase are untouched
+            (
+                "the commander's period-drama",
+                "the commander's period-drama",
+            ),
+            ("no symbols at all", "no symbols at all"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(english(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_symbols_and_spacing() {
+        let cases = [
+            ("call it open paren later close paren", "call it (later)"),
+            ("open quote hi close quote she said", "\"hi\" she said"),
+            (
+                "mail me at ops at sign example dot com",
+                "mail me at ops@example.com",
+            ),
+            ("read write slash execute", "read write/execute"),
+            ("snake underscore case", "snake_case"),
+            ("fix colon handle empty input", "fix: handle empty input"),
+            ("a dash b", "a - b"),
+            ("user no space name", "username"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(english(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_formatting_commands() {
+        assert_eq!(english("set all caps todo here"), "set TODO here");
+        assert_eq!(english("no caps NASA lowered"), "nasa lowered");
+        // The command ends the phrase it sits in, including Whisper's punctuation
+        assert_eq!(
+            english("Ship it today. I meant, scratch that. Tomorrow works."),
+            "Ship it today. Tomorrow works."
+        );
+        assert_eq!(english("scratch that hello"), "hello");
+        // Decimal points are not sentence ends
+        assert_eq!(
+            english("Version 1.2 is out. It costs 3.5 dollars, scratch that. It is free."),
+            "Version 1.2 is out. It is free."
+        );
+        assert_eq!(english("set it to 0.5 scratch that 0.7"), "0.7");
+        assert_eq!(
+            english("line one new line oops scratch that two"),
+            "line one\ntwo"
+        );
+    }
+
+    #[test]
+    fn test_escape_word() {
+        assert_eq!(english("type literal comma here"), "type comma here");
+        assert_eq!(english("say literal new line twice"), "say new line twice");
+        // Without a phrase after it, the escape word is just a word
+        assert_eq!(english("a literal translation"), "a literal translation");
+    }
+
+    #[test]
+    fn test_languages_and_overrides() {
+        let german = Grammar::new(&SpokenConfig::default(), "de");
+        assert_eq!(german.apply("Hallo Komma Welt Punkt"), "Hallo, Welt.");
+        assert_eq!(german.apply("wörtlich Punkt"), "Punkt");
+
+        let spanish = Grammar::new(&SpokenConfig::default(), "es");
+        assert_eq!(spanish.apply("uno punto y coma dos punto"), "uno; dos.");
+
+        // Unknown languages fall back to English
+        assert_eq!(
+            Grammar::new(&SpokenConfig::default(), "xx").apply("a comma b"),
+            "a, b"
+        );
+
+        let config: SpokenConfig = toml::from_str(
+            r#"
+            language = "en"
+            escape_word = "verbatim"
+            [vocab.en]
+            "arrow" = "->"
+            "smiley" = { text = ":)", glue = "left" }
+            "shout" = { action = "all_caps" }
+            "dot" = ""
+            "#,
+        )
+        .unwrap();
+        let grammar = Grammar::new(&config, "de");
+        assert_eq!(grammar.apply("a arrow b smiley shout yes"), "a -> b:) YES");
+        assert_eq!(grammar.apply("verbatim comma dot"), "comma dot");
+    }
+}
@@ -59,6 +59,19 @@ pub fn get_history_file() -> Result<PathBuf> {
     Ok(get_data_dir()?.join("history.jsonl"))
 }
 
+/// Get the directory for saved transcripts (~/.local/share/mojovoice/transcripts)
+pub fn get_transcripts_dir() -> Result<PathBuf> {
+    let dir = get_data_dir()?.join("transcripts");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// A new timestamped transcript file path, e.g. `meeting-20260301-142500.txt`
+pub fn new_transcript_path(kind: &str) -> Result<PathBuf> {
+    let name = format!("{}-{}.txt", kind, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    Ok(get_transcripts_dir()?.join(name))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,8 +1,24 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
 pub mod candle_engine;
 mod mojo_ffi;
 
+use crate::audio::vad::{self, VadParams};
+
+/// Whisper input sample rate
+const SAMPLE_RATE: f64 = 16000.0;
+
+/// A timed piece of a transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    /// Start time in seconds from the beginning of the audio
+    pub start_secs: f64,
+    /// End time in seconds from the beginning of the audio
+    pub end_secs: f64,
+    pub text: String,
+}
+
 /// Trait to abstract transcription engines
 pub trait Transcriber: Send + Sync {
     /// Transcribe 16kHz mono f32 audio data to text
@@ -10,4 +26,67 @@ pub trait Transcriber: Send + Sync {
     /// Note: `&mut self` is required for Candle's stateful encoder/decoder forward passes.
     /// The model maintains internal state during inference that must be mutated.
     fn transcribe(&mut self, audio: &[f32]) -> Result<String>;
+
+    /// Transcribe 16kHz mono f32 audio into timed segments
+    ///
+    /// The default implementation splits the audio at pauses (energy VAD) and
+    /// transcribes each speech region on its own, so timing is region-accurate.
+    fn transcribe_segments(&mut self, audio: &[f32]) -> Result<Vec<Segment>> {
+        let mut segments = Vec::new();
+
+        for region in vad::detect_speech(audio, &VadParams::default()) {
+            let text = self.transcribe(&audio[region.start..region.end])?;
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            segments.push(Segment {
+                start_secs: region.start as f64 / SAMPLE_RATE,
+                end_secs: region.end as f64 / SAMPLE_RATE,
+                text: text.to_string(),
+            });
+        }
+
+        Ok(segments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reports the length of each chunk it is asked to transcribe
+    struct LengthTranscriber;
+
+    impl Transcriber for LengthTranscriber {
+        fn transcribe(&mut self, audio: &[f32]) -> Result<String> {
+            Ok(format!("{} samples", audio.len()))
+        }
+    }
+
+    #[test]
+    fn test_transcribe_segments_times_speech_regions() {
+        let tone = |secs: f32| -> Vec<f32> {
+            (0..(secs * 16000.0) as usize)
+                .map(|i| (i as f32 * 0.1).sin() * 0.3)
+                .collect()
+        };
+        let silence = |secs: f32| vec![0.0f32; (secs * 16000.0) as usize];
+        let audio = [silence(1.0), tone(1.0), silence(2.0), tone(1.0)].concat();
+
+        let segments = LengthTranscriber.transcribe_segments(&audio).unwrap();
+
+        assert_eq!(segments.len(), 2);
+        assert!(
+            (segments[0].start_secs - 0.8).abs() < 0.05,
+            "{:?}",
+            segments
+        );
+        assert!(
+            (segments[1].start_secs - 3.8).abs() < 0.05,
+            "{:?}",
+            segments
+        );
+        assert!(segments[0].end_secs <= segments[1].start_secs);
+    }
 }
@@ -97,6 +97,11 @@ impl VocabStore {
         Ok(entries)
     }
 
+    /// All terms, most used first
+    pub fn terms(&self) -> Result<Vec<String>> {
+        Ok(self.list_terms()?.into_iter().map(|e| e.term).collect())
+    }
+
     /// Increment the use_count for the given term. No-op if the term doesn't exist.
     #[allow(dead_code)]
     pub fn increment_use_count(&self, term: &str) -> Result<()> {
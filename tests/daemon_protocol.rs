@@ -2,7 +2,13 @@
 //!
 //! Tests request/response serialization, error handling, and protocol contracts.
 
-use mojovoice::daemon::protocol::{DaemonRequest, DaemonResponse};
+use mojovoice::audio::health::DeviceState;
+use mojovoice::audio::levels::AudioLevels;
+use mojovoice::daemon::protocol::{DaemonRequest, DaemonResponse, TranscribeOptions};
+use mojovoice::output::window::FocusedWindow;
+use mojovoice::postprocess::LlmMode;
+use mojovoice::postprocess::identifier::IdentifierCase;
+use mojovoice::transcribe::Segment;
 
 #[test]
 fn test_request_ping_serialization() {
@@ -18,13 +24,39 @@ fn test_request_ping_serialization() {
 
 #[test]
 fn test_request_start_recording_serialization() {
-    let request = DaemonRequest::StartRecording { max_duration: 300 };
+    let request = DaemonRequest::StartRecording {
+        max_duration: 300,
+        code: None,
+        llm: None,
+    };
     let json = serde_json::to_string(&request).unwrap();
+    assert_eq!(json, r#"{"type":"start_recording","max_duration":300}"#);
     let parsed: DaemonRequest = serde_json::from_str(&json).unwrap();
 
     match parsed {
-        DaemonRequest::StartRecording { max_duration } => {
+        DaemonRequest::StartRecording {
+            max_duration,
+            code,
+            llm,
+        } => {
             assert_eq!(max_duration, 300);
+            assert_eq!(code, None);
+            assert_eq!(llm, None);
+        },
+        _ => panic!("Expected StartRecording variant"),
+    }
+}
+
+#[test]
+fn test_request_start_recording_with_options() {
+    let json = r#"{"type":"start_recording","max_duration":60,
+        "code":"screaming_snake","llm":"commit"}"#;
+    let parsed: DaemonRequest = serde_json::from_str(json).unwrap();
+
+    match parsed {
+        DaemonRequest::StartRecording { code, llm, .. } => {
+            assert_eq!(code, Some(IdentifierCase::ScreamingSnake));
+            assert_eq!(llm, Some(LlmMode::Commit));
         },
         _ => panic!("Expected StartRecording variant"),
     }
@@ -32,16 +64,39 @@ fn test_request_start_recording_serialization() {
 
 #[test]
 fn test_request_stop_recording_serialization() {
-    let request = DaemonRequest::StopRecording;
+    let request = DaemonRequest::StopRecording { window: None };
     let json = serde_json::to_string(&request).unwrap();
+    assert_eq!(json, r#"{"type":"stop_recording"}"#);
     let parsed: DaemonRequest = serde_json::from_str(&json).unwrap();
 
     match parsed {
-        DaemonRequest::StopRecording => {}, // Success
+        DaemonRequest::StopRecording { window: None } => {}, // Success
         _ => panic!("Expected StopRecording variant"),
     }
 }
 
+#[test]
+fn test_request_stop_recording_with_window() {
+    let request = DaemonRequest::StopRecording {
+        window: Some(FocusedWindow {
+            class: "kitty".to_string(),
+            title: "~/src".to_string(),
+        }),
+    };
+    let json = serde_json::to_string(&request).unwrap();
+    assert_eq!(
+        json,
+        r#"{"type":"stop_recording","window":{"class":"kitty","title":"~/src"}}"#
+    );
+
+    match serde_json::from_str(&json).unwrap() {
+        DaemonRequest::StopRecording {
+            window: Some(window),
+        } => assert_eq!(window.class, "kitty"),
+        _ => panic!("Expected StopRecording variant with a window"),
+    }
+}
+
 #[test]
 fn test_request_shutdown_serialization() {
     let request = DaemonRequest::Shutdown;
@@ -75,6 +130,94 @@ fn test_request_transcribe_audio_serialization() {
     }
 }
 
+#[test]
+fn test_request_transcribe_segments_serialization() {
+    let json = serde_json::to_string(&DaemonRequest::TranscribeSegments {
+        samples: vec![0.5],
+    })
+    .unwrap();
+    assert_eq!(json, r#"{"type":"transcribe_segments","samples":[0.5]}"#);
+}
+
+#[test]
+fn test_request_push_to_talk_serialization() {
+    let json = serde_json::to_string(&DaemonRequest::PushToTalkPress { max_duration: 60 }).unwrap();
+    assert_eq!(json, r#"{"type":"push_to_talk_press","max_duration":60}"#);
+
+    let parsed: DaemonRequest = serde_json::from_str(r#"{"type":"push_to_talk_release"}"#).unwrap();
+    assert!(matches!(
+        parsed,
+        DaemonRequest::PushToTalkRelease { window: None }
+    ));
+}
+
+#[test]
+fn test_request_transcribe_file_serialization() {
+    let request = DaemonRequest::TranscribeFile {
+        path: "/tmp/memo.wav".into(),
+        options: TranscribeOptions {
+            save_history: true,
+            timestamps: true,
+            denoise: Some(true),
+            postprocess: true,
+        },
+    };
+    let json = serde_json::to_string(&request).unwrap();
+    let parsed: DaemonRequest = serde_json::from_str(&json).unwrap();
+
+    match parsed {
+        DaemonRequest::TranscribeFile { path, options } => {
+            assert_eq!(path, std::path::Path::new("/tmp/memo.wav"));
+            assert!(options.save_history);
+            assert!(options.timestamps);
+            assert_eq!(options.denoise, Some(true));
+            assert!(options.postprocess);
+        },
+        _ => panic!("Expected TranscribeFile variant"),
+    }
+}
+
+#[test]
+fn test_request_transcribe_file_options_default() {
+    // External tools may omit options entirely
+    let json = r#"{"type":"transcribe_file","path":"/tmp/memo.wav"}"#;
+    let parsed: DaemonRequest = serde_json::from_str(json).unwrap();
+
+    match parsed {
+        DaemonRequest::TranscribeFile { options, .. } => {
+            assert!(!options.save_history);
+            assert!(!options.timestamps);
+            assert_eq!(options.denoise, None);
+            assert!(!options.postprocess);
+        },
+        _ => panic!("Expected TranscribeFile variant"),
+    }
+}
+
+#[test]
+fn test_response_transcript_serialization() {
+    let response = DaemonResponse::Transcript {
+        text: "Hello world".to_string(),
+        segments: vec![Segment {
+            start_secs: 0.5,
+            end_secs: 1.75,
+            text: "Hello world".to_string(),
+        }],
+    };
+    let json = serde_json::to_string(&response).unwrap();
+    assert!(json.contains(r#""status":"transcript""#));
+
+    let parsed: DaemonResponse = serde_json::from_str(&json).unwrap();
+    match parsed {
+        DaemonResponse::Transcript { text, segments } => {
+            assert_eq!(text, "Hello world");
+            assert_eq!(segments.len(), 1);
+            assert_eq!(segments[0].end_secs, 1.75);
+        },
+        _ => panic!("Expected Transcript variant"),
+    }
+}
+
 #[test]
 fn test_response_ok_serialization() {
     let response = DaemonResponse::Ok {
@@ -187,3 +330,59 @@ fn test_response_with_unicode() {
         _ => panic!("Expected Success variant"),
     }
 }
+
+#[test]
+fn test_levels_requests_wire_format() {
+    let json = serde_json::to_string(&DaemonRequest::GetLevels).unwrap();
+    assert_eq!(json, r#"{"type":"get_levels"}"#);
+
+    let json = r#"{"type":"subscribe_levels","interval_ms":50}"#;
+    match serde_json::from_str(json).unwrap() {
+        DaemonRequest::SubscribeLevels { interval_ms } => assert_eq!(interval_ms, 50),
+        _ => panic!("Expected SubscribeLevels variant"),
+    }
+}
+
+#[test]
+fn test_levels_response_roundtrip() {
+    let response = DaemonResponse::Levels {
+        levels: AudioLevels {
+            active: true,
+            rms: 0.25,
+            peak: 0.5,
+            silent_ms: 1200,
+        },
+    };
+    let json = serde_json::to_string(&response).unwrap();
+    assert!(json.contains(r#""status":"levels""#));
+
+    match serde_json::from_str(&json).unwrap() {
+        DaemonResponse::Levels { levels } => {
+            assert!(levels.active);
+            assert_eq!(levels.rms, 0.25);
+            assert_eq!(levels.peak, 0.5);
+            assert_eq!(levels.silent_ms, 1200);
+        },
+        _ => panic!("Expected Levels variant"),
+    }
+}
+
+#[test]
+fn test_status_device_health() {
+    let json = r#"{"status":"status","model_name":"tiny","gpu_enabled":false,"gpu_name":"CPU","uptime_secs":5,"device":{"state":"fallback","device":"default","last_error":"device unplugged","reconnects":1}}"#;
+    match serde_json::from_str(json).unwrap() {
+        DaemonResponse::Status { device, .. } => {
+            assert_eq!(device.state, DeviceState::Fallback);
+            assert_eq!(device.device.as_deref(), Some("default"));
+            assert_eq!(device.reconnects, 1);
+        },
+        _ => panic!("Expected Status variant"),
+    }
+
+    // Older daemons don't send device health
+    let json = r#"{"status":"status","model_name":"tiny","gpu_enabled":false,"gpu_name":"CPU","uptime_secs":5}"#;
+    match serde_json::from_str(json).unwrap() {
+        DaemonResponse::Status { device, .. } => assert_eq!(device.state, DeviceState::Idle),
+        _ => panic!("Expected Status variant"),
+    }
+}
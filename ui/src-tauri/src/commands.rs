@@ -198,6 +198,54 @@ pub async fn get_daemon_status() -> Result<DaemonStatus, String> {
     }
 }
 
+/// Live input level for the recording meter
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioLevels {
+    pub active: bool,
+    pub rms: f32,
+    pub peak: f32,
+    pub silent_ms: u64,
+}
+
+/// Interval between `audio-levels` events
+const LEVELS_INTERVAL_MS: u32 = 100;
+
+/// Bumped by every (un)subscribe; a level stream ends once it no longer matches
+static LEVELS_GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Stream the daemon's input level to the window as `audio-levels` events, over one
+/// connection, until `unsubscribe_audio_levels` or a newer subscription ends it
+#[tauri::command]
+pub async fn subscribe_audio_levels(window: tauri::Window) -> Result<(), String> {
+    let generation = LEVELS_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    std::thread::spawn(move || {
+        let result = daemon_client::subscribe_levels(LEVELS_INTERVAL_MS, |levels| {
+            if LEVELS_GENERATION.load(Ordering::SeqCst) != generation {
+                return false;
+            }
+            let levels = AudioLevels {
+                active: levels.active,
+                rms: levels.rms,
+                peak: levels.peak,
+                silent_ms: levels.silent_ms,
+            };
+            window.emit("audio-levels", levels).is_ok()
+        });
+        if let Err(e) = result {
+            eprintln!("Audio level subscription ended: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Stop the `audio-levels` events
+#[tauri::command]
+pub async fn unsubscribe_audio_levels() -> Result<(), String> {
+    LEVELS_GENERATION.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
 /// Audio device information for the frontend
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -20,6 +20,8 @@ pub enum DaemonRequest {
     Ping,
     #[serde(rename = "get_status")]
     GetStatus,
+    #[serde(rename = "subscribe_levels")]
+    SubscribeLevels { interval_ms: u32 },
 }
 
 /// Response from daemon
@@ -43,6 +45,17 @@ pub enum DaemonResponse {
         #[serde(default)]
         uptime_secs: Option<u64>,
     },
+    #[serde(rename = "levels")]
+    Levels { levels: AudioLevels },
+}
+
+/// Input level snapshot (matches `mojovoice::audio::levels::AudioLevels`)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct AudioLevels {
+    pub active: bool,
+    pub rms: f32,
+    pub peak: f32,
+    pub silent_ms: u64,
 }
 
 /// Get the daemon socket path
@@ -136,6 +149,39 @@ pub fn get_status() -> Result<DaemonStatusInfo> {
     }
 }
 
+/// Subscribe to the daemon's input level over one connection, calling `on_levels` for
+/// each update until it returns false or the daemon goes away
+pub fn subscribe_levels(
+    interval_ms: u32,
+    mut on_levels: impl FnMut(AudioLevels) -> bool,
+) -> Result<()> {
+    let socket_path = get_socket_path()?;
+    let mut stream = UnixStream::connect(&socket_path)
+        .context("Failed to connect to daemon socket")?;
+
+    let request_json = serde_json::to_string(&DaemonRequest::SubscribeLevels { interval_ms })
+        .context("Failed to serialize request")?;
+    stream.write_all(request_json.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.flush()?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line.context("Lost connection to daemon")?;
+        match serde_json::from_str(line.trim()).context("Failed to parse daemon response")? {
+            DaemonResponse::Levels { levels } => {
+                if !on_levels(levels) {
+                    return Ok(());
+                }
+            }
+            DaemonResponse::Error { message } => {
+                anyhow::bail!("Failed to get audio levels: {}", message)
+            }
+            _ => anyhow::bail!("Unexpected response from daemon"),
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DaemonStatusInfo {
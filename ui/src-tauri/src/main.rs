@@ -50,6 +50,8 @@ fn main() {
             commands::get_storage_info,
             // Audio devices
             commands::list_audio_devices,
+            commands::subscribe_audio_levels,
+            commands::unsubscribe_audio_levels,
             // Vocabulary management
             commands::vocab_list,
             commands::vocab_add,
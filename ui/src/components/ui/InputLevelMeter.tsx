@@ -0,0 +1,77 @@
+import { useEffect, useState } from 'react';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { invoke } from '../../lib/ipc';
+
+interface AudioLevels {
+  active: boolean;
+  rms: number;
+  peak: number;
+  silentMs: number;
+}
+
+/** Silence longer than this while recording suggests a muted or wrong mic */
+const SILENT_WARN_MS = 3000;
+
+/** Map linear amplitude to 0..100 on a -60..0 dBFS scale */
+function toPercent(amplitude: number): number {
+  if (amplitude <= 0) return 0;
+  const db = 20 * Math.log10(amplitude);
+  return Math.min(100, Math.max(0, ((db + 60) / 60) * 100));
+}
+
+export default function InputLevelMeter() {
+  const [levels, setLevels] = useState<AudioLevels | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    let unlisten: UnlistenFn | null = null;
+
+    // The daemon pushes levels over one subscription instead of being polled
+    const subscribe = async () => {
+      try {
+        unlisten = await listen<AudioLevels>('audio-levels', (event) => {
+          if (!cancelled) setLevels(event.payload);
+        });
+        if (cancelled) {
+          unlisten();
+          return;
+        }
+        await invoke('subscribe_audio_levels');
+      } catch {
+        // daemon unavailable; the meter stays hidden
+      }
+    };
+    subscribe();
+
+    return () => {
+      cancelled = true;
+      if (unlisten) unlisten();
+      invoke('unsubscribe_audio_levels').catch(() => {});
+    };
+  }, []);
+
+  if (!levels?.active) return null;
+
+  const silent = levels.silentMs >= SILENT_WARN_MS;
+
+  return (
+    <div className="flex items-center gap-3" role="meter" aria-label="Input level"
+      aria-valuemin={0} aria-valuemax={100} aria-valuenow={Math.round(toPercent(levels.rms))}>
+      <div className="relative w-[160px] h-2 bg-[var(--bg-surface)] border border-[var(--border-default)] overflow-hidden">
+        <div
+          className="absolute inset-y-0 left-0 bg-[var(--success)] transition-[width] duration-75"
+          style={{ width: `${toPercent(levels.rms)}%` }}
+        />
+        <div
+          className="absolute inset-y-0 w-[2px] bg-[var(--text-primary)]"
+          style={{ left: `${toPercent(levels.peak)}%` }}
+        />
+      </div>
+      {silent && (
+        <span className="text-xs text-red-400 font-ui" role="alert">
+          Mic silent - muted or wrong input?
+        </span>
+      )}
+    </div>
+  );
+}
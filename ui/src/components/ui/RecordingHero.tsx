@@ -1,5 +1,6 @@
 import { useState } from 'react';
 import { Button } from './Button';
+import InputLevelMeter from './InputLevelMeter';
 import { invoke } from '../../lib/ipc';
 import { useAppStore } from '../../stores/appStore';
 
@@ -70,6 +71,8 @@ export default function RecordingHero() {
             CANCEL
           </Button>
         )}
+
+        {isRecording && <InputLevelMeter />}
       </div>
 
       {/* Transcription result */}
@@ -15,6 +15,9 @@ const getMockData = (command: string, args?: Record<string, unknown>): any => {
         gpu_name: null,
         uptime_secs: null
       };
+    case 'subscribe_audio_levels':
+    case 'unsubscribe_audio_levels':
+      return null;
     case 'get_config':
       return {
         model: {